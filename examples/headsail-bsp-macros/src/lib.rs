@@ -0,0 +1,51 @@
+//! Attribute macros re-exported by `headsail-bsp`
+use proc_macro::{TokenStream, TokenTree};
+
+/// Registers a `fn() -> TestResult` with the `headsail_bsp::tb` test harness
+///
+/// The function is kept as is. A `TestCase` for it is placed in the
+/// `.headsail_tests` link section, from where `headsail_tests!()` picks up all
+/// the registered tests. Expands to paths into `headsail_bsp`, which must be
+/// a dependency under that name.
+#[proc_macro_attribute]
+pub fn headsail_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return compile_error("`#[headsail_test]` takes no arguments");
+    }
+    let Some(name) = fn_name(item.clone()) else {
+        return compile_error("`#[headsail_test]` must be applied to a function");
+    };
+
+    let registration = format!(
+        r#"
+        #[used]
+        #[link_section = ".headsail_tests"]
+        #[allow(non_upper_case_globals)]
+        static __headsail_test_{name}: ::headsail_bsp::tb::TestCase = ::headsail_bsp::tb::TestCase {{
+            name: "{name}",
+            function: {name},
+        }};
+        "#
+    );
+    let mut out = item;
+    out.extend(registration.parse::<TokenStream>().unwrap());
+    out
+}
+
+/// Returns the identifier following the `fn` keyword
+fn fn_name(item: TokenStream) -> Option<String> {
+    let mut tokens = item.into_iter();
+    while let Some(token) = tokens.next() {
+        if matches!(&token, TokenTree::Ident(i) if i.to_string() == "fn") {
+            return match tokens.next() {
+                Some(TokenTree::Ident(name)) => Some(name.to_string()),
+                _ => None,
+            };
+        }
+    }
+    None
+}
+
+fn compile_error(msg: &str) -> TokenStream {
+    format!("compile_error!({msg:?});").parse().unwrap()
+}
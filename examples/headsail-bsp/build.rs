@@ -16,6 +16,17 @@ fn main() {
             println!("cargo:rustc-env=RUSTFLAGS=-C link-arg=-Tmem_hpc.x -C link-arg=-Tlink.x");
         }
     }
+    // Only applies to the examples. Crates using the defmt logger need to pass
+    // `-Tdefmt.x` themselves. Build scripts see features only through the
+    // environment.
+    if env::var_os("CARGO_FEATURE_DEFMT_APB_UART0").is_some()
+        || env::var_os("CARGO_FEATURE_DEFMT_SYSCTRL_UART").is_some()
+    {
+        println!("cargo:rustc-link-arg-examples=-Tdefmt.x");
+    }
+    // Collects the tests registered with `#[headsail_test]`
+    fs::copy("tb.x", out.join("tb.x")).unwrap();
+    println!("cargo:rustc-link-arg-examples=-Ttb.x");
     fs::copy("sdram_hpc.x", out.join("sdram_hpc.x")).unwrap();
     fs::copy("mem_sysctrl.x", out.join("mem_sysctrl.x")).unwrap();
     println!("cargo:rustc-link-search={}", out.display());
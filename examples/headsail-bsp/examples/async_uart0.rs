@@ -0,0 +1,42 @@
+//! Echoes bytes over UART0 from an async task
+//!
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use core::ptr::addr_of_mut;
+
+use headsail_bsp::{
+    apb_uart::ApbUart0,
+    executor::{task, Executor},
+    riscv,
+    rt::entry,
+    sprintln,
+};
+
+static mut EXECUTOR: Option<Executor> = None;
+
+#[task]
+async fn echo(mut uart: ApbUart0) {
+    let mut buf = [0u8; 16];
+    loop {
+        let n = uart.read_async(&mut buf).await;
+        uart.write_async(&buf[..n]).await;
+    }
+}
+
+#[entry]
+fn main() -> ! {
+    let (soc_freq, baud) = (30_000_000, 115_200);
+    let uart = ApbUart0::init(soc_freq, baud);
+
+    sprintln!("Input characters to echo them back");
+
+    unsafe { riscv::interrupt::enable() };
+
+    // SAFETY: the executor is only ever accessed from `main`, which never returns
+    let executor = unsafe { (*addr_of_mut!(EXECUTOR)).insert(Executor::new()) };
+    executor.run(|spawner| spawner.must_spawn(echo(uart)))
+}
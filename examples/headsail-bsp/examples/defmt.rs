@@ -0,0 +1,43 @@
+//! Logs with deferred formatting over APB UART0
+//!
+//! Build with `DEFMT_LOG=trace` and decode with `scripts/defmt_print.sh`.
+//! `trace` is filtered out as only `-Flog-debug` is enabled.
+//!
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use headsail_bsp::{apb_uart::ApbUart0, log, riscv::register::mcycle, rt::entry};
+
+#[derive(defmt::Format)]
+struct Shape {
+    width: u16,
+    height: u16,
+    channels: u16,
+}
+
+#[entry]
+fn main() -> ! {
+    let (soc_freq, baud) = (30_000_000, 115_200);
+    let _uart = ApbUart0::init(soc_freq, baud);
+
+    log::info!("defmt example");
+    let shape = Shape {
+        width: 16,
+        height: 16,
+        channels: 16,
+    };
+    log::debug!("input {}", shape);
+
+    let start = mcycle::read64();
+    let sum: u32 = (0..1000).sum();
+    log::info!("sum {=u32} in {=u64} cycles", sum, mcycle::read64() - start);
+    log::trace!("not logged");
+    log::warn!("done");
+
+    loop {
+        continue;
+    }
+}
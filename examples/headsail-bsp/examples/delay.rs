@@ -0,0 +1,18 @@
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use headsail_bsp::{rt::entry, sprintln, time};
+
+#[entry]
+fn main() -> ! {
+    sprintln!("Delay example");
+    loop {
+        let start = time::now();
+        time::delay_ms(500);
+        let elapsed = time::now() - start;
+        sprintln!("Slept for {} us", elapsed.to_micros());
+    }
+}
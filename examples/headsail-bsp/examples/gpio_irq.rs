@@ -0,0 +1,55 @@
+//! Counts edges on APB GPIO pin 0 using the PLIC GPIO interrupt
+//!
+//! On the VP, toggle the pin from the monitor, e.g., `apb_gpio OnGPIO 0 true`.
+//!
+//! Assumes test is run on hart 0 with no other cores interfering.
+#![no_std]
+#![no_main]
+
+use core::sync::atomic::{AtomicU32, Ordering};
+use headsail_bsp::{
+    apb_gpio::{on_gpio_interrupt, ApbGpio, Edge},
+    rt::entry,
+    sprintln, Interrupt, Priority, PLIC,
+};
+
+static EDGES: AtomicU32 = AtomicU32::new(0);
+
+fn on_edge() {
+    EDGES.fetch_add(1, Ordering::Relaxed);
+}
+
+#[entry]
+fn main() -> ! {
+    let mut pin = ApbGpio::<0>::new().into_input();
+    pin.listen(Edge::Any, on_edge);
+
+    unsafe {
+        PLIC::priorities().set_priority(Interrupt::Gpio, Priority::P1);
+        PLIC::ctx0().enables().enable(Interrupt::Gpio);
+        riscv::register::mie::set_mext();
+        riscv::interrupt::enable();
+    };
+
+    sprintln!("Waiting for edges on GPIO 0");
+
+    let mut seen = 0;
+    loop {
+        riscv::asm::wfi();
+        let edges = EDGES.load(Ordering::Relaxed);
+        if edges != seen {
+            seen = edges;
+            sprintln!("edges: {}, level: {}", edges, pin.is_high() as u8);
+        }
+    }
+}
+
+#[export_name = "MachineExternal"]
+fn machine_external() {
+    if let Some(irq) = PLIC::ctx0().claim().claim::<Interrupt>() {
+        if let Interrupt::Gpio = irq {
+            on_gpio_interrupt();
+        }
+        PLIC::ctx0().claim().complete(irq);
+    }
+}
@@ -0,0 +1,50 @@
+//! Starts the secondary HARTs and synchronizes them with HART 0
+//!
+//! Run with `scripts/resc/2_run_hpc_smp.resc` to keep HARTs 1..=3 running.
+//!
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use headsail_bsp::{
+    riscv,
+    rt::entry,
+    smp::{self, Barrier},
+    sprintln, HartId,
+};
+
+static BARRIER: Barrier = Barrier::new(smp::HART_COUNT);
+
+fn secondary(arg: usize) -> ! {
+    let hart = smp::hart_id();
+    BARRIER.wait();
+
+    // Report back to HART 0
+    smp::send(HartId::H0, hart as usize * arg);
+
+    loop {
+        riscv::asm::wfi();
+    }
+}
+
+#[entry]
+fn main() -> ! {
+    sprintln!("SMP example");
+
+    for hart in [HartId::H1, HartId::H2, HartId::H3] {
+        smp::start_hart(hart, secondary, 10).unwrap();
+    }
+    BARRIER.wait();
+    sprintln!("All HARTs passed the barrier");
+
+    for _ in 1..smp::HART_COUNT {
+        let msg = smp::recv();
+        sprintln!("Received {}", msg);
+    }
+
+    loop {
+        riscv::asm::wfi();
+    }
+}
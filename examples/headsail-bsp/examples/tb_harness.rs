@@ -0,0 +1,45 @@
+//! Runs a few smoke tests with the `tb` test harness
+//!
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use headsail_bsp::{
+    headsail_tests,
+    riscv::register::mcycle,
+    tb::{headsail_test, TestResult},
+    tb_assert, tb_assert_eq,
+    timer::Timer0,
+};
+
+#[headsail_test]
+fn mcycle_advances() -> TestResult {
+    let start = mcycle::read64();
+    for _ in 0..100 {
+        core::hint::black_box(());
+    }
+    tb_assert!(mcycle::read64() > start);
+    Ok(())
+}
+
+#[headsail_test]
+fn timer0_counts() -> TestResult {
+    let start = Timer0::get_count();
+    Timer0::enable();
+    for _ in 0..10_000 {
+        core::hint::black_box(());
+    }
+    Timer0::disable();
+    tb_assert!(Timer0::get_count() != start, "timer0 did not count");
+    Ok(())
+}
+
+#[headsail_test]
+fn wrapping_add() -> TestResult {
+    tb_assert_eq!(u8::MAX.wrapping_add(1), 0);
+    Ok(())
+}
+
+headsail_tests!();
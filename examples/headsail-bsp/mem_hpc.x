@@ -9,3 +9,7 @@ REGION_ALIAS("REGION_DATA", BOOTRAM);
 REGION_ALIAS("REGION_BSS", BOOTRAM);
 REGION_ALIAS("REGION_HEAP", BOOTRAM);
 REGION_ALIAS("REGION_STACK", BOOTRAM);
+
+/* Give each of the four HPC HARTs a stack of its own */
+PROVIDE(_max_hart_id = 3);
+PROVIDE(_hart_stack_size = 2K);
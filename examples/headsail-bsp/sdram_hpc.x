@@ -9,3 +9,7 @@ REGION_ALIAS("REGION_DATA", SDRAM);
 REGION_ALIAS("REGION_BSS", SDRAM);
 REGION_ALIAS("REGION_HEAP", SDRAM);
 REGION_ALIAS("REGION_STACK", SDRAM);
+
+/* Give each of the four HPC HARTs a stack of its own */
+PROVIDE(_max_hart_id = 3);
+PROVIDE(_hart_stack_size = 64K);
@@ -1,3 +1,4 @@
+use core::sync::atomic::{AtomicBool, Ordering};
 use good_memory_allocator::SpinLockedAllocator;
 
 #[cfg(feature = "hpc")]
@@ -9,10 +10,38 @@ const HEAP_SIZE: usize = 0x10000000;
 #[global_allocator]
 static ALLOCATOR: SpinLockedAllocator = SpinLockedAllocator::empty();
 
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
 /// # Safety
 ///
 /// If the allocator was already initialized, this function will panic.
 #[inline]
 pub unsafe fn init_heap() {
-    ALLOCATOR.init(HEAP_START, HEAP_SIZE);
+    assert!(
+        init_heap_at(HEAP_START, HEAP_SIZE),
+        "heap already initialized"
+    );
+}
+
+/// Initializes the heap in `start..start + size` instead of the default
+/// region, e.g., to share memory with a C runtime that has its own `malloc`.
+///
+/// Returns `false` and leaves the allocator untouched if it was already
+/// initialized.
+///
+/// # Safety
+///
+/// The region must be valid for reads and writes, and not used for anything
+/// else for the rest of the program.
+pub unsafe fn init_heap_at(start: usize, size: usize) -> bool {
+    if INITIALIZED.swap(true, Ordering::AcqRel) {
+        return false;
+    }
+    ALLOCATOR.init(start, size);
+    true
+}
+
+/// Returns `true` once the heap has been initialized
+pub fn is_heap_initialized() -> bool {
+    INITIALIZED.load(Ordering::Acquire)
 }
@@ -0,0 +1,316 @@
+//! APB GPIO
+//!
+//! The ASIC implements the PULP APB GPIO with 32 pins. The VP models it with
+//! the CC2538 GPIO, which only has 8 pins.
+//!
+//! Edge interrupts are available on HPC, where the GPIO is wired to PLIC
+//! source [Interrupt::Gpio](crate::Interrupt::Gpio).
+use core::{convert::Infallible, marker::PhantomData};
+
+use crate::mmap::GPIO_ADDR;
+use crate::{read_u32, write_u32};
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin, StatefulOutputPin};
+use riscv::interrupt;
+
+#[cfg(feature = "vp")]
+use crate::mmap::{GPIO_DATA_OFS, GPIO_DIR_OFS};
+#[cfg(not(feature = "vp"))]
+use crate::mmap::{GPIO_PADDIR_OFS, GPIO_PADIN_OFS, GPIO_PADOUT_OFS};
+
+/// Type-state trait for GPIO in different states
+pub trait GpioState {}
+
+pub struct Uninit;
+impl GpioState for Uninit {}
+
+pub struct Input;
+impl GpioState for Input {}
+
+pub struct Output;
+impl GpioState for Output {}
+
+/// Pin `IDX` of the APB GPIO
+///
+/// Pins are not tracked for ownership. Make sure each pin is only used from
+/// one place.
+pub struct ApbGpio<const IDX: u32, State: GpioState = Uninit> {
+    _pd: PhantomData<State>,
+}
+
+#[cfg(not(feature = "vp"))]
+const DIR: usize = GPIO_ADDR + GPIO_PADDIR_OFS;
+#[cfg(feature = "vp")]
+const DIR: usize = GPIO_ADDR + GPIO_DIR_OFS;
+
+/// Read-modify-writes `bit` of `reg`
+fn modify_bit(reg: usize, bit: u32, set: bool) {
+    interrupt::free(|| {
+        let val = read_u32(reg);
+        write_u32(
+            reg,
+            if set {
+                val | (1 << bit)
+            } else {
+                val & !(1 << bit)
+            },
+        );
+    })
+}
+
+/// Returns the input levels of all pins
+#[inline]
+fn read_pins() -> u32 {
+    #[cfg(not(feature = "vp"))]
+    {
+        read_u32(GPIO_ADDR + GPIO_PADIN_OFS)
+    }
+    #[cfg(feature = "vp")]
+    {
+        read_u32(GPIO_ADDR + GPIO_DATA_OFS)
+    }
+}
+
+impl<const IDX: u32> ApbGpio<IDX, Uninit> {
+    pub fn new() -> Self {
+        Self { _pd: PhantomData }
+    }
+
+    pub fn into_input(self) -> ApbGpio<IDX, Input> {
+        modify_bit(DIR, IDX, false);
+        ApbGpio { _pd: PhantomData }
+    }
+
+    pub fn into_output(self) -> ApbGpio<IDX, Output> {
+        modify_bit(DIR, IDX, true);
+        ApbGpio { _pd: PhantomData }
+    }
+}
+
+impl<const IDX: u32> Default for ApbGpio<IDX, Uninit> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const IDX: u32> ApbGpio<IDX, Input> {
+    pub fn is_high(&self) -> bool {
+        read_pins() & (1 << IDX) != 0
+    }
+
+    pub fn is_low(&self) -> bool {
+        !self.is_high()
+    }
+}
+
+impl<const IDX: u32> ApbGpio<IDX, Output> {
+    fn write(&mut self, high: bool) {
+        #[cfg(not(feature = "vp"))]
+        modify_bit(GPIO_ADDR + GPIO_PADOUT_OFS, IDX, high);
+        // Address bits [2:9] select the pins affected by the write
+        #[cfg(feature = "vp")]
+        write_u32(GPIO_ADDR + ((1 << IDX) << 2), (high as u32) << IDX);
+    }
+
+    /// Returns the level the pin is driven to
+    fn is_set(&self) -> bool {
+        #[cfg(not(feature = "vp"))]
+        let out = read_u32(GPIO_ADDR + GPIO_PADOUT_OFS);
+        #[cfg(feature = "vp")]
+        let out = read_pins();
+        out & (1 << IDX) != 0
+    }
+
+    pub fn set_high(&mut self) {
+        self.write(true);
+    }
+
+    pub fn set_low(&mut self) {
+        self.write(false);
+    }
+
+    pub fn toggle(&mut self) {
+        self.write(!self.is_set());
+    }
+}
+
+impl<const IDX: u32, S: GpioState> ApbGpio<IDX, S> {
+    /// # Safety
+    ///
+    /// This will not configure the pin in any way (i.e., direction).
+    pub unsafe fn steal() -> Self {
+        Self { _pd: PhantomData }
+    }
+}
+
+#[cfg(feature = "hpc")]
+pub use irq::*;
+
+#[cfg(feature = "hpc")]
+mod irq {
+    use super::{modify_bit, ApbGpio, Input};
+    use crate::{mmap::GPIO_ADDR, read_u32};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[cfg(feature = "vp")]
+    use crate::{
+        mmap::{GPIO_IBE_OFS, GPIO_IC_OFS, GPIO_IEV_OFS, GPIO_IE_OFS, GPIO_IS_OFS, GPIO_MIS_OFS},
+        write_u32,
+    };
+    #[cfg(not(feature = "vp"))]
+    use {
+        super::read_pins,
+        crate::mmap::{GPIO_INTEN_OFS, GPIO_INTSTATUS_OFS, GPIO_INTTYPE0_OFS, GPIO_INTTYPE1_OFS},
+        core::sync::atomic::AtomicU32,
+    };
+
+    /// Edge that raises a GPIO interrupt
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Edge {
+        Rising,
+        Falling,
+        Any,
+    }
+
+    /// Interrupt callbacks as `fn()` addresses, indexed by pin. `0` when
+    /// unset.
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NO_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+    static CALLBACKS: [AtomicUsize; 32] = [NO_CALLBACK; 32];
+
+    /// Pins listening to [Edge::Any]. The PULP APB GPIO only detects one edge
+    /// at a time, so the polarity is flipped after each interrupt.
+    #[cfg(not(feature = "vp"))]
+    static ANY_EDGE: AtomicU32 = AtomicU32::new(0);
+
+    /// Sets the polarity of pin `idx` on the PULP APB GPIO
+    #[cfg(not(feature = "vp"))]
+    fn set_falling(idx: u32, falling: bool) {
+        modify_bit(GPIO_ADDR + GPIO_INTTYPE1_OFS, idx, falling);
+    }
+
+    impl<const IDX: u32> ApbGpio<IDX, Input> {
+        /// Calls `callback` in interrupt context on every `edge`
+        ///
+        /// [on_gpio_interrupt] must be called from the handler of PLIC source
+        /// [Interrupt::Gpio](crate::Interrupt::Gpio), which must be enabled
+        /// on the PLIC.
+        pub fn listen(&mut self, edge: Edge, callback: fn()) {
+            CALLBACKS[IDX as usize].store(callback as usize, Ordering::Release);
+
+            #[cfg(not(feature = "vp"))]
+            {
+                let falling = match edge {
+                    Edge::Rising => false,
+                    Edge::Falling => true,
+                    // Wait for the opposite of the current level
+                    Edge::Any => self.is_high(),
+                };
+                if edge == Edge::Any {
+                    ANY_EDGE.fetch_or(1 << IDX, Ordering::AcqRel);
+                } else {
+                    ANY_EDGE.fetch_and(!(1 << IDX), Ordering::AcqRel);
+                }
+                modify_bit(GPIO_ADDR + GPIO_INTTYPE0_OFS, IDX, true);
+                set_falling(IDX, falling);
+                modify_bit(GPIO_ADDR + GPIO_INTEN_OFS, IDX, true);
+            }
+            #[cfg(feature = "vp")]
+            {
+                modify_bit(GPIO_ADDR + GPIO_IS_OFS, IDX, false);
+                modify_bit(GPIO_ADDR + GPIO_IBE_OFS, IDX, edge == Edge::Any);
+                modify_bit(GPIO_ADDR + GPIO_IEV_OFS, IDX, edge == Edge::Rising);
+                write_u32(GPIO_ADDR + GPIO_IC_OFS, 1 << IDX);
+                modify_bit(GPIO_ADDR + GPIO_IE_OFS, IDX, true);
+            }
+        }
+
+        /// Stops calling the callback set with [ApbGpio::listen]
+        pub fn unlisten(&mut self) {
+            #[cfg(not(feature = "vp"))]
+            modify_bit(GPIO_ADDR + GPIO_INTEN_OFS, IDX, false);
+            #[cfg(feature = "vp")]
+            modify_bit(GPIO_ADDR + GPIO_IE_OFS, IDX, false);
+            CALLBACKS[IDX as usize].store(0, Ordering::Release);
+        }
+    }
+
+    /// Reads and clears the pending pins
+    fn take_status() -> u32 {
+        #[cfg(not(feature = "vp"))]
+        {
+            // Reading the status clears it
+            let status = read_u32(GPIO_ADDR + GPIO_INTSTATUS_OFS);
+            // Re-arm the any-edge pins for the opposite edge
+            let any = status & ANY_EDGE.load(Ordering::Acquire);
+            if any != 0 {
+                let levels = read_pins();
+                for idx in (0..32).filter(|idx| any & (1 << idx) != 0) {
+                    set_falling(idx, levels & (1 << idx) != 0);
+                }
+            }
+            status
+        }
+        #[cfg(feature = "vp")]
+        {
+            let status = read_u32(GPIO_ADDR + GPIO_MIS_OFS);
+            write_u32(GPIO_ADDR + GPIO_IC_OFS, status);
+            status
+        }
+    }
+
+    /// Calls the callbacks of the pins that raised an interrupt
+    ///
+    /// Call this from the handler of PLIC source
+    /// [Interrupt::Gpio](crate::Interrupt::Gpio), before completing the
+    /// claim.
+    pub fn on_gpio_interrupt() {
+        let status = take_status();
+        for (idx, callback) in CALLBACKS.iter().enumerate() {
+            if status & (1 << idx) == 0 {
+                continue;
+            }
+            let addr = callback.load(Ordering::Acquire);
+            if addr != 0 {
+                // SAFETY: only `fn()` addresses are stored in `CALLBACKS`
+                let callback: fn() = unsafe { core::mem::transmute(addr) };
+                callback();
+            }
+        }
+    }
+}
+
+impl<const IDX: u32, S: GpioState> ErrorType for ApbGpio<IDX, S> {
+    type Error = Infallible;
+}
+
+impl<const IDX: u32> InputPin for ApbGpio<IDX, Input> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(ApbGpio::is_high(self))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(ApbGpio::is_low(self))
+    }
+}
+
+impl<const IDX: u32> OutputPin for ApbGpio<IDX, Output> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        ApbGpio::set_low(self);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        ApbGpio::set_high(self);
+        Ok(())
+    }
+}
+
+impl<const IDX: u32> StatefulOutputPin for ApbGpio<IDX, Output> {
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.is_set())
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set())
+    }
+}
@@ -0,0 +1,242 @@
+//! APB SPI masters
+//!
+//! The ASIC implements the PULP APB SPI master, which frames each transfer
+//! with its own chip select. [SpiBus::read] and [SpiBus::write] are each done
+//! as one transfer of up to `MAX_TRANSFER_LEN` bytes, so chip select #0 stays
+//! asserted for the whole buffer unless it's longer. The VP models the masters
+//! with the STM32 SPI, which has no chip select at all. Either way, devices
+//! that need the chip select held over several operations, e.g., SD cards,
+//! should use a GPIO as chip select, e.g., with
+//! `embedded_hal_bus::spi::ExclusiveDevice`.
+//!
+//! The PULP APB SPI master is half-duplex. [SpiBus::transfer] and
+//! [SpiBus::transfer_in_place] read when all the words written are `0xFF` and
+//! write otherwise, reading back `0xFF`. This covers the SD card protocol,
+//! where the host clocks out `0xFF` while reading.
+use crate::mmap::{SPIM0_ADDR, SPIM1_ADDR};
+use crate::{read_u32, write_u32};
+use embedded_hal::spi::{self, ErrorType, SpiBus};
+
+#[cfg(not(feature = "vp"))]
+use crate::mmap::{
+    SPIM_CLKDIV_OFS, SPIM_RXFIFO_OFS, SPIM_SPILEN_OFS, SPIM_STATUS_OFS, SPIM_TXFIFO_OFS,
+};
+#[cfg(feature = "vp")]
+use crate::mmap::{SPIM_CR1_OFS, SPIM_DR_OFS, SPIM_SR_OFS};
+
+#[cfg(not(feature = "vp"))]
+mod bits {
+    pub const STATUS_RD: u32 = 1 << 0;
+    pub const STATUS_WR: u32 = 1 << 1;
+    pub const STATUS_SRST: u32 = 1 << 4;
+    /// Chip select #0
+    pub const STATUS_CS0: u32 = 1 << 8;
+    pub const STATUS_STATE_MASK: u32 = 0x7F;
+    pub const STATUS_STATE_IDLE: u32 = 1;
+    pub const STATUS_RX_ELEMS_SHIFT: u32 = 16;
+    pub const STATUS_TX_ELEMS_SHIFT: u32 = 24;
+    /// Depth of the TX and RX FIFOs in words
+    pub const FIFO_DEPTH: u32 = 8;
+    pub const SPILEN_DATA_SHIFT: u32 = 16;
+}
+
+#[cfg(feature = "vp")]
+mod bits {
+    pub const CR1_MSTR: u32 = 1 << 2;
+    pub const CR1_BR_SHIFT: u32 = 3;
+    pub const CR1_SPE: u32 = 1 << 6;
+    pub const SR_RXNE: u32 = 1 << 0;
+    pub const SR_TXE: u32 = 1 << 1;
+}
+
+use bits::*;
+
+/// Longest transfer in bytes on the ASIC. The data length in `SPILEN` is 16
+/// bits wide and counted in bits, and transfers are kept to whole FIFO words.
+#[cfg(not(feature = "vp"))]
+pub const MAX_TRANSFER_LEN: usize = (u16::MAX as usize / 8) & !3;
+
+/// Relocatable driver for the APB SPI masters
+///
+/// The generic represents the base address for the SPI master. This driver is
+/// compatible with both ASIC and the VP. Use `-Fvp` for the VP implementation.
+pub struct ApbSpim<const BASE_ADDR: usize>;
+
+/// Type alias for APB SPI master 0
+pub type ApbSpim0 = ApbSpim<SPIM0_ADDR>;
+
+/// Type alias for APB SPI master 1
+pub type ApbSpim1 = ApbSpim<SPIM1_ADDR>;
+
+impl<const BASE_ADDR: usize> ApbSpim<BASE_ADDR> {
+    /// # Parameters
+    ///
+    /// * `clk_div` - SCK is the peripheral clock divided by `clk_div`. The VP
+    ///   rounds it down to a power of two.
+    pub fn init(clk_div: u32) -> Self {
+        #[cfg(not(feature = "vp"))]
+        {
+            write_u32(BASE_ADDR + SPIM_CLKDIV_OFS, clk_div);
+            // Clear the FIFOs
+            write_u32(BASE_ADDR + SPIM_STATUS_OFS, STATUS_SRST);
+            write_u32(BASE_ADDR + SPIM_STATUS_OFS, 0);
+        }
+        #[cfg(feature = "vp")]
+        {
+            // Baud rate is f_PCLK / 2^(BR + 1)
+            let br = clk_div.max(2).ilog2() - 1;
+            write_u32(
+                BASE_ADDR + SPIM_CR1_OFS,
+                CR1_SPE | CR1_MSTR | (br.min(7) << CR1_BR_SHIFT),
+            );
+        }
+        Self
+    }
+
+    /// Starts a transfer of `len` bytes with chip select #0 asserted
+    #[cfg(not(feature = "vp"))]
+    fn start(&mut self, cmd: u32, len: usize) {
+        write_u32(
+            BASE_ADDR + SPIM_SPILEN_OFS,
+            ((len * 8) as u32) << SPILEN_DATA_SHIFT,
+        );
+        write_u32(BASE_ADDR + SPIM_STATUS_OFS, STATUS_CS0 | cmd);
+    }
+
+    #[cfg(not(feature = "vp"))]
+    fn wait_idle(&mut self) {
+        let status = BASE_ADDR + SPIM_STATUS_OFS;
+        while read_u32(status) & STATUS_STATE_MASK != STATUS_STATE_IDLE {}
+    }
+
+    /// Writes `words` in one transfer of at most [MAX_TRANSFER_LEN] bytes
+    #[cfg(not(feature = "vp"))]
+    fn write_transfer(&mut self, words: &[u8]) {
+        let status = BASE_ADDR + SPIM_STATUS_OFS;
+        self.start(STATUS_WR, words.len());
+        for chunk in words.chunks(4) {
+            // Data is shifted out MSB first
+            let word = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |w, (i, &b)| w | (b as u32) << (24 - 8 * i));
+            while (read_u32(status) >> STATUS_TX_ELEMS_SHIFT) & 0xFF >= FIFO_DEPTH {}
+            write_u32(BASE_ADDR + SPIM_TXFIFO_OFS, word);
+        }
+        self.wait_idle();
+    }
+
+    /// Reads `words` in one transfer of at most [MAX_TRANSFER_LEN] bytes
+    #[cfg(not(feature = "vp"))]
+    fn read_transfer(&mut self, words: &mut [u8]) {
+        let status = BASE_ADDR + SPIM_STATUS_OFS;
+        self.start(STATUS_RD, words.len());
+        for chunk in words.chunks_mut(4) {
+            while (read_u32(status) >> STATUS_RX_ELEMS_SHIFT) & 0xFF == 0 {}
+            // Data is shifted in from the LSB, so a partial word is right-aligned
+            let word = read_u32(BASE_ADDR + SPIM_RXFIFO_OFS);
+            let last = chunk.len() - 1;
+            for (i, b) in chunk.iter_mut().enumerate() {
+                *b = (word >> (8 * (last - i))) as u8;
+            }
+        }
+        self.wait_idle();
+    }
+
+    /// Transfers one byte and returns the byte received
+    #[cfg(feature = "vp")]
+    fn transfer_byte(&mut self, byte: u8) -> u8 {
+        while read_u32(BASE_ADDR + SPIM_SR_OFS) & SR_TXE == 0 {}
+        write_u32(BASE_ADDR + SPIM_DR_OFS, byte as u32);
+        while read_u32(BASE_ADDR + SPIM_SR_OFS) & SR_RXNE == 0 {}
+        read_u32(BASE_ADDR + SPIM_DR_OFS) as u8
+    }
+}
+
+impl<const BASE_ADDR: usize> ErrorType for ApbSpim<BASE_ADDR> {
+    type Error = spi::ErrorKind;
+}
+
+#[cfg(not(feature = "vp"))]
+impl<const BASE_ADDR: usize> SpiBus for ApbSpim<BASE_ADDR> {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        for chunk in words.chunks_mut(MAX_TRANSFER_LEN) {
+            self.read_transfer(chunk);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        for chunk in words.chunks(MAX_TRANSFER_LEN) {
+            self.write_transfer(chunk);
+        }
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        if write.iter().all(|&w| w == 0xFF) {
+            // Words past the end of `write` are `0xFF` as well
+            self.read(read)
+        } else {
+            self.write(write)?;
+            let n = write.len().min(read.len());
+            read[..n].fill(0xFF);
+            // Words past the end of `write` are read
+            self.read(&mut read[n..])
+        }
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        if words.iter().all(|&w| w == 0xFF) {
+            self.read(words)
+        } else {
+            self.write(words)?;
+            words.fill(0xFF);
+            Ok(())
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // Each transfer is complete once `read` or `write` returns
+        Ok(())
+    }
+}
+
+#[cfg(feature = "vp")]
+impl<const BASE_ADDR: usize> SpiBus for ApbSpim<BASE_ADDR> {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        for w in words {
+            *w = self.transfer_byte(0xFF);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        for &w in words {
+            self.transfer_byte(w);
+        }
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        for idx in 0..read.len().max(write.len()) {
+            let b = self.transfer_byte(write.get(idx).copied().unwrap_or(0xFF));
+            if let Some(r) = read.get_mut(idx) {
+                *r = b;
+            }
+        }
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        for w in words {
+            *w = self.transfer_byte(*w);
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // Each byte is complete once `transfer_byte` returns
+        Ok(())
+    }
+}
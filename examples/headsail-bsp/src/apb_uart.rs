@@ -1,3 +1,5 @@
+#[cfg(feature = "sysctrl")]
+use crate::sysctrl::pad_mux::{signal, AltPad};
 use crate::{
     mmap::{UART0_ADDR, UART1_ADDR, UART_LSR_RX_FIFO_VALID, UART_RBR_THR_DLL_OFS},
     read_u8, write_u8,
@@ -42,6 +44,12 @@ pub type ApbUart0 = ApbUart<UART0_ADDR>;
 pub type ApbUart1 = ApbUart<UART1_ADDR>;
 
 impl<const BASE_ADDR: usize> ApbUart<BASE_ADDR> {
+    /// Initializes the UART without touching the pads
+    ///
+    /// The pads are routed by SysCtrl, so this is the constructor to use on
+    /// HPC. On SysCtrl, use `init_with_pads` of [ApbUart0] or [ApbUart1] to
+    /// route and claim the pads.
+    ///
     /// # Parameters
     ///
     /// * `soc_freq`-  used to calculate BAUD rate together with divisor
@@ -153,12 +161,17 @@ impl<const BASE_ADDR: usize> ApbUart<BASE_ADDR> {
         unsafe { write_u8(BASE_ADDR + UART_RBR_THR_DLL_OFS, c) };
     }
 
+    /// Returns `true` if there is at least one byte to read
+    #[inline]
+    pub fn is_data_ready(&self) -> bool {
+        // Safety: UART_LSR is 4-byte aligned
+        unsafe { read_u8(BASE_ADDR + crate::mmap::UART_LSR_OFS) } & UART_LSR_RX_FIFO_VALID != 0
+    }
+
     #[inline]
     pub fn getc(&mut self) -> u8 {
         // Wait for data to become ready
-        while unsafe { read_u8(BASE_ADDR + crate::mmap::UART_LSR_OFS) } & UART_LSR_RX_FIFO_VALID
-            == 0
-        {}
+        while !self.is_data_ready() {}
 
         // SAFETY: UART0_ADDR is 4-byte aligned
         unsafe { read_u8(BASE_ADDR) }
@@ -194,6 +207,27 @@ impl<const BASE_ADDR: usize> ApbUart<BASE_ADDR> {
     }
 }
 
+macro_rules! init_with_pads {
+    ($uart:ident, $tx:ident, $rx:ident) => {
+        #[cfg(feature = "sysctrl")]
+        impl $uart {
+            /// Like [ApbUart::init], but the UART is routed to `tx` and `rx`,
+            /// which stay configured for the UART
+            pub fn init_with_pads<const TX: u32, const RX: u32>(
+                soc_freq: u32,
+                baud: u32,
+                _tx: AltPad<TX, signal::$tx>,
+                _rx: AltPad<RX, signal::$rx>,
+            ) -> Self {
+                Self::init(soc_freq, baud)
+            }
+        }
+    };
+}
+
+init_with_pads!(ApbUart0, ApbUart0Tx, ApbUart0Rx);
+init_with_pads!(ApbUart1, ApbUart1Tx, ApbUart1Rx);
+
 impl<const BASE_ADDR: usize> ufmt_write::uWrite for ApbUart<BASE_ADDR> {
     type Error = core::convert::Infallible;
 
@@ -202,3 +236,68 @@ impl<const BASE_ADDR: usize> ufmt_write::uWrite for ApbUart<BASE_ADDR> {
         Ok(())
     }
 }
+
+/// Async reads are driven by the UART interrupt via the PLIC, which is only
+/// available on VP
+#[cfg(all(feature = "async", feature = "hpc", feature = "vp"))]
+mod asynch {
+    use super::{ApbUart, UartInterrupt};
+    use crate::{
+        mmap::{UART0_ADDR, UART_RBR_THR_DLL_OFS},
+        read_u8, wait_for_interrupt, Interrupt,
+    };
+
+    impl<const BASE_ADDR: usize> ApbUart<BASE_ADDR> {
+        /// PLIC source of this UART
+        const IRQ: Interrupt = match BASE_ADDR {
+            UART0_ADDR => Interrupt::Uart0,
+            _ => Interrupt::Uart1,
+        };
+
+        /// Waits until at least one byte is available, then reads as many
+        /// bytes as are available and fit into `buf`
+        pub async fn read_async(&mut self, buf: &mut [u8]) -> usize {
+            if buf.is_empty() {
+                return 0;
+            }
+
+            while !self.is_data_ready() {
+                self.listen(UartInterrupt::OnData);
+                wait_for_interrupt(Self::IRQ).await;
+            }
+
+            let mut n = 0;
+            while n < buf.len() && self.is_data_ready() {
+                // SAFETY: UART_RBR is 4-byte aligned
+                buf[n] = unsafe { read_u8(BASE_ADDR + UART_RBR_THR_DLL_OFS) };
+                n += 1;
+            }
+            n
+        }
+
+        /// Writes all of `buf`
+        ///
+        /// The VP UART never applies back-pressure so this completes
+        /// immediately.
+        pub async fn write_async(&mut self, buf: &[u8]) {
+            self.write(buf);
+        }
+    }
+
+    impl<const BASE_ADDR: usize> embedded_io::ErrorType for ApbUart<BASE_ADDR> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl<const BASE_ADDR: usize> embedded_io_async::Read for ApbUart<BASE_ADDR> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            Ok(self.read_async(buf).await)
+        }
+    }
+
+    impl<const BASE_ADDR: usize> embedded_io_async::Write for ApbUart<BASE_ADDR> {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.write_async(buf).await;
+            Ok(buf.len())
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! defmt global logger over UART
+//!
+//! Frames are rzCOBS encoded and separated by zero bytes. The UART must be
+//! initialized before logging, i.e., with
+//! [ApbUart::init](crate::apb_uart::ApbUart::init) or
+//! [UdmaUart::enable](crate::sysctrl::udma::UdmaUart::enable). Text printed
+//! on the same UART, e.g., with `sprintln!`, breaks decoding.
+//!
+//! The logger is not synchronized between HARTs. Log from one HART only.
+
+use core::ptr::addr_of_mut;
+
+use riscv::register::{mcycle, mstatus};
+
+#[defmt::global_logger]
+struct Logger;
+
+static mut TAKEN: bool = false;
+/// Interrupts were enabled before acquiring
+static mut RESTORE_MIE: bool = false;
+static mut ENCODER: defmt::Encoder = defmt::Encoder::new();
+
+/// Timestamps are in `mcycle` cycles
+defmt::timestamp!("{=u64}", mcycle::read64());
+
+unsafe impl defmt::Logger for Logger {
+    fn acquire() {
+        let mie = mstatus::read().mie();
+        riscv::interrupt::disable();
+
+        unsafe {
+            if TAKEN {
+                panic!("defmt logger taken reentrantly");
+            }
+            TAKEN = true;
+            RESTORE_MIE = mie;
+            (*addr_of_mut!(ENCODER)).start_frame(serial::write);
+        }
+    }
+
+    unsafe fn flush() {
+        serial::flush();
+    }
+
+    unsafe fn release() {
+        (*addr_of_mut!(ENCODER)).end_frame(serial::write);
+        serial::flush();
+        TAKEN = false;
+        if RESTORE_MIE {
+            riscv::interrupt::enable();
+        }
+    }
+
+    unsafe fn write(bytes: &[u8]) {
+        (*addr_of_mut!(ENCODER)).write(bytes, serial::write);
+    }
+}
+
+#[cfg(feature = "defmt-apb-uart0")]
+mod serial {
+    use crate::apb_uart::ApbUart0;
+
+    pub(super) fn write(bytes: &[u8]) {
+        let mut uart = unsafe { ApbUart0::instance() };
+        for &b in bytes {
+            uart.putc(b);
+        }
+    }
+
+    pub(super) fn flush() {}
+}
+
+/// The uDMA sends from memory, so bytes are collected into a buffer and sent
+/// in one transfer
+#[cfg(feature = "defmt-sysctrl-uart")]
+mod serial {
+    use core::ptr::addr_of_mut;
+
+    use crate::{pac, sysctrl::udma::UdmaUart};
+
+    const BUF_LEN: usize = 64;
+    static mut BUF: [u8; BUF_LEN] = [0; BUF_LEN];
+    static mut FILLED: usize = 0;
+
+    pub(super) fn write(bytes: &[u8]) {
+        for &b in bytes {
+            unsafe {
+                if FILLED == BUF_LEN {
+                    flush();
+                }
+                (*addr_of_mut!(BUF))[FILLED] = b;
+                FILLED += 1;
+            }
+        }
+    }
+
+    pub(super) fn flush() {
+        unsafe {
+            if FILLED == 0 {
+                return;
+            }
+            let udma = (*pac::Sysctrl::ptr()).udma();
+            UdmaUart::steal(udma).write(&(*addr_of_mut!(BUF))[..FILLED]);
+            FILLED = 0;
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! embassy executors for HPC and SysCtrl
+//!
+//! [Executor] runs tasks in thread mode and sleeps with `wfi` while idle.
+//! [InterruptExecutor] runs tasks in the machine software interrupt, which
+//! preempts thread mode. On HPC the software interrupt is pended through the
+//! CLINT `msip` of the HART that started the executor, on SysCtrl through ITC
+//! line [SOFT_IRQ](crate::sysctrl::itc::SOFT_IRQ).
+//!
+//! Both executors rely on `critical-section`. SysCtrl gets an implementation
+//! via "sysctrl-rt". On HPC, enable "critical-section-multi-hart" or provide
+//! one in the application.
+use core::{
+    cell::{Cell, UnsafeCell},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use critical_section::Mutex;
+use embassy_executor::raw;
+pub use embassy_executor::{task, SendSpawner, Spawner};
+
+/// Pender context of the thread mode [Executor]
+const THREAD_PENDER: usize = usize::MAX;
+
+/// Set when there is work for the thread mode executor
+static SIGNAL_WORK_THREAD_MODE: AtomicBool = AtomicBool::new(false);
+
+#[export_name = "__pender"]
+fn __pender(context: *mut ()) {
+    match context as usize {
+        THREAD_PENDER => SIGNAL_WORK_THREAD_MODE.store(true, Ordering::SeqCst),
+        hart => pend_soft(hart),
+    }
+}
+
+#[cfg(feature = "hpc")]
+fn pend_soft(hart: usize) {
+    use crate::CLINT;
+    match hart {
+        0 => CLINT::msip0().pend(),
+        1 => CLINT::msip1().pend(),
+        2 => CLINT::msip2().pend(),
+        3 => CLINT::msip3().pend(),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(feature = "hpc")]
+fn unpend_soft(hart: usize) {
+    use crate::CLINT;
+    match hart {
+        0 => CLINT::msip0().unpend(),
+        1 => CLINT::msip1().unpend(),
+        2 => CLINT::msip2().unpend(),
+        3 => CLINT::msip3().unpend(),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(feature = "sysctrl")]
+fn pend_soft(_hart: usize) {
+    crate::sysctrl::itc::pend(crate::sysctrl::itc::SOFT_IRQ);
+}
+
+#[cfg(feature = "sysctrl")]
+fn unpend_soft(_hart: usize) {
+    crate::sysctrl::itc::unpend(crate::sysctrl::itc::SOFT_IRQ);
+}
+
+#[cfg(feature = "hpc")]
+fn enable_soft() {
+    unsafe { riscv::register::mie::set_msoft() };
+}
+
+#[cfg(feature = "sysctrl")]
+fn enable_soft() {
+    crate::sysctrl::itc::enable(crate::sysctrl::itc::SOFT_IRQ);
+}
+
+/// Thread mode executor
+pub struct Executor {
+    inner: raw::Executor,
+    not_send: PhantomData<*mut ()>,
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        Self {
+            inner: raw::Executor::new(THREAD_PENDER as *mut ()),
+            not_send: PhantomData,
+        }
+    }
+
+    /// Runs the executor
+    ///
+    /// `init` is called with a [Spawner] for spawning the initial tasks on
+    /// this executor. Requires `&'static mut self`, e.g., a `static mut` or a
+    /// `StaticCell`.
+    pub fn run(&'static mut self, init: impl FnOnce(Spawner)) -> ! {
+        init(self.inner.spawner());
+
+        loop {
+            unsafe { self.inner.poll() };
+            riscv::interrupt::free(|| {
+                // Interrupts only ever set the signal, so load & store don't race
+                if SIGNAL_WORK_THREAD_MODE.load(Ordering::SeqCst) {
+                    SIGNAL_WORK_THREAD_MODE.store(false, Ordering::SeqCst);
+                } else {
+                    // Pending interrupts wake up `wfi` and are serviced once
+                    // `free` re-enables interrupts
+                    riscv::asm::wfi();
+                }
+            });
+        }
+    }
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Interrupt mode executor
+///
+/// Tasks are polled from the machine software interrupt handler. Forward the
+/// interrupt to the executor with [InterruptExecutor::on_interrupt]:
+///
+/// ```ignore
+/// static EXECUTOR: InterruptExecutor = InterruptExecutor::new();
+///
+/// #[export_name = "MachineSoft"]
+/// fn machine_soft() {
+///     unsafe { EXECUTOR.on_interrupt() };
+/// }
+/// ```
+pub struct InterruptExecutor {
+    started: Mutex<Cell<bool>>,
+    hart: Mutex<Cell<usize>>,
+    executor: UnsafeCell<MaybeUninit<raw::Executor>>,
+}
+
+unsafe impl Send for InterruptExecutor {}
+unsafe impl Sync for InterruptExecutor {}
+
+impl InterruptExecutor {
+    pub const fn new() -> Self {
+        Self {
+            started: Mutex::new(Cell::new(false)),
+            hart: Mutex::new(Cell::new(0)),
+            executor: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Polls the executor
+    ///
+    /// # Safety
+    ///
+    /// Must only be called from the machine software interrupt handler of the
+    /// HART that started the executor.
+    pub unsafe fn on_interrupt(&'static self) {
+        let hart = critical_section::with(|cs| self.hart.borrow(cs).get());
+        unpend_soft(hart);
+        let executor = unsafe { (*self.executor.get()).assume_init_ref() };
+        unsafe { executor.poll() };
+    }
+
+    /// Starts the executor on the calling HART
+    ///
+    /// Enables the machine software interrupt but not interrupts globally.
+    /// Returns a [SendSpawner] for spawning tasks on this executor.
+    ///
+    /// # Panics
+    ///
+    /// If the executor has already been started.
+    pub fn start(&'static self) -> SendSpawner {
+        let hart = riscv::register::mhartid::read();
+        critical_section::with(|cs| {
+            if self.started.borrow(cs).replace(true) {
+                panic!("InterruptExecutor::start() called multiple times");
+            }
+            self.hart.borrow(cs).set(hart);
+        });
+
+        unsafe {
+            (*self.executor.get())
+                .as_mut_ptr()
+                .write(raw::Executor::new(hart as *mut ()))
+        };
+        let executor = unsafe { (*self.executor.get()).assume_init_ref() };
+        enable_soft();
+
+        executor.spawner().make_send()
+    }
+
+    /// Returns a [SendSpawner] for this executor
+    ///
+    /// # Panics
+    ///
+    /// If the executor has not been started yet.
+    pub fn spawner(&'static self) -> SendSpawner {
+        if !critical_section::with(|cs| self.started.borrow(cs).get()) {
+            panic!("InterruptExecutor::spawner() called on uninitialized executor");
+        }
+        let executor = unsafe { (*self.executor.get()).assume_init_ref() };
+        executor.spawner().make_send()
+    }
+}
+
+impl Default for InterruptExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
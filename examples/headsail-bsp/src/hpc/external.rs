@@ -0,0 +1,66 @@
+//! Async dispatch of PLIC external interrupts
+//!
+//! [wait_for_interrupt] enables a PLIC source on the calling HART's machine
+//! context and completes once the source fires. The source is disabled again
+//! when it's claimed, so level-triggered peripherals don't re-trigger before
+//! the woken task has serviced them.
+use super::{Interrupt, Priority, PLIC};
+use core::{future::poll_fn, task::Poll};
+use embassy_sync::waitqueue::AtomicWaker;
+use riscv::{register::mhartid, InterruptNumber};
+use riscv_peripheral::plic::CTX;
+
+const SOURCE_COUNT: usize = Interrupt::MAX_INTERRUPT_NUMBER + 1;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+static WAKERS: [AtomicWaker; SOURCE_COUNT] = [NEW_WAKER; SOURCE_COUNT];
+
+/// Returns the machine mode PLIC context of the calling HART
+fn ctx() -> CTX<PLIC> {
+    match mhartid::read() {
+        0 => PLIC::ctx0(),
+        1 => PLIC::ctx2(),
+        2 => PLIC::ctx4(),
+        3 => PLIC::ctx6(),
+        _ => unreachable!(),
+    }
+}
+
+/// Waits for `irq` to be raised once
+///
+/// `irq` is assigned [Priority::P1], since priority 0 never interrupts.
+pub async fn wait_for_interrupt(irq: Interrupt) {
+    let ctx = ctx();
+    let mut enabled = false;
+    poll_fn(|cx| {
+        WAKERS[irq.number()].register(cx.waker());
+        if !enabled {
+            unsafe {
+                PLIC::priorities().set_priority(irq, Priority::P1);
+                ctx.enables().enable(irq);
+                riscv::register::mie::set_mext();
+            }
+            enabled = true;
+            return Poll::Pending;
+        }
+        // The handler disables the source once it fires
+        if ctx.enables().is_enabled(irq) {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await
+}
+
+/// Claims the pending sources of the calling HART and wakes their waiters
+#[cfg_attr(feature = "rt", export_name = "MachineExternal")]
+pub fn on_external_interrupt() {
+    let ctx = ctx();
+    while let Some(irq) = ctx.claim().claim::<Interrupt>() {
+        ctx.enables().disable(irq);
+        ctx.claim().complete(irq);
+        WAKERS[irq.number()].wake();
+    }
+}
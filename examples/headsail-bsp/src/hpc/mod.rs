@@ -1,5 +1,11 @@
 //! Abstractions that only exist on HPC
+#[cfg(all(feature = "async", feature = "vp"))]
+mod external;
 mod hart_id;
 mod interrupt;
+pub mod smp;
+pub mod time;
+#[cfg(all(feature = "async", feature = "vp"))]
+pub use external::*;
 pub use hart_id::*;
 pub use interrupt::*;
@@ -0,0 +1,300 @@
+//! Multi-HART support for the four HPC cores
+//!
+//! On reset, all HPC HARTs run the `riscv-rt` start-up code on their own stack
+//! (sa. `_hart_stack_size` in `mem_hpc.x` and `sdram_hpc.x`). HART 0 continues
+//! to `main` while the secondary HARTs are parked in `_mp_hook` until they are
+//! started with [start_hart].
+//!
+//! HARTs are woken up by pending their CLINT `msip`. The same mechanism is used
+//! to signal messages sent with [send], so the machine software interrupt
+//! should not be used for other purposes while HARTs are communicating.
+use super::{HartId, CLINT};
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use riscv::register::{mhartid, mie};
+use riscv_pac::HartIdNumber;
+
+/// Number of HARTs on HPC
+pub const HART_COUNT: usize = HartId::MAX_HART_ID_NUMBER + 1;
+
+/// Entry point of a secondary HART. Receives the argument passed to
+/// [start_hart].
+pub type HartEntry = fn(usize) -> !;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmpError {
+    /// HART 0 runs `main` and cannot be started
+    BootHart,
+    /// The HART has already been started
+    AlreadyStarted,
+}
+
+/// Start request for one secondary HART
+struct StartSlot {
+    /// `HartEntry` as `usize`, 0 when not requested
+    entry: AtomicUsize,
+    arg: AtomicUsize,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_START_SLOT: StartSlot = StartSlot {
+    entry: AtomicUsize::new(0),
+    arg: AtomicUsize::new(0),
+};
+static START_SLOTS: [StartSlot; HART_COUNT] = [NEW_START_SLOT; HART_COUNT];
+
+/// Returns the ID of the calling HART
+#[inline]
+pub fn hart_id() -> HartId {
+    HartId::from_number(mhartid::read()).unwrap()
+}
+
+#[inline]
+fn pend(hart: HartId) {
+    match hart {
+        HartId::H0 => CLINT::msip0().pend(),
+        HartId::H1 => CLINT::msip1().pend(),
+        HartId::H2 => CLINT::msip2().pend(),
+        HartId::H3 => CLINT::msip3().pend(),
+    }
+}
+
+#[inline]
+fn unpend(hart: HartId) {
+    match hart {
+        HartId::H0 => CLINT::msip0().unpend(),
+        HartId::H1 => CLINT::msip1().unpend(),
+        HartId::H2 => CLINT::msip2().unpend(),
+        HartId::H3 => CLINT::msip3().unpend(),
+    }
+}
+
+#[inline]
+fn is_pending(hart: HartId) -> bool {
+    match hart {
+        HartId::H0 => CLINT::msip0().is_pending(),
+        HartId::H1 => CLINT::msip1().is_pending(),
+        HartId::H2 => CLINT::msip2().is_pending(),
+        HartId::H3 => CLINT::msip3().is_pending(),
+    }
+}
+
+/// Sleeps until the calling HART's `msip` is pended and clears it
+///
+/// The software interrupt is not taken, i.e., any `MachineSoft` handler does
+/// not run.
+fn wait_for_msip() {
+    let hart = hart_id();
+    riscv::interrupt::free(|| {
+        let prev_msoft = mie::read().msoft();
+        unsafe { mie::set_msoft() };
+        while !is_pending(hart) {
+            riscv::asm::wfi();
+        }
+        unpend(hart);
+        if !prev_msoft {
+            unsafe { mie::clear_msoft() };
+        }
+    })
+}
+
+/// Starts `hart` at `entry` with `arg`
+///
+/// The HART runs on its own stack allocated by `riscv-rt`. Each HART can be
+/// started once.
+pub fn start_hart(hart: HartId, entry: HartEntry, arg: usize) -> Result<(), SmpError> {
+    if hart == HartId::H0 {
+        return Err(SmpError::BootHart);
+    }
+
+    let slot = &START_SLOTS[hart.number()];
+    slot.arg.store(arg, Ordering::Relaxed);
+    if slot
+        .entry
+        .compare_exchange(0, entry as usize, Ordering::Release, Ordering::Relaxed)
+        .is_err()
+    {
+        return Err(SmpError::AlreadyStarted);
+    }
+
+    pend(hart);
+    Ok(())
+}
+
+/// Parks the secondary HARTs until they're started with [start_hart]
+///
+/// Runs before `.bss` and `.data` are initialized by HART 0, so the start
+/// slots are only read after HART 0 has pended `msip` from `main`.
+#[cfg(feature = "rt")]
+#[export_name = "_mp_hook"]
+fn mp_hook(hartid: usize) -> bool {
+    if hartid == 0 {
+        return true;
+    }
+
+    let slot = &START_SLOTS[hartid];
+    let entry = loop {
+        wait_for_msip();
+        match slot.entry.load(Ordering::Acquire) {
+            0 => continue,
+            entry => break entry,
+        }
+    };
+    let arg = slot.arg.load(Ordering::Relaxed);
+
+    extern "Rust" {
+        fn _setup_interrupts();
+    }
+    unsafe {
+        _setup_interrupts();
+        let entry: HartEntry = core::mem::transmute::<usize, HartEntry>(entry);
+        entry(arg)
+    }
+}
+
+/// Single-slot message box of one HART
+struct Mailbox {
+    state: AtomicU8,
+    msg: AtomicUsize,
+}
+
+/// The mailbox can be claimed by a sender
+const MAILBOX_EMPTY: u8 = 0;
+/// A sender has claimed the mailbox and is storing its message
+const MAILBOX_WRITING: u8 = 1;
+/// The message can be read
+const MAILBOX_FULL: u8 = 2;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_MAILBOX: Mailbox = Mailbox {
+    state: AtomicU8::new(MAILBOX_EMPTY),
+    msg: AtomicUsize::new(0),
+};
+static MAILBOXES: [Mailbox; HART_COUNT] = [NEW_MAILBOX; HART_COUNT];
+
+/// Sends `msg` to `to`, spinning while the previous message is unread
+///
+/// Several HARTs may send to the same HART, the mailbox is claimed by one
+/// sender at a time.
+pub fn send(to: HartId, msg: usize) {
+    let mailbox = &MAILBOXES[to.number()];
+    while mailbox
+        .state
+        .compare_exchange_weak(
+            MAILBOX_EMPTY,
+            MAILBOX_WRITING,
+            Ordering::Acquire,
+            Ordering::Relaxed,
+        )
+        .is_err()
+    {
+        core::hint::spin_loop();
+    }
+    mailbox.msg.store(msg, Ordering::Relaxed);
+    mailbox.state.store(MAILBOX_FULL, Ordering::Release);
+    pend(to);
+}
+
+/// Returns a message sent to the calling HART, if any
+pub fn try_recv() -> Option<usize> {
+    let mailbox = &MAILBOXES[hart_id().number()];
+    if mailbox.state.load(Ordering::Acquire) == MAILBOX_FULL {
+        let msg = mailbox.msg.load(Ordering::Relaxed);
+        mailbox.state.store(MAILBOX_EMPTY, Ordering::Release);
+        Some(msg)
+    } else {
+        None
+    }
+}
+
+/// Sleeps until a message is sent to the calling HART
+pub fn recv() -> usize {
+    loop {
+        if let Some(msg) = try_recv() {
+            return msg;
+        }
+        wait_for_msip();
+    }
+}
+
+/// Barrier for synchronizing a fixed number of HARTs
+pub struct Barrier {
+    count: usize,
+    waiting: AtomicUsize,
+    generation: AtomicUsize,
+}
+
+impl Barrier {
+    pub const fn new(count: usize) -> Self {
+        Self {
+            count,
+            waiting: AtomicUsize::new(0),
+            generation: AtomicUsize::new(0),
+        }
+    }
+
+    /// Spins until `count` HARTs have called `wait`
+    pub fn wait(&self) {
+        let generation = self.generation.load(Ordering::Acquire);
+        if self.waiting.fetch_add(1, Ordering::AcqRel) + 1 == self.count {
+            self.waiting.store(0, Ordering::Relaxed);
+            self.generation.fetch_add(1, Ordering::Release);
+        } else {
+            while self.generation.load(Ordering::Acquire) == generation {
+                core::hint::spin_loop();
+            }
+        }
+    }
+}
+
+/// `critical-section` implementation that is sound with several HARTs running
+///
+/// Disables interrupts on the calling HART and takes a global spin lock.
+/// Nested critical sections on the same HART are allowed.
+#[cfg(feature = "critical-section-multi-hart")]
+mod critical_section_impl {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use critical_section::RawRestoreState;
+    use riscv::register::{mhartid, mstatus};
+
+    struct MultiHartCriticalSection;
+    critical_section::set_impl!(MultiHartCriticalSection);
+
+    const UNLOCKED: usize = usize::MAX;
+    static LOCK_OWNER: AtomicUsize = AtomicUsize::new(UNLOCKED);
+
+    /// Interrupts were enabled before acquiring
+    const RESTORE_MIE: u8 = 0b01;
+    /// The lock was already held by this HART
+    const RESTORE_NESTED: u8 = 0b10;
+
+    unsafe impl critical_section::Impl for MultiHartCriticalSection {
+        unsafe fn acquire() -> RawRestoreState {
+            let mut state = 0;
+            if mstatus::read().mie() {
+                state |= RESTORE_MIE;
+            }
+            riscv::interrupt::disable();
+
+            let hart = mhartid::read();
+            if LOCK_OWNER.load(Ordering::Relaxed) == hart {
+                return state | RESTORE_NESTED;
+            }
+            while LOCK_OWNER
+                .compare_exchange_weak(UNLOCKED, hart, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                core::hint::spin_loop();
+            }
+            state
+        }
+
+        unsafe fn release(state: RawRestoreState) {
+            if state & RESTORE_NESTED == 0 {
+                LOCK_OWNER.store(UNLOCKED, Ordering::Release);
+            }
+            if state & RESTORE_MIE != 0 {
+                riscv::interrupt::enable();
+            }
+        }
+    }
+}
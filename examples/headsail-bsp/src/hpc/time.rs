@@ -0,0 +1,216 @@
+//! Monotonic time and delays based on the CLINT `mtime` counter
+//!
+//! `mtime` is shared by all HARTs and counts at [TICK_HZ]. Delays busy-wait on
+//! `mtime` with interrupts enabled and leave `mtimecmp` to the RTIC monotonic or
+//! embassy-time driver.
+use super::CLINT;
+#[cfg(any(feature = "rtic-monotonic", feature = "embassy-time-driver"))]
+use riscv::register::mhartid;
+#[cfg(feature = "rtic-monotonic")]
+use riscv::register::mie;
+#[cfg(any(feature = "rtic-monotonic", feature = "embassy-time-driver"))]
+use riscv_peripheral::aclint::mtimer::MTIMECMP;
+
+/// Frequency of the CLINT `mtime` counter
+pub const TICK_HZ: u32 = 32_768;
+
+/// A point in time as measured by `mtime`
+pub type Instant = fugit::TimerInstantU64<TICK_HZ>;
+/// A span of time in `mtime` ticks
+pub type Duration = fugit::TimerDurationU64<TICK_HZ>;
+
+/// Returns the current value of the monotonic clock
+#[inline]
+pub fn now() -> Instant {
+    Instant::from_ticks(CLINT::mtime().read())
+}
+
+/// Returns the `mtimecmp` register of the calling HART
+#[cfg(any(feature = "rtic-monotonic", feature = "embassy-time-driver"))]
+#[inline]
+pub(crate) fn mtimecmp() -> MTIMECMP {
+    match mhartid::read() {
+        0 => CLINT::mtimecmp0(),
+        1 => CLINT::mtimecmp1(),
+        2 => CLINT::mtimecmp2(),
+        3 => CLINT::mtimecmp3(),
+        _ => unreachable!(),
+    }
+}
+
+/// Busy-waits until `deadline` has passed
+///
+/// Interrupts, including alarms of an RTIC monotonic or embassy-time on the
+/// calling HART's `mtimecmp`, are still taken while waiting.
+#[inline]
+pub fn delay_until(deadline: Instant) {
+    while now() < deadline {}
+}
+
+/// Busy-waits for at least `duration`
+///
+/// One tick is added, as the current tick may already be almost over.
+#[inline]
+pub fn delay(duration: Duration) {
+    delay_until(now() + duration + Duration::from_ticks(1))
+}
+
+/// Busy-waits for at least `ms` milliseconds
+#[inline]
+pub fn delay_ms(ms: u32) {
+    delay(Duration::millis_at_least(ms as u64))
+}
+
+/// Busy-waits for at least `us` microseconds
+///
+/// N.b. resolution is limited by [TICK_HZ], i.e., roughly 30 us.
+#[inline]
+pub fn delay_us(us: u32) {
+    delay(Duration::micros_at_least(us as u64))
+}
+
+/// [embedded_hal::delay::DelayNs] implementation based on [delay]
+///
+/// Busy-waits for at least the requested time, rounded up to the next tick.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Delay;
+
+impl embedded_hal::delay::DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        delay(Duration::nanos_at_least(ns as u64))
+    }
+
+    #[inline]
+    fn delay_us(&mut self, us: u32) {
+        delay_us(us)
+    }
+
+    #[inline]
+    fn delay_ms(&mut self, ms: u32) {
+        delay_ms(ms)
+    }
+}
+
+/// Monotonic timer for RTIC, based on the calling HART's `mtimecmp`
+#[cfg(feature = "rtic-monotonic")]
+pub struct MonoTimer;
+
+#[cfg(feature = "rtic-monotonic")]
+impl rtic_monotonic::Monotonic for MonoTimer {
+    type Instant = Instant;
+    type Duration = Duration;
+
+    #[inline]
+    fn now(&mut self) -> Self::Instant {
+        now()
+    }
+
+    fn set_compare(&mut self, instant: Self::Instant) {
+        mtimecmp().write(instant.ticks());
+    }
+
+    fn clear_compare_flag(&mut self) {
+        // MTIP is level sensitive and only clears when `mtimecmp` is moved past
+        // `mtime`
+        mtimecmp().write(u64::MAX);
+    }
+
+    #[inline]
+    fn zero() -> Self::Instant {
+        Instant::from_ticks(0)
+    }
+
+    /// `mtime` is shared by all HARTs and is never reset
+    unsafe fn reset(&mut self) {
+        mtimecmp().write(u64::MAX);
+    }
+
+    fn enable_timer(&mut self) {
+        unsafe { mie::set_mtimer() };
+    }
+
+    fn disable_timer(&mut self) {
+        unsafe { mie::clear_mtimer() };
+    }
+}
+
+#[cfg(feature = "embassy-time-driver")]
+mod embassy {
+    use super::{mtimecmp, CLINT};
+    use core::{cell::RefCell, task::Waker};
+    use critical_section::Mutex;
+    use embassy_time_driver::Driver;
+    use embassy_time_queue_utils::Queue;
+    use riscv::register::mie;
+
+    const _: () = assert!(
+        embassy_time_driver::TICK_HZ == super::TICK_HZ as u64,
+        "embassy-time tick rate must match CLINT frequency"
+    );
+
+    /// embassy-time driver on the CLINT
+    ///
+    /// Alarms are raised on the `mtimecmp` of the HART that last scheduled a
+    /// wake-up, which is in practice the HART running the executor.
+    struct ClintDriver {
+        queue: Mutex<RefCell<Queue>>,
+    }
+
+    impl ClintDriver {
+        /// Returns `false` if `at` has already passed
+        fn set_alarm(&self, at: u64) -> bool {
+            if at == u64::MAX {
+                mtimecmp().write(u64::MAX);
+                return true;
+            }
+            mtimecmp().write(at);
+            CLINT::mtime().read() < at
+        }
+
+        /// Wakes expired timers and arms the alarm for the next one
+        fn rearm(&self, queue: &mut Queue) {
+            loop {
+                let next = queue.next_expiration(self.now());
+                if self.set_alarm(next) {
+                    break;
+                }
+            }
+        }
+
+        fn on_interrupt(&self) {
+            critical_section::with(|cs| self.rearm(&mut self.queue.borrow(cs).borrow_mut()))
+        }
+    }
+
+    impl Driver for ClintDriver {
+        #[inline]
+        fn now(&self) -> u64 {
+            CLINT::mtime().read()
+        }
+
+        fn schedule_wake(&self, at: u64, waker: &Waker) {
+            critical_section::with(|cs| {
+                let mut queue = self.queue.borrow(cs).borrow_mut();
+                if queue.schedule_wake(at, waker) {
+                    self.rearm(&mut queue);
+                    unsafe { mie::set_mtimer() };
+                }
+            })
+        }
+    }
+
+    embassy_time_driver::time_driver_impl!(static DRIVER: ClintDriver = ClintDriver {
+        queue: Mutex::new(RefCell::new(Queue::new())),
+    });
+
+    /// Wakes expired timers and re-arms the alarm
+    ///
+    /// Exported as the `MachineTimer` handler when the "rt" feature is
+    /// enabled. Otherwise, call this from the machine timer interrupt handler.
+    #[cfg_attr(feature = "rt", export_name = "MachineTimer")]
+    pub fn on_timer_interrupt() {
+        DRIVER.on_interrupt();
+    }
+}
+#[cfg(feature = "embassy-time-driver")]
+pub use embassy::on_timer_interrupt;
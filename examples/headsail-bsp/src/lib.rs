@@ -9,7 +9,7 @@ pub use headsail_sysctrl_pac as pac;
 
 // CPU specific modules
 #[cfg(feature = "hpc")]
-mod hpc;
+pub mod hpc;
 #[cfg(feature = "sysctrl")]
 pub mod sysctrl;
 #[cfg(feature = "hpc")]
@@ -20,10 +20,13 @@ compile_error!(
     "CPU-specific features \"hpc\" and feature \"sysctrl\" cannot be enabled at the same time. Select the one that matches the current target CPU."
 );
 
+#[cfg(feature = "async")]
+pub mod executor;
+
 #[cfg(feature = "alloc")]
 pub mod alloc;
 #[cfg(feature = "alloc")]
-pub use alloc::init_heap;
+pub use alloc::{init_heap, init_heap_at, is_heap_initialized};
 
 // Timer implementation is somewhat different on hardware and VP. We pick and
 // re-export the correct one here.
@@ -54,15 +57,30 @@ pub mod sprintln;
 #[cfg(any(feature = "panic-apb-uart0", feature = "panic-sysctrl-uart"))]
 mod ufmt_panic;
 pub use ufmt;
+#[cfg(any(feature = "defmt-apb-uart0", feature = "defmt-sysctrl-uart"))]
+mod defmt_logger;
+#[cfg(any(feature = "defmt-apb-uart0", feature = "defmt-sysctrl-uart"))]
+pub use defmt;
+#[cfg(any(feature = "defmt-apb-uart0", feature = "defmt-sysctrl-uart"))]
+pub mod log;
 
 #[cfg(all(feature = "panic-apb-uart0", feature = "panic-sysctrl-uart"))]
 compile_error!(
     "Features \"panic-apb-uart0\" and feature \"panic-sysctrl-uart\" cannot be enabled at the same time. Only one panic implementation must exist at a time."
 );
 
+#[cfg(all(feature = "defmt-apb-uart0", feature = "defmt-sysctrl-uart"))]
+compile_error!(
+    "Features \"defmt-apb-uart0\" and feature \"defmt-sysctrl-uart\" cannot be enabled at the same time. Only one defmt logger must exist at a time."
+);
+
+pub mod apb_gpio;
+pub mod apb_spim;
 pub mod apb_uart;
 pub mod mmap;
 mod mmio;
+#[cfg(feature = "sdmmc")]
+pub mod sd;
 pub mod sdram;
 pub mod tb;
 
@@ -0,0 +1,79 @@
+//! Leveled logging with deferred formatting
+//!
+//! The macros forward to the ones in [defmt] when their level is enabled with
+//! one of the `log-*` features, e.g., `-Flog-info` enables [error], [warn]
+//! and [info]. Messages are encoded on target and formatted on host, sa.
+//! `scripts/defmt_print.sh`.
+//!
+//! defmt filters levels on its own based on `DEFMT_LOG`, which defaults to
+//! `error`. Set `DEFMT_LOG=trace` to leave filtering to the `log-*` features.
+//! The crate using the macros must depend on `defmt` directly.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use headsail_bsp::log;
+//!
+//! log::info!("DLA done in {=u64} cycles", cycles);
+//! ```
+
+pub const ERROR: bool = cfg!(feature = "log-error");
+pub const WARN: bool = cfg!(feature = "log-warn");
+pub const INFO: bool = cfg!(feature = "log-info");
+pub const DEBUG: bool = cfg!(feature = "log-debug");
+pub const TRACE: bool = cfg!(feature = "log-trace");
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_error {
+    ($($arg:tt)*) => {
+        if $crate::log::ERROR {
+            $crate::defmt::error!($($arg)*)
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_warn {
+    ($($arg:tt)*) => {
+        if $crate::log::WARN {
+            $crate::defmt::warn!($($arg)*)
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_info {
+    ($($arg:tt)*) => {
+        if $crate::log::INFO {
+            $crate::defmt::info!($($arg)*)
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_debug {
+    ($($arg:tt)*) => {
+        if $crate::log::DEBUG {
+            $crate::defmt::debug!($($arg)*)
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_trace {
+    ($($arg:tt)*) => {
+        if $crate::log::TRACE {
+            $crate::defmt::trace!($($arg)*)
+        }
+    };
+}
+
+pub use crate::{
+    __log_debug as debug, __log_error as error, __log_info as info, __log_trace as trace,
+    __log_warn as warn,
+};
@@ -138,6 +138,121 @@ mod uart {
 }
 pub use self::uart::*;
 
+mod spim {
+    use super::EXT_ACCESS_BIT;
+
+    /// Location of APB SPI master #0
+    pub const SPIM0_ADDR: usize = 0xFFF02000 | EXT_ACCESS_BIT;
+    /// Location of APB SPI master #1
+    pub const SPIM1_ADDR: usize = 0xFFF03000 | EXT_ACCESS_BIT;
+
+    // The ASIC implements the PULP APB SPI master
+
+    /// Status register
+    ///
+    /// - write `[0:3]`: start a read, write, quad read or quad write
+    /// - write `[4]`: clear the FIFOs
+    /// - write `[8:11]`: chip select to assert for the transfer
+    /// - read `[0:6]`: controller state, `1` when idle
+    /// - read `[16:23]`: words in RX FIFO
+    /// - read `[24:31]`: words in TX FIFO
+    #[cfg(not(feature = "vp"))]
+    pub const SPIM_STATUS_OFS: usize = 0x0;
+    #[cfg(not(feature = "vp"))]
+    pub const SPIM_CLKDIV_OFS: usize = 0x4;
+    #[cfg(not(feature = "vp"))]
+    pub const SPIM_SPICMD_OFS: usize = 0x8;
+    #[cfg(not(feature = "vp"))]
+    pub const SPIM_SPIADR_OFS: usize = 0xC;
+    /// Transfer length register
+    ///
+    /// - `[0:5]`: command length in bits
+    /// - `[8:13]`: address length in bits
+    /// - `[16:31]`: data length in bits
+    #[cfg(not(feature = "vp"))]
+    pub const SPIM_SPILEN_OFS: usize = 0x10;
+    #[cfg(not(feature = "vp"))]
+    pub const SPIM_SPIDUM_OFS: usize = 0x14;
+    #[cfg(not(feature = "vp"))]
+    pub const SPIM_TXFIFO_OFS: usize = 0x18;
+    #[cfg(not(feature = "vp"))]
+    pub const SPIM_RXFIFO_OFS: usize = 0x20;
+
+    // The VP models the masters with the STM32 SPI
+
+    /// Control register 1
+    #[cfg(feature = "vp")]
+    pub const SPIM_CR1_OFS: usize = 0x0;
+    /// Status register
+    #[cfg(feature = "vp")]
+    pub const SPIM_SR_OFS: usize = 0x8;
+    /// Data register
+    #[cfg(feature = "vp")]
+    pub const SPIM_DR_OFS: usize = 0xC;
+}
+pub use self::spim::*;
+
+mod gpio {
+    use super::EXT_ACCESS_BIT;
+
+    /// Location of APB GPIO
+    pub const GPIO_ADDR: usize = 0xFFF04000 | EXT_ACCESS_BIT;
+
+    // The ASIC implements the PULP APB GPIO
+
+    /// Direction, `1` for output
+    #[cfg(not(feature = "vp"))]
+    pub const GPIO_PADDIR_OFS: usize = 0x0;
+    #[cfg(not(feature = "vp"))]
+    pub const GPIO_PADIN_OFS: usize = 0x4;
+    #[cfg(not(feature = "vp"))]
+    pub const GPIO_PADOUT_OFS: usize = 0x8;
+    #[cfg(not(feature = "vp"))]
+    pub const GPIO_INTEN_OFS: usize = 0xC;
+    /// Interrupt type 0, `1` for edge and `0` for level triggered
+    #[cfg(not(feature = "vp"))]
+    pub const GPIO_INTTYPE0_OFS: usize = 0x10;
+    /// Interrupt type 1, `1` for falling edge / active low and `0` for rising
+    /// edge / active high
+    #[cfg(not(feature = "vp"))]
+    pub const GPIO_INTTYPE1_OFS: usize = 0x14;
+    /// Interrupt status, cleared when read
+    #[cfg(not(feature = "vp"))]
+    pub const GPIO_INTSTATUS_OFS: usize = 0x18;
+
+    // The VP models the GPIO with the CC2538 GPIO
+
+    /// Data, masked by address bits `[2:9]`. This offset accesses all pins.
+    #[cfg(feature = "vp")]
+    pub const GPIO_DATA_OFS: usize = 0x3FC;
+    /// Direction, `1` for output
+    #[cfg(feature = "vp")]
+    pub const GPIO_DIR_OFS: usize = 0x400;
+    /// Interrupt sense, `1` for level and `0` for edge triggered
+    #[cfg(feature = "vp")]
+    pub const GPIO_IS_OFS: usize = 0x404;
+    /// Interrupt on both edges
+    #[cfg(feature = "vp")]
+    pub const GPIO_IBE_OFS: usize = 0x408;
+    /// Interrupt event, `1` for rising edge and `0` for falling edge
+    #[cfg(feature = "vp")]
+    pub const GPIO_IEV_OFS: usize = 0x40C;
+    #[cfg(feature = "vp")]
+    pub const GPIO_IE_OFS: usize = 0x410;
+    /// Masked interrupt status
+    #[cfg(feature = "vp")]
+    pub const GPIO_MIS_OFS: usize = 0x418;
+    /// Interrupt clear, write `1` to clear
+    #[cfg(feature = "vp")]
+    pub const GPIO_IC_OFS: usize = 0x41C;
+}
+pub use self::gpio::*;
+
+/// VP-only register that ends a test run with the written exit code, sa.
+/// [tb::exit](crate::tb::exit)
+#[cfg(feature = "vp")]
+pub const TEST_FINISHER_ADDR: usize = 0xFFF0F000 | EXT_ACCESS_BIT;
+
 // HPC's timers
 pub const TIMER0_ADDR: usize = 0x5_0000;
 pub const TIMER1_ADDR: usize = 0x5_0010;
@@ -0,0 +1,60 @@
+//! SD cards in SPI mode
+//!
+//! [SdCard] implements [embedded_sdmmc::BlockDevice] on top of any
+//! [SpiDevice], e.g., [UdmaSpim](crate::sysctrl::udma::UdmaSpim) on SysCtrl
+//! or an [ApbSpim](crate::apb_spim::ApbSpim) with a GPIO chip select on HPC.
+//! [VolumeManager] gives access to FAT filesystems on the card.
+//!
+//! Delays use the `time` module of the current CPU. On SysCtrl, call
+//! `time::init` first.
+//!
+//! The card expects at least 74 clock cycles with chip select deasserted
+//! before [SdCard] talks to it, sa.
+//! [UdmaSpim::send_idle_clocks](crate::sysctrl::udma::UdmaSpim::send_idle_clocks).
+use embedded_hal::spi::SpiDevice;
+pub use embedded_sdmmc;
+use embedded_sdmmc::{TimeSource, Timestamp};
+
+#[cfg(feature = "hpc")]
+use crate::hpc::time::Delay;
+#[cfg(feature = "sysctrl")]
+use crate::sysctrl::time::Delay;
+
+/// SD card block device
+pub type SdCard<SPI> = embedded_sdmmc::SdCard<SPI, Delay>;
+
+/// FAT filesystem access on an [SdCard]
+pub type VolumeManager<SPI> = embedded_sdmmc::VolumeManager<SdCard<SPI>, FixedTime>;
+
+/// Creates an [SdCard] on `spi`. The card is initialized on first use.
+pub fn sd_card<SPI: SpiDevice<u8>>(spi: SPI) -> SdCard<SPI> {
+    SdCard::new(spi, Delay)
+}
+
+/// Creates a [VolumeManager] for the SD card on `spi`
+pub fn volume_manager<SPI: SpiDevice<u8>>(spi: SPI) -> VolumeManager<SPI> {
+    VolumeManager::new(sd_card(spi), FixedTime)
+}
+
+/// Headsail has no real-time clock, so files are stamped with
+/// [FixedTime::TIMESTAMP]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FixedTime;
+
+impl FixedTime {
+    /// 2024-01-01 00:00:00
+    pub const TIMESTAMP: Timestamp = Timestamp {
+        year_since_1970: 54,
+        zero_indexed_month: 0,
+        zero_indexed_day: 0,
+        hours: 0,
+        minutes: 0,
+        seconds: 0,
+    };
+}
+
+impl TimeSource for FixedTime {
+    fn get_timestamp(&self) -> Timestamp {
+        Self::TIMESTAMP
+    }
+}
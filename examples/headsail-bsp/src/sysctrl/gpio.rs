@@ -1,7 +1,35 @@
-use core::marker::PhantomData;
+use core::{
+    convert::Infallible,
+    marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::{
+    itc, mmap,
+    soc_ctrl::{self, Pull},
+};
+use crate::{mask_u32, read_u32, toggle_u32, unmask_u32, write_u32};
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin, StatefulOutputPin};
+use riscv::interrupt;
+
+/// ITC line raised by GPIO edge interrupts
+///
+/// Same as the GPIO line of the PULPissimo fabric controller, i.e.,
+/// `ARCHI_FC_EVT_GPIO` in the PULP runtime.
+pub const GPIO_IRQ: usize = 15;
+
+/// Edge that raises a GPIO interrupt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    Falling = 0b00,
+    Rising = 0b01,
+    Any = 0b10,
+}
 
-use super::{mmap, soc_ctrl};
-use crate::{mask_u32, toggle_u32, unmask_u32};
+/// Interrupt callbacks as `fn()` addresses, indexed by GPIO. `0` when unset.
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+static CALLBACKS: [AtomicUsize; 32] = [NO_CALLBACK; 32];
 
 /// Type-state trait for GPIO in different states
 pub trait GpioState {}
@@ -33,6 +61,8 @@ impl<const IDX: u32> Gpio<IDX, Uninit> {
 
     pub fn into_input(self) -> Gpio<IDX, Input> {
         unmask_u32(mmap::GPIO_DIR, 1 << IDX);
+        // Enable input sampling
+        mask_u32(mmap::GPIO_EN, 1 << IDX);
 
         Gpio { _pd: PhantomData }
     }
@@ -44,6 +74,63 @@ impl<const IDX: u32> Gpio<IDX, Uninit> {
     }
 }
 
+impl<const IDX: u32> Gpio<IDX, Input> {
+    pub fn is_high(&self) -> bool {
+        read_u32(mmap::GPIO_IN) & (1 << IDX) != 0
+    }
+
+    pub fn is_low(&self) -> bool {
+        !self.is_high()
+    }
+
+    /// Calls `callback` in interrupt context on every `edge`
+    ///
+    /// The interrupt is taken on ITC line [GPIO_IRQ], which must be dispatched
+    /// to [on_gpio_interrupt], e.g., with [itc::dispatch] when the "async"
+    /// feature is enabled.
+    /// Interrupts must also be enabled globally.
+    pub fn listen(&mut self, edge: Edge, callback: fn()) {
+        let (reg, ofs) = if IDX <= 15 {
+            (mmap::GPIO_INT_TYPE0, IDX * 2)
+        } else {
+            (mmap::GPIO_INT_TYPE1, (IDX - 16) * 2)
+        };
+        CALLBACKS[IDX as usize].store(callback as usize, Ordering::Release);
+        interrupt::free(|| {
+            let ty = read_u32(reg) & !(0b11 << ofs);
+            write_u32(reg, ty | ((edge as u32) << ofs));
+            mask_u32(mmap::GPIO_INT_EN, 1 << IDX);
+        });
+        itc::enable(GPIO_IRQ);
+    }
+
+    /// Stops calling the callback set with [Gpio::listen]
+    pub fn unlisten(&mut self) {
+        interrupt::free(|| unmask_u32(mmap::GPIO_INT_EN, 1 << IDX));
+        CALLBACKS[IDX as usize].store(0, Ordering::Release);
+    }
+}
+
+/// Calls the callbacks of the GPIOs that raised an interrupt
+///
+/// Call this from the handler of ITC line [GPIO_IRQ].
+pub fn on_gpio_interrupt() {
+    // Reading the status clears it
+    let status = read_u32(mmap::GPIO_INT_STATUS);
+    itc::unpend(GPIO_IRQ);
+    for (idx, callback) in CALLBACKS.iter().enumerate() {
+        if status & (1 << idx) == 0 {
+            continue;
+        }
+        let addr = callback.load(Ordering::Acquire);
+        if addr != 0 {
+            // SAFETY: only `fn()` addresses are stored in `CALLBACKS`
+            let callback: fn() = unsafe { core::mem::transmute(addr) };
+            callback();
+        }
+    }
+}
+
 impl<const IDX: u32> Gpio<IDX, Output> {
     pub fn toggle(&mut self) {
         toggle_u32(mmap::GPIO_OUT, 1 << IDX);
@@ -59,19 +146,17 @@ impl<const IDX: u32> Gpio<IDX, Output> {
 }
 
 impl<const IDX: u32, S: GpioState> Gpio<IDX, S> {
+    #[inline]
+    pub fn set_pull(&mut self, pull: Pull) {
+        soc_ctrl::pad_set_pull(IDX, pull);
+    }
+
     /// Release pad back to its original function
     ///
     /// Pins can be released in any state. GPIO register configurations will
     /// retain their state (until overridden again by HAL methods).
     pub fn release(self) -> soc_ctrl::Pad<IDX> {
-        unmask_u32(
-            if IDX <= 15 {
-                mmap::PADMUX0
-            } else {
-                mmap::PADMUX1
-            },
-            (soc_ctrl::PadFn::Gpio as u32) << (IDX * 2),
-        );
+        soc_ctrl::pad_set_fn(IDX, soc_ctrl::PadFn::Default);
 
         soc_ctrl::Pad::<IDX> {}
     }
@@ -84,3 +169,39 @@ impl<const IDX: u32, S: GpioState> Gpio<IDX, S> {
         Self { _pd: PhantomData }
     }
 }
+
+impl<const IDX: u32, S: GpioState> ErrorType for Gpio<IDX, S> {
+    type Error = Infallible;
+}
+
+impl<const IDX: u32> InputPin for Gpio<IDX, Input> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(Gpio::is_high(self))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(Gpio::is_low(self))
+    }
+}
+
+impl<const IDX: u32> OutputPin for Gpio<IDX, Output> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Gpio::set_low(self);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Gpio::set_high(self);
+        Ok(())
+    }
+}
+
+impl<const IDX: u32> StatefulOutputPin for Gpio<IDX, Output> {
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(read_u32(mmap::GPIO_OUT) & (1 << IDX) != 0)
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(read_u32(mmap::GPIO_OUT) & (1 << IDX) == 0)
+    }
+}
@@ -0,0 +1,365 @@
+//! Boots HPC from SysCtrl
+//!
+//! HPC is brought up in the following order:
+//!
+//! 1. [BootStep::PowerOn]: take the HPC, interconnect, TLP and SDRAM subsystems
+//!    out of reset and enable their clocks
+//! 2. [BootStep::Sdram]: set SDRAM to DDR mode and enable its AXI port
+//! 3. [BootStep::ClusterConfig]: configure the HPC executable and cached regions
+//! 4. [BootStep::Load]: copy the payload into bootram and/or SDRAM
+//! 5. [BootStep::ResetVector]: point the HPC reset vector at the payload entry
+//! 6. [BootStep::Release]: enable the clock of HPC core #0, which starts executing
+//!
+//! Use [boot], [boot_elf] or [boot_raw] to run all of the steps, or call the steps
+//! individually for finer control. [BootStep::Load] and [BootStep::ResetVector]
+//! go through a [Loader].
+//!
+//! Addresses are given as seen by HPC and translated to the SysCtrl view.
+use super::soc_ctrl::{self, Subsystem};
+use crate::{pac, sdram};
+
+/// HPC bootram as seen by HPC
+pub const HPC_BOOTRAM_ADDR: u64 = 0x1_0000;
+/// HPC bootram as seen by SysCtrl
+pub const HPC_BOOTRAM_SYSCTRL_ADDR: usize = 0xFFE1_0000;
+pub const HPC_BOOTRAM_SIZE: usize = 0x8000;
+/// HPC starts executing from the beginning of bootram
+pub const HPC_RESET_VECTOR: u64 = HPC_BOOTRAM_ADDR;
+
+/// The part of HPC SDRAM that is visible to SysCtrl, as seen by HPC
+///
+/// SysCtrl sees this range at the same address without the external access
+/// bit, i.e., `0x2000_0000..0x7000_0000`.
+pub const HPC_SDRAM_SHARED: core::ops::Range<u64> = 0x1_2000_0000..0x1_7000_0000;
+const EXT_ACCESS_BIT: u64 = 1 << 32;
+
+/// Subsystems required by HPC, in power-on order
+const SUBSYSTEMS: [Subsystem; 4] = [
+    Subsystem::Hpc,
+    Subsystem::Icn,
+    Subsystem::Tlp,
+    Subsystem::Sdram,
+];
+
+const SDRAM_DDR_MODE: u32 = 0b1;
+const SDRAM_AXI_ENABLE: u32 = 0b1 << 1;
+
+const SDRAM_REGION_LEN: u32 = 0x7000_0000;
+const C2C_REGION_LEN: u32 = 0x2000_0000;
+const SRAMS_REGION_LEN: u32 = 0x10_0000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BootStep {
+    PowerOn,
+    Sdram,
+    ClusterConfig,
+    Load,
+    ResetVector,
+    Release,
+}
+
+impl BootStep {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            BootStep::PowerOn => "power on HPC, ICN, TLP & SDRAM",
+            BootStep::Sdram => "configure SDRAM",
+            BootStep::ClusterConfig => "configure HPC cluster",
+            BootStep::Load => "load payload",
+            BootStep::ResetVector => "set reset vector",
+            BootStep::Release => "release HPC core #0",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BootError {
+    /// The subsystem did not come out of reset or its clock is not enabled
+    PowerOn(Subsystem),
+    /// The address (as seen by HPC) is not accessible from SysCtrl
+    Unreachable(u64),
+    /// The payload is not a little-endian, 64-bit RISC-V ELF executable
+    InvalidElf,
+    /// The payload image header is invalid or the image is corrupted
+    InvalidImage,
+    /// The payload overlaps with the jump trampoline at the reset vector
+    ResetVectorConflict(u64),
+}
+
+/// Translates an HPC address range into a SysCtrl address
+fn to_sysctrl(hpc_addr: u64, len: usize) -> Result<usize, BootError> {
+    let end = hpc_addr
+        .checked_add(len as u64)
+        .ok_or(BootError::Unreachable(hpc_addr))?;
+    let bootram = HPC_BOOTRAM_ADDR..HPC_BOOTRAM_ADDR + HPC_BOOTRAM_SIZE as u64;
+
+    if bootram.contains(&hpc_addr) && end <= bootram.end {
+        Ok(HPC_BOOTRAM_SYSCTRL_ADDR + (hpc_addr - HPC_BOOTRAM_ADDR) as usize)
+    } else if HPC_SDRAM_SHARED.contains(&hpc_addr) && end <= HPC_SDRAM_SHARED.end {
+        Ok((hpc_addr - EXT_ACCESS_BIT) as usize)
+    } else {
+        Err(BootError::Unreachable(hpc_addr))
+    }
+}
+
+/// Enables the HPC, interconnect, TLP and SDRAM subsystems and verifies they
+/// are running. HPC cores remain gated.
+///
+/// Each subsystem holds a [soc_ctrl::enable] reference from here on.
+pub fn power_on() -> Result<(), BootError> {
+    for ss in SUBSYSTEMS {
+        soc_ctrl::enable(ss);
+        if !soc_ctrl::is_enabled(ss) {
+            return Err(BootError::PowerOn(ss));
+        }
+    }
+    Ok(())
+}
+
+/// Sets SDRAM to DDR mode and enables its AXI port
+pub fn configure_sdram() {
+    sdram::sdram_cfg_axi_ddr_mode_mask(SDRAM_DDR_MODE);
+    sdram::sdram_cfg_axi_enable_mask(SDRAM_AXI_ENABLE);
+}
+
+/// Configures the executable and cached regions of the HPC cluster
+///
+/// SDRAM is made executable and cacheable, C2C and the shared SRAMs are made
+/// executable.
+pub fn configure_cluster() {
+    let hpc = unsafe { pac::Hpc::steal() };
+    let cluster_cfg = hpc.cluster_config();
+
+    cluster_cfg
+        .execute_region_length2()
+        .write(|w| unsafe { w.bits(SDRAM_REGION_LEN) });
+    cluster_cfg
+        .cached_region_addr_length0()
+        .write(|w| unsafe { w.bits(SDRAM_REGION_LEN) });
+    cluster_cfg
+        .execute_region_length3()
+        .write(|w| unsafe { w.bits(C2C_REGION_LEN) });
+    cluster_cfg
+        .execute_region_length4()
+        .write(|w| unsafe { w.bits(SRAMS_REGION_LEN) });
+}
+
+/// Copies `image` to `hpc_addr` and zero-fills up to `mem_len` without
+/// recording the range
+fn write_to_hpc(image: &[u8], hpc_addr: u64, mem_len: usize) -> Result<(), BootError> {
+    let dst = to_sysctrl(hpc_addr, mem_len.max(image.len()))? as *mut u8;
+    unsafe {
+        core::ptr::copy_nonoverlapping(image.as_ptr(), dst, image.len());
+        if mem_len > image.len() {
+            core::ptr::write_bytes(dst.add(image.len()), 0, mem_len - image.len());
+        }
+    }
+    Ok(())
+}
+
+fn read_u16(buf: &[u8], ofs: usize) -> Result<u16, BootError> {
+    buf.get(ofs..ofs.checked_add(2).ok_or(BootError::InvalidElf)?)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or(BootError::InvalidElf)
+}
+
+fn read_u32(buf: &[u8], ofs: usize) -> Result<u32, BootError> {
+    buf.get(ofs..ofs.checked_add(4).ok_or(BootError::InvalidElf)?)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(BootError::InvalidElf)
+}
+
+fn read_u64(buf: &[u8], ofs: usize) -> Result<u64, BootError> {
+    let hi_ofs = ofs.checked_add(4).ok_or(BootError::InvalidElf)?;
+    Ok(read_u32(buf, ofs)? as u64 | (read_u32(buf, hi_ofs)? as u64) << 32)
+}
+
+/// Reads a 64-bit offset or size, which must fit in SysCtrl's `usize`
+fn read_usize(buf: &[u8], ofs: usize) -> Result<usize, BootError> {
+    usize::try_from(read_u64(buf, ofs)?).map_err(|_| BootError::InvalidElf)
+}
+
+/// Returns `base + ofs`, failing on overflow
+fn offset(base: usize, ofs: usize) -> Result<usize, BootError> {
+    base.checked_add(ofs).ok_or(BootError::InvalidElf)
+}
+
+/// Loads payload segments to HPC and keeps track of the ones overlapping the
+/// trampoline at the reset vector
+#[derive(Clone, Debug, Default)]
+pub struct Loader {
+    /// Start of the first segment loaded over the trampoline, if any
+    trampoline_overlap: Option<u64>,
+}
+
+impl Loader {
+    pub const fn new() -> Self {
+        Self {
+            trampoline_overlap: None,
+        }
+    }
+
+    /// Copies a payload segment to `hpc_addr` and zero-fills up to `mem_len`.
+    /// Segments overlapping the trampoline are recorded for
+    /// [Loader::set_reset_vector].
+    fn copy_to_hpc(
+        &mut self,
+        image: &[u8],
+        hpc_addr: u64,
+        mem_len: usize,
+    ) -> Result<(), BootError> {
+        write_to_hpc(image, hpc_addr, mem_len)?;
+        // `write_to_hpc` has checked that the range doesn't overflow
+        let end = hpc_addr + mem_len.max(image.len()) as u64;
+        if hpc_addr < HPC_RESET_VECTOR + TRAMPOLINE_LEN as u64 && end > HPC_RESET_VECTOR {
+            self.trampoline_overlap.get_or_insert(hpc_addr);
+        }
+        Ok(())
+    }
+
+    /// Loads a raw binary `image` to `hpc_addr`
+    pub fn load_raw(&mut self, image: &[u8], hpc_addr: u64) -> Result<(), BootError> {
+        self.copy_to_hpc(image, hpc_addr, image.len())
+    }
+
+    /// Loads the `PT_LOAD` segments of a 64-bit RISC-V ELF to their physical
+    /// addresses. Returns the entry point.
+    pub fn load_elf(&mut self, elf: &[u8]) -> Result<u64, BootError> {
+        const ELF_MAGIC: &[u8] = b"\x7fELF";
+        const ELFCLASS64: u8 = 2;
+        const ELFDATA2LSB: u8 = 1;
+        const EM_RISCV: u16 = 243;
+        const PT_LOAD: u32 = 1;
+
+        if elf.get(0..4) != Some(ELF_MAGIC)
+            || elf.get(4) != Some(&ELFCLASS64)
+            || elf.get(5) != Some(&ELFDATA2LSB)
+            || read_u16(elf, 18)? != EM_RISCV
+        {
+            return Err(BootError::InvalidElf);
+        }
+
+        let entry = read_u64(elf, 24)?;
+        let phoff = read_usize(elf, 32)?;
+        let phentsize = read_u16(elf, 54)? as usize;
+        let phnum = read_u16(elf, 56)? as usize;
+
+        for idx in 0..phnum {
+            let ph = idx
+                .checked_mul(phentsize)
+                .ok_or(BootError::InvalidElf)
+                .and_then(|ph| offset(phoff, ph))?;
+            if read_u32(elf, ph)? != PT_LOAD {
+                continue;
+            }
+            let seg_offset = read_usize(elf, offset(ph, 8)?)?;
+            let paddr = read_u64(elf, offset(ph, 24)?)?;
+            let filesz = read_usize(elf, offset(ph, 32)?)?;
+            let memsz = read_usize(elf, offset(ph, 40)?)?;
+
+            let data = elf
+                .get(seg_offset..offset(seg_offset, filesz)?)
+                .ok_or(BootError::InvalidElf)?;
+            self.copy_to_hpc(data, paddr, memsz)?;
+        }
+
+        Ok(entry)
+    }
+
+    /// Makes HPC jump to `entry` on reset
+    ///
+    /// If `entry` is not the reset vector, a trampoline is written at the start
+    /// of bootram. The payload must then leave the first [TRAMPOLINE_LEN] bytes
+    /// of bootram free, which is checked against the segments loaded with this
+    /// loader.
+    pub fn set_reset_vector(&self, entry: u64) -> Result<(), BootError> {
+        if entry == HPC_RESET_VECTOR {
+            return Ok(());
+        }
+        if (HPC_RESET_VECTOR..HPC_RESET_VECTOR + TRAMPOLINE_LEN as u64).contains(&entry) {
+            return Err(BootError::ResetVectorConflict(entry));
+        }
+        if let Some(addr) = self.trampoline_overlap {
+            return Err(BootError::ResetVectorConflict(addr));
+        }
+
+        let mut trampoline = [0u8; TRAMPOLINE_LEN];
+        for (idx, insn) in TRAMPOLINE_INSNS.iter().enumerate() {
+            trampoline[idx * 4..idx * 4 + 4].copy_from_slice(&insn.to_le_bytes());
+        }
+        trampoline[16..].copy_from_slice(&entry.to_le_bytes());
+        write_to_hpc(&trampoline, HPC_RESET_VECTOR, TRAMPOLINE_LEN)
+    }
+}
+
+/// `auipc t0, 0; ld t0, 16(t0); jr t0; nop`, followed by the 64-bit target
+const TRAMPOLINE_INSNS: [u32; 4] = [0x0000_0297, 0x0102_b283, 0x0002_8067, 0x0000_0013];
+pub const TRAMPOLINE_LEN: usize = 24;
+
+/// Ungates the clock of HPC core #0, which starts executing from the reset
+/// vector
+pub fn release() {
+    soc_ctrl::set_hpc_core0_clock(true);
+}
+
+/// Runs the steps up to and including [BootStep::ClusterConfig]
+fn prepare(report: &mut impl FnMut(BootStep, Result<(), BootError>)) -> Result<(), BootError> {
+    let res = power_on();
+    report(BootStep::PowerOn, res);
+    res?;
+    configure_sdram();
+    report(BootStep::Sdram, Ok(()));
+    configure_cluster();
+    report(BootStep::ClusterConfig, Ok(()));
+    Ok(())
+}
+
+/// Runs the steps starting from [BootStep::ResetVector]
+fn start(
+    loader: &Loader,
+    entry: u64,
+    report: &mut impl FnMut(BootStep, Result<(), BootError>),
+) -> Result<(), BootError> {
+    let res = loader.set_reset_vector(entry);
+    report(BootStep::ResetVector, res);
+    res?;
+    release();
+    report(BootStep::Release, Ok(()));
+    Ok(())
+}
+
+/// Boots HPC into a payload loaded by `load`
+///
+/// `load` runs once SDRAM is available, loads the payload with the given
+/// [Loader] and returns the entry point of the payload. `report` is called
+/// with the outcome of each step. Booting stops at the first failing step.
+pub fn boot(
+    load: impl FnOnce(&mut Loader) -> Result<u64, BootError>,
+    mut report: impl FnMut(BootStep, Result<(), BootError>),
+) -> Result<(), BootError> {
+    prepare(&mut report)?;
+    let mut loader = Loader::new();
+    let entry = load(&mut loader);
+    report(BootStep::Load, entry.map(|_| ()));
+    start(&loader, entry?, &mut report)
+}
+
+/// Boots HPC into an ELF executable. See [boot].
+pub fn boot_elf(
+    elf: &[u8],
+    report: impl FnMut(BootStep, Result<(), BootError>),
+) -> Result<(), BootError> {
+    boot(|loader| loader.load_elf(elf), report)
+}
+
+/// Boots HPC into a raw binary `image` loaded at and entered from `hpc_addr`.
+/// See [boot].
+pub fn boot_raw(
+    image: &[u8],
+    hpc_addr: u64,
+    report: impl FnMut(BootStep, Result<(), BootError>),
+) -> Result<(), BootError> {
+    boot(
+        |loader| loader.load_raw(image, hpc_addr).map(|_| hpc_addr),
+        report,
+    )
+}
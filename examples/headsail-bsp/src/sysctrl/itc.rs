@@ -0,0 +1,129 @@
+//! Driver for the PULP interrupt controller (ITC) of SysCtrl
+//!
+//! Each of the 32 ITC lines is wired to the SysCtrl core interrupt with the
+//! same index, i.e., a line must be unmasked on the ITC and enabled in `mie`
+//! to be taken. uDMA and other SoC events are queued in the ITC event FIFO and
+//! signaled on line [EVENT_IRQ].
+use super::mmap;
+use crate::{read_u32, write_u32};
+
+/// ITC line signaling a pending event in the event FIFO
+pub const EVENT_IRQ: usize = 26;
+
+/// ITC line used to pend software interrupts
+///
+/// Line 3 is taken as the core's machine software interrupt, i.e., it is
+/// dispatched to the `MachineSoft` handler.
+pub const SOFT_IRQ: usize = 3;
+
+/// Unmasks `line` on the ITC and enables the matching core interrupt
+pub fn enable(line: usize) {
+    write_u32(mmap::ITC_MASK_SET, 1 << line);
+    unsafe { core::arch::asm!("csrs mie, {0}", in(reg) 1 << line) };
+}
+
+/// Masks `line` on the ITC and disables the matching core interrupt
+pub fn disable(line: usize) {
+    unsafe { core::arch::asm!("csrc mie, {0}", in(reg) 1 << line) };
+    write_u32(mmap::ITC_MASK_CLR, 1 << line);
+}
+
+/// Returns `true` if `line` is unmasked on the ITC
+pub fn is_enabled(line: usize) -> bool {
+    read_u32(mmap::ITC_MASK) & (1 << line) != 0
+}
+
+/// Raises `line` from software
+pub fn pend(line: usize) {
+    write_u32(mmap::ITC_INT_SET, 1 << line);
+}
+
+/// Clears a pending `line`
+pub fn unpend(line: usize) {
+    write_u32(mmap::ITC_INT_CLR, 1 << line);
+}
+
+/// Returns `true` if `line` is pending
+pub fn is_pending(line: usize) -> bool {
+    read_u32(mmap::ITC_INT) & (1 << line) != 0
+}
+
+/// Pops the oldest event from the event FIFO
+///
+/// Only valid while [EVENT_IRQ] is pending.
+pub fn pop_event() -> u8 {
+    read_u32(mmap::ITC_FIFO) as u8
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use super::{pop_event, unpend, EVENT_IRQ};
+    use core::{future::poll_fn, task::Poll};
+    use embassy_sync::waitqueue::AtomicWaker;
+
+    /// Number of distinct events supported by the SysCtrl event controller
+    const EVENT_COUNT: usize = 32;
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+    static EVENT_WAKERS: [AtomicWaker; EVENT_COUNT] = [NEW_WAKER; EVENT_COUNT];
+
+    /// Waits until `done` returns `true`, re-checking it every time `event`
+    /// fires
+    ///
+    /// Requires the event FIFO interrupt to be dispatched to
+    /// [on_event_interrupt], e.g., with [dispatch].
+    pub async fn wait_for_event(event: u8, done: impl FnMut() -> bool) {
+        wait_for_events(&[event], done).await
+    }
+
+    /// Like [wait_for_event], but re-checks `done` when any of `events` fires
+    pub async fn wait_for_events(events: &[u8], mut done: impl FnMut() -> bool) {
+        super::enable(EVENT_IRQ);
+        poll_fn(|cx| {
+            for &event in events {
+                EVENT_WAKERS[event as usize].register(cx.waker());
+            }
+            if done() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Wakes the tasks waiting on the event at the head of the event FIFO
+    pub fn on_event_interrupt() {
+        unpend(EVENT_IRQ);
+        let event = pop_event() as usize % EVENT_COUNT;
+        EVENT_WAKERS[event].wake();
+    }
+
+    /// Dispatches the ITC lines serviced by the async drivers
+    ///
+    /// Returns `false` if `line` is not one of them, so the application can
+    /// handle its own lines. Call this from `DefaultHandler`:
+    ///
+    /// ```ignore
+    /// #[export_name = "DefaultHandler"]
+    /// fn default_handler() {
+    ///     let line = riscv::register::mcause::read().code();
+    ///     if !itc::dispatch(line) {
+    ///         // Application specific lines
+    ///     }
+    /// }
+    /// ```
+    pub fn dispatch(line: usize) -> bool {
+        match line {
+            EVENT_IRQ => on_event_interrupt(),
+            #[cfg(feature = "embassy-time-driver")]
+            crate::sysctrl::time::TIMER_IRQ => crate::sysctrl::time::on_timer_interrupt(),
+            crate::sysctrl::gpio::GPIO_IRQ => crate::sysctrl::gpio::on_gpio_interrupt(),
+            _ => return false,
+        }
+        true
+    }
+}
+#[cfg(feature = "async")]
+pub use asynch::*;
@@ -6,11 +6,18 @@ pub(crate) const SYSCTRL_ADDR: usize = 0x1a10_0000;
 
 pub(crate) const GPIO_ADDR: usize = SYSCTRL_ADDR + 0x1000;
 pub(crate) const GPIO_DIR: usize = GPIO_ADDR + 0x0;
+pub(crate) const GPIO_EN: usize = GPIO_ADDR + 0x4;
+pub(crate) const GPIO_IN: usize = GPIO_ADDR + 0x8;
 pub(crate) const GPIO_OUT: usize = GPIO_ADDR + 0xc;
+pub(crate) const GPIO_INT_EN: usize = GPIO_ADDR + 0x18;
+pub(crate) const GPIO_INT_TYPE0: usize = GPIO_ADDR + 0x1c;
+pub(crate) const GPIO_INT_TYPE1: usize = GPIO_ADDR + 0x20;
+pub(crate) const GPIO_INT_STATUS: usize = GPIO_ADDR + 0x24;
 
 pub(crate) const SOC_CONTROL_ADDR: usize = SYSCTRL_ADDR + 0x4000;
 pub const PADMUX0: usize = SOC_CONTROL_ADDR + 0x10;
 pub const PADMUX1: usize = SOC_CONTROL_ADDR + 0x14;
+pub const PADCFG0: usize = SOC_CONTROL_ADDR + 0x20;
 
 pub const SS_RESET_EN: usize = SOC_CONTROL_ADDR + 0xb0;
 pub const SS_CLK_CTRL1: usize = SOC_CONTROL_ADDR + 0x98;
@@ -18,3 +25,30 @@ pub const SS_CLK_CTRL2: usize = SOC_CONTROL_ADDR + 0x9c;
 pub const SS_CLK_CTRL3: usize = SOC_CONTROL_ADDR + 0xb8;
 
 pub const PERIPH_CLK_DIV: usize = SOC_CONTROL_ADDR + 0xA8;
+
+pub(crate) const UDMA_ADDR: usize = SYSCTRL_ADDR + 0x2000;
+pub(crate) const UDMA_I2C0_ADDR: usize = UDMA_ADDR + 0x180;
+pub(crate) const UDMA_I2C0_RX_SADDR: usize = UDMA_I2C0_ADDR + 0x0;
+pub(crate) const UDMA_I2C0_RX_SIZE: usize = UDMA_I2C0_ADDR + 0x4;
+pub(crate) const UDMA_I2C0_RX_CFG: usize = UDMA_I2C0_ADDR + 0x8;
+pub(crate) const UDMA_I2C0_TX_SADDR: usize = UDMA_I2C0_ADDR + 0x10;
+pub(crate) const UDMA_I2C0_TX_SIZE: usize = UDMA_I2C0_ADDR + 0x14;
+pub(crate) const UDMA_I2C0_TX_CFG: usize = UDMA_I2C0_ADDR + 0x18;
+pub(crate) const UDMA_I2C0_STATUS: usize = UDMA_I2C0_ADDR + 0x20;
+pub(crate) const UDMA_I2C0_SETUP: usize = UDMA_I2C0_ADDR + 0x24;
+
+pub(crate) const ITC_ADDR: usize = SYSCTRL_ADDR + 0x9000;
+pub(crate) const ITC_MASK: usize = ITC_ADDR + 0x0;
+pub(crate) const ITC_MASK_SET: usize = ITC_ADDR + 0x4;
+pub(crate) const ITC_MASK_CLR: usize = ITC_ADDR + 0x8;
+pub(crate) const ITC_INT: usize = ITC_ADDR + 0xc;
+pub(crate) const ITC_INT_SET: usize = ITC_ADDR + 0x10;
+pub(crate) const ITC_INT_CLR: usize = ITC_ADDR + 0x14;
+pub(crate) const ITC_FIFO: usize = ITC_ADDR + 0x24;
+
+pub(crate) const TIMER_ADDR: usize = SYSCTRL_ADDR + 0xb000;
+pub(crate) const TIMER_CFG_LO: usize = TIMER_ADDR + 0x0;
+pub(crate) const TIMER_CNT_LO: usize = TIMER_ADDR + 0x8;
+pub(crate) const TIMER_CNT_HI: usize = TIMER_ADDR + 0xc;
+pub(crate) const TIMER_CMP_LO: usize = TIMER_ADDR + 0x10;
+pub(crate) const TIMER_CMP_HI: usize = TIMER_ADDR + 0x14;
@@ -1,7 +1,14 @@
 //! Abstractions that only exist on SysCtrl
 pub mod gpio;
+#[cfg(feature = "pac")]
+pub mod hpc_boot;
+pub mod itc;
+pub mod pad_mux;
 pub mod soc_ctrl;
 #[cfg(feature = "pac")]
+pub mod spi_flash;
+pub mod time;
+#[cfg(feature = "pac")]
 pub mod udma;
 
 pub mod interrupt;
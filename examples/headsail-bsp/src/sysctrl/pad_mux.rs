@@ -0,0 +1,199 @@
+//! Pad multiplexing
+//!
+//! Each SysCtrl pad carries one of up to four functions, selected in
+//! `PADMUX*` (sa. [PadFn]). A [Pad] is converted into an [AltPad] for one of
+//! the peripheral signals in [signal], e.g., `pads.p2.into_spim_sck()`. The
+//! conversion only exists for pads that carry the signal, and since the [Pad]
+//! is moved, a pad can't be handed to two peripherals. Peripheral constructors
+//! such as [UdmaI2c::enable](super::udma::UdmaI2c::enable) take [AltPad]s of
+//! the right signals. Their `enable_unchecked` variants leave the pads
+//! unclaimed and are `unsafe`. The APB UARTs take their pads in
+//! [ApbUart0::init_with_pads](crate::apb_uart::ApbUart0::init_with_pads).
+//!
+//! | Pad     | Default        | Alt2          | Alt3            |
+//! | :-      | :-             | :-            | :-              |
+//! | 0       | uDMA UART TX   |               | C2C TX clock    |
+//! | 1       | uDMA UART RX   |               | C2C RX clock    |
+//! | 2       | SPI-M SCK      |               | C2C TX data 0   |
+//! | 3       | SPI-M CS0      |               | C2C TX data 1   |
+//! | 4       | SPI-M SDIO0    |               | C2C RX data 0   |
+//! | 5       | SPI-M SDIO1    |               | C2C RX data 1   |
+//! | 6       | SPI-M SDIO2    |               | Ethernet0 MDC   |
+//! | 7       | SPI-M SDIO3    |               | Ethernet0 MDIO  |
+//! | 8       | I2C SCL        |               | Ethernet1 MDC   |
+//! | 9       | I2C SDA        |               | Ethernet1 MDIO  |
+//! | 10      |                | APB UART0 TX  |                 |
+//! | 11      |                | APB UART0 RX  |                 |
+//! | 12      |                | APB UART1 TX  |                 |
+//! | 13      |                | APB UART1 RX  |                 |
+//! | 14      | APB I2C SCL    |               |                 |
+//! | 15      | APB I2C SDA    |               |                 |
+//! | 16      | SPI-M CS1      |               |                 |
+//! | 17      | SPI-M CS2      |               |                 |
+//! | 18      | SPI-M CS3      |               |                 |
+//!
+//! Every pad carries GPIO as [PadFn::Gpio], sa. [Pad::into_gpio].
+use core::marker::PhantomData;
+
+use super::soc_ctrl::{pad_set_fn, Pad, PadFn};
+
+/// Marker types for the signals a pad can carry
+pub mod signal {
+    pub struct UdmaUartTx;
+    pub struct UdmaUartRx;
+    pub struct SpimSck;
+    pub struct SpimCs0;
+    pub struct SpimSdio0;
+    pub struct SpimSdio1;
+    pub struct SpimSdio2;
+    pub struct SpimSdio3;
+    pub struct I2cScl;
+    pub struct I2cSda;
+    pub struct ApbUart0Tx;
+    pub struct ApbUart0Rx;
+    pub struct ApbUart1Tx;
+    pub struct ApbUart1Rx;
+    pub struct ApbI2cScl;
+    pub struct ApbI2cSda;
+    pub struct SpimCs1;
+    pub struct SpimCs2;
+    pub struct SpimCs3;
+    pub struct C2cTxClk;
+    pub struct C2cRxClk;
+    pub struct C2cTxData0;
+    pub struct C2cTxData1;
+    pub struct C2cRxData0;
+    pub struct C2cRxData1;
+    pub struct Eth0Mdc;
+    pub struct Eth0Mdio;
+    pub struct Eth1Mdc;
+    pub struct Eth1Mdio;
+}
+
+/// Implemented for the pads that can carry signal `S`
+pub trait PadSignal<S> {
+    /// Function that routes `S` to the pad
+    const FN: PadFn;
+}
+
+/// Pad `IDX` configured to carry signal `S`
+pub struct AltPad<const IDX: u32, S> {
+    _pd: PhantomData<S>,
+}
+
+impl<const IDX: u32, S> AltPad<IDX, S> {
+    /// Release pad back to its default function
+    pub fn release(self) -> Pad<IDX> {
+        pad_set_fn(IDX, PadFn::Default);
+        Pad {}
+    }
+
+    /// # Safety
+    ///
+    /// This will not configure the pad in any way.
+    pub unsafe fn steal() -> Self {
+        Self { _pd: PhantomData }
+    }
+}
+
+impl<const IDX: u32> Pad<IDX> {
+    /// Routes signal `S` to the pad
+    pub fn into_alt<S>(self) -> AltPad<IDX, S>
+    where
+        Self: PadSignal<S>,
+    {
+        pad_set_fn(IDX, <Self as PadSignal<S>>::FN);
+        AltPad { _pd: PhantomData }
+    }
+}
+
+macro_rules! into_signal_fns {
+    ($($name:ident => $sig:ident),* $(,)?) => {
+        impl<const IDX: u32> Pad<IDX> {
+            $(
+                #[doc = concat!("Routes [signal::", stringify!($sig), "] to the pad")]
+                #[inline]
+                pub fn $name(self) -> AltPad<IDX, signal::$sig>
+                where
+                    Self: PadSignal<signal::$sig>,
+                {
+                    self.into_alt()
+                }
+            )*
+        }
+    };
+}
+
+into_signal_fns!(
+    into_udma_uart_tx => UdmaUartTx,
+    into_udma_uart_rx => UdmaUartRx,
+    into_spim_sck => SpimSck,
+    into_spim_cs0 => SpimCs0,
+    into_spim_sdio0 => SpimSdio0,
+    into_spim_sdio1 => SpimSdio1,
+    into_spim_sdio2 => SpimSdio2,
+    into_spim_sdio3 => SpimSdio3,
+    into_i2c_scl => I2cScl,
+    into_i2c_sda => I2cSda,
+    into_apb_uart0_tx => ApbUart0Tx,
+    into_apb_uart0_rx => ApbUart0Rx,
+    into_apb_uart1_tx => ApbUart1Tx,
+    into_apb_uart1_rx => ApbUart1Rx,
+    into_apb_i2c_scl => ApbI2cScl,
+    into_apb_i2c_sda => ApbI2cSda,
+    into_spim_cs1 => SpimCs1,
+    into_spim_cs2 => SpimCs2,
+    into_spim_cs3 => SpimCs3,
+    into_c2c_tx_clk => C2cTxClk,
+    into_c2c_rx_clk => C2cRxClk,
+    into_c2c_tx_data0 => C2cTxData0,
+    into_c2c_tx_data1 => C2cTxData1,
+    into_c2c_rx_data0 => C2cRxData0,
+    into_c2c_rx_data1 => C2cRxData1,
+    into_eth0_mdc => Eth0Mdc,
+    into_eth0_mdio => Eth0Mdio,
+    into_eth1_mdc => Eth1Mdc,
+    into_eth1_mdio => Eth1Mdio,
+);
+
+macro_rules! pad_mux_table {
+    ($($idx:literal: $f:ident => $sig:ident),* $(,)?) => {
+        $(
+            impl PadSignal<signal::$sig> for Pad<$idx> {
+                const FN: PadFn = PadFn::$f;
+            }
+        )*
+    };
+}
+
+pad_mux_table!(
+    0: Default => UdmaUartTx,
+    1: Default => UdmaUartRx,
+    2: Default => SpimSck,
+    3: Default => SpimCs0,
+    4: Default => SpimSdio0,
+    5: Default => SpimSdio1,
+    6: Default => SpimSdio2,
+    7: Default => SpimSdio3,
+    8: Default => I2cScl,
+    9: Default => I2cSda,
+    10: Alt2 => ApbUart0Tx,
+    11: Alt2 => ApbUart0Rx,
+    12: Alt2 => ApbUart1Tx,
+    13: Alt2 => ApbUart1Rx,
+    14: Default => ApbI2cScl,
+    15: Default => ApbI2cSda,
+    16: Default => SpimCs1,
+    17: Default => SpimCs2,
+    18: Default => SpimCs3,
+    0: Alt3 => C2cTxClk,
+    1: Alt3 => C2cRxClk,
+    2: Alt3 => C2cTxData0,
+    3: Alt3 => C2cTxData1,
+    4: Alt3 => C2cRxData0,
+    5: Alt3 => C2cRxData1,
+    6: Alt3 => Eth0Mdc,
+    7: Alt3 => Eth0Mdio,
+    8: Alt3 => Eth1Mdc,
+    9: Alt3 => Eth1Mdio,
+);
@@ -3,10 +3,33 @@ use riscv::interrupt;
 use super::{gpio::Gpio, mmap};
 use crate::{mask_u32, read_u32, unmask_u32, write_u32};
 
+/// Bits of a clock field in `SS_CLK_CTRL*` that are described by [ClockConfig]
+const CLK_CFG_MASK: u32 = 0b1111_1111;
+/// Clock enable of HPC core #0 in `SS_CLK_CTRL1`
+const HPC_CORE0_CLK_EN: u32 = 1 << 20;
+
+/// Pad function select in `PADMUX*`
+///
+/// See [pad_mux](super::pad_mux) for the signals behind [PadFn::Default],
+/// [PadFn::Alt2] and [PadFn::Alt3] on each pad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
-pub(crate) enum PadFn {
-    //Default = 0,
+pub enum PadFn {
+    Default = 0,
     Gpio = 1,
+    Alt2 = 2,
+    Alt3 = 3,
+}
+
+impl PadFn {
+    const fn from_bits(bits: u32) -> Self {
+        match bits & 0b11 {
+            0 => PadFn::Default,
+            1 => PadFn::Gpio,
+            2 => PadFn::Alt2,
+            _ => PadFn::Alt3,
+        }
+    }
 }
 
 /// ~Pin
@@ -44,6 +67,7 @@ impl Pads {
             if unsafe { PADS_TAKEN } {
                 None
             } else {
+                unsafe { PADS_TAKEN = true };
                 Some(unsafe { Self::steal() })
             }
         })
@@ -82,20 +106,85 @@ impl Pads {
 
 impl<const IDX: u32> Pad<IDX> {
     pub fn into_gpio(self) -> Gpio<IDX> {
-        mask_u32(
-            if IDX <= 15 {
-                mmap::PADMUX0
-            } else {
-                mmap::PADMUX1
-            },
-            (PadFn::Gpio as u32) << (IDX * 2),
-        );
+        pad_set_fn(IDX, PadFn::Gpio);
 
         Gpio::<IDX>::new()
     }
+
+    #[inline]
+    pub fn set_pull(&mut self, pull: Pull) {
+        pad_set_pull(IDX, pull);
+    }
+}
+
+/// Returns the `PADMUX*` register and the bit offset of the mux field of pad `idx`
+#[inline]
+const fn padmux_field(idx: u32) -> (usize, u32) {
+    if idx <= 15 {
+        (mmap::PADMUX0, idx * 2)
+    } else {
+        (mmap::PADMUX1, (idx - 16) * 2)
+    }
+}
+
+/// Selects the function of pad `idx`. Prefer the typed conversions on [Pad].
+pub fn pad_set_fn(idx: u32, f: PadFn) {
+    let (reg, ofs) = padmux_field(idx);
+    interrupt::free(|| {
+        let val = read_u32(reg) & !(0b11 << ofs);
+        write_u32(reg, val | ((f as u32) << ofs));
+    })
+}
+
+/// Returns the function selected for pad `idx`
+pub fn pad_fn(idx: u32) -> PadFn {
+    let (reg, ofs) = padmux_field(idx);
+    PadFn::from_bits(read_u32(reg) >> ofs)
+}
+
+/// Pull resistor of a pad
+///
+/// The pad frame only wires the pull enable, so the direction of the pull is
+/// fixed by the pad cell. Drive strength isn't wired to `PADCFG*` either and
+/// can't be configured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pull {
+    Disabled,
+    /// Enables the resistor the pad cell has
+    Enabled,
+}
+
+// Pad configuration bits. Each pad has an 8-bit field in `PADCFG*`, four pads per register, as in
+// PULPissimo's `apb_soc_ctrl`. The pad frame only wires bit 0 to the pull enable of the pad cell.
+const PADCFG_PULL_EN: u32 = 1 << 0;
+
+/// Returns the register and the bit offset of the configuration field of pad `idx`
+#[inline]
+const fn padcfg_field(idx: u32) -> (usize, u32) {
+    (mmap::PADCFG0 + (idx / 4) as usize * 4, (idx % 4) * 8)
+}
+
+/// Read-modify-writes the configuration field of pad `idx`
+fn pad_modify(idx: u32, clear: u32, set: u32) {
+    let (reg, ofs) = padcfg_field(idx);
+    interrupt::free(|| {
+        let val = read_u32(reg) & !(clear << ofs);
+        write_u32(reg, val | (set << ofs));
+    })
+}
+
+/// Enables or disables the pull resistor of pad `idx`
+pub fn pad_set_pull(idx: u32, pull: Pull) {
+    let set = match pull {
+        Pull::Disabled => 0,
+        Pull::Enabled => PADCFG_PULL_EN,
+    };
+    pad_modify(idx, PADCFG_PULL_EN, set);
 }
 
-/// Enables subsystems based on the mask. Returns the previous value in register.
+/// Enables subsystems based on the mask. Prefer [enable] over raw bit patterns.
+///
+/// Returns the previous value in register.
 pub fn ss_enable(ss_bits: u32) -> u32 {
     let pvalue = read_u32(mmap::SS_RESET_EN);
     mask_u32(mmap::SS_RESET_EN, ss_bits);
@@ -130,6 +219,203 @@ pub fn clk3_mask(conf_val: u32) -> u32 {
     pvalue
 }
 
+/// Subsystems controlled through SoC control
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subsystem {
+    Hpc,
+    Sdram,
+    /// Interconnect
+    Icn,
+    /// Top-level peripherals
+    Tlp,
+}
+
+impl Subsystem {
+    const COUNT: usize = 4;
+
+    /// Reset enable bit in `SS_RESET_EN`
+    const fn reset_bit(self) -> u32 {
+        match self {
+            Subsystem::Hpc => 1 << 2,
+            Subsystem::Sdram => 1 << 3,
+            Subsystem::Icn => 1 << 5,
+            Subsystem::Tlp => 1 << 8,
+        }
+    }
+
+    /// Clock control register and the offset of the subsystem's clock field
+    const fn clk_field(self) -> (usize, u32) {
+        match self {
+            Subsystem::Hpc => (mmap::SS_CLK_CTRL1, 16),
+            Subsystem::Sdram => (mmap::SS_CLK_CTRL1, 24),
+            Subsystem::Icn => (mmap::SS_CLK_CTRL2, 8),
+            Subsystem::Tlp => (mmap::SS_CLK_CTRL3, 0),
+        }
+    }
+}
+
+/// Clock configuration of a subsystem
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockConfig {
+    /// Select clock A
+    pub sel_cka: bool,
+    /// Force clock A
+    pub force_cka: bool,
+    /// Force clock B
+    pub force_ckb: bool,
+    /// Subsystem clock enable
+    pub enable: bool,
+    /// Clock divider select in bits 4..=6, 0 for the undivided clock
+    ///
+    /// On [Subsystem::Hpc] the lowest bit is the clock enable of core #0,
+    /// which [set_clock_config] leaves to [set_hpc_core0_clock].
+    pub divider: u8,
+    /// PLL control valid
+    pub pll_ctrl_valid: bool,
+}
+
+impl ClockConfig {
+    /// Clock A, enabled. Used by [enable].
+    pub const DEFAULT: Self = Self {
+        sel_cka: true,
+        force_cka: false,
+        force_ckb: false,
+        enable: true,
+        divider: 0,
+        pll_ctrl_valid: false,
+    };
+
+    pub const fn bits(&self) -> u32 {
+        (self.sel_cka as u32)
+            | (self.force_cka as u32) << 1
+            | (self.force_ckb as u32) << 2
+            | (self.enable as u32) << 3
+            | (self.divider as u32 & 0b111) << 4
+            | (self.pll_ctrl_valid as u32) << 7
+    }
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Self {
+            sel_cka: bits & 1 != 0,
+            force_cka: bits & (1 << 1) != 0,
+            force_ckb: bits & (1 << 2) != 0,
+            enable: bits & (1 << 3) != 0,
+            divider: ((bits >> 4) & 0b111) as u8,
+            pll_ctrl_valid: bits & (1 << 7) != 0,
+        }
+    }
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Read-back state of a subsystem
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Status {
+    /// Reset is deasserted
+    pub out_of_reset: bool,
+    pub clock: ClockConfig,
+    /// Number of [enable] calls not matched by [disable]
+    pub refcount: u8,
+    /// Was already enabled when the first reference was taken, so [disable]
+    /// leaves it on
+    pub external: bool,
+}
+
+/// Enable reference counts, indexed by `Subsystem as usize`
+static mut REFCOUNTS: [u8; Subsystem::COUNT] = [0; Subsystem::COUNT];
+/// Set for subsystems that were enabled by other means before their first
+/// reference, indexed by `Subsystem as usize`
+static mut EXTERNAL: [bool; Subsystem::COUNT] = [false; Subsystem::COUNT];
+
+/// Takes a reference to `ss`, taking it out of reset and enabling its clock
+/// with [ClockConfig::DEFAULT] on the first reference
+///
+/// Calls must be matched with [disable]. Subsystems already enabled by other
+/// means, e.g., the bootloader, are left as they are and stay enabled after
+/// the last [disable].
+pub fn enable(ss: Subsystem) {
+    interrupt::free(|| {
+        let refcount = unsafe { REFCOUNTS[ss as usize] };
+        if refcount == 0 {
+            let external = is_enabled(ss);
+            unsafe { EXTERNAL[ss as usize] = external };
+            if !external {
+                mask_u32(mmap::SS_RESET_EN, ss.reset_bit());
+                set_clock_config(ss, ClockConfig::DEFAULT);
+            }
+        }
+        unsafe { REFCOUNTS[ss as usize] = refcount.saturating_add(1) };
+    })
+}
+
+/// Drops a reference to `ss`, gating its clock and putting it into reset when
+/// the last reference is dropped, unless it was enabled externally
+pub fn disable(ss: Subsystem) {
+    interrupt::free(|| {
+        let refcount = unsafe { REFCOUNTS[ss as usize] };
+        if refcount == 1 && !unsafe { EXTERNAL[ss as usize] } {
+            set_clock_config(
+                ss,
+                ClockConfig {
+                    enable: false,
+                    ..clock_config(ss)
+                },
+            );
+            unmask_u32(mmap::SS_RESET_EN, ss.reset_bit());
+        }
+        unsafe { REFCOUNTS[ss as usize] = refcount.saturating_sub(1) };
+    })
+}
+
+/// Returns `true` if `ss` is out of reset and its clock is enabled
+pub fn is_enabled(ss: Subsystem) -> bool {
+    let status = status(ss);
+    status.out_of_reset && status.clock.enable
+}
+
+/// Reads back the state of `ss`
+pub fn status(ss: Subsystem) -> Status {
+    Status {
+        out_of_reset: read_u32(mmap::SS_RESET_EN) & ss.reset_bit() != 0,
+        clock: clock_config(ss),
+        refcount: interrupt::free(|| unsafe { REFCOUNTS[ss as usize] }),
+        external: interrupt::free(|| unsafe { EXTERNAL[ss as usize] }),
+    }
+}
+
+/// Reads the clock configuration of `ss`
+pub fn clock_config(ss: Subsystem) -> ClockConfig {
+    let (reg, ofs) = ss.clk_field();
+    ClockConfig::from_bits(read_u32(reg) >> ofs)
+}
+
+/// Writes the clock configuration of `ss`. Other bits in the register, and
+/// the core #0 clock enable of HPC, are preserved.
+pub fn set_clock_config(ss: Subsystem, cfg: ClockConfig) {
+    let (reg, ofs) = ss.clk_field();
+    let mask = match ss {
+        Subsystem::Hpc => (CLK_CFG_MASK << ofs) & !HPC_CORE0_CLK_EN,
+        _ => CLK_CFG_MASK << ofs,
+    };
+    interrupt::free(|| {
+        let r = read_u32(reg) & !mask;
+        write_u32(reg, r | (cfg.bits() << ofs) & mask);
+    })
+}
+
+/// Gates or ungates the clock of HPC core #0. HPC must be enabled first.
+pub fn set_hpc_core0_clock(enable: bool) {
+    if enable {
+        mask_u32(mmap::SS_CLK_CTRL1, HPC_CORE0_CLK_EN);
+    } else {
+        unmask_u32(mmap::SS_CLK_CTRL1, HPC_CORE0_CLK_EN);
+    }
+}
+
 /// # Parameters
 ///
 /// * `div` - value to set the `div` register to. Divider will be 1 << `div`
@@ -0,0 +1,294 @@
+//! SPI NOR flash on the uDMA SPI-M
+//!
+//! Uses the common 24-bit address command set (JEDEC ID `0x9F`, read `0x03`,
+//! quad output read `0x6B`, page program `0x02`, 4 KiB sector erase `0x20`).
+//!
+//! # Boot layout
+//!
+//! SysCtrl can boot HPC from a raw image stored in flash with
+//! [SpiFlash::boot_hpc]. The image is described by a [BootHeader] at
+//! [BOOT_HEADER_ADDR]. `scripts/mk_flash_image.py` creates a flash image in
+//! this layout.
+//!
+//! | Offset | Size | Field          |
+//! | :-     | :-   | :-             |
+//! | 0x00   | 4    | magic `"HSBT"` |
+//! | 0x04   | 4    | image offset   |
+//! | 0x08   | 4    | image length   |
+//! | 0x0C   | 4    | image CRC-32   |
+//! | 0x10   | 8    | load address   |
+//! | 0x18   | 8    | entry point    |
+//!
+//! All fields are little-endian. Addresses are as seen by HPC.
+use super::{
+    hpc_boot::{self, BootError, BootStep, Loader},
+    udma::{
+        spim::{SPI_CMD_SEND_CMD_BASE, SPI_CMD_SOT},
+        Enabled, UdmaSpim,
+    },
+};
+
+pub const CMD_READ: u8 = 0x03;
+pub const CMD_QUAD_OUTPUT_READ: u8 = 0x6B;
+pub const CMD_PAGE_PROGRAM: u8 = 0x02;
+pub const CMD_SECTOR_ERASE: u8 = 0x20;
+pub const CMD_WRITE_ENABLE: u8 = 0x06;
+pub const CMD_READ_STATUS: u8 = 0x05;
+pub const CMD_JEDEC_ID: u8 = 0x9F;
+
+/// Write in progress
+const STATUS_WIP: u8 = 1 << 0;
+
+pub const PAGE_SIZE: u32 = 256;
+pub const SECTOR_SIZE: u32 = 4096;
+
+/// The SPI-M data commands transfer at least two bytes
+const MIN_XFER: usize = 2;
+
+/// Flash address of the [BootHeader]
+pub const BOOT_HEADER_ADDR: u32 = 0;
+pub const BOOT_HEADER_LEN: usize = 32;
+pub const BOOT_MAGIC: [u8; 4] = *b"HSBT";
+
+/// Describes the HPC image stored in flash. See the module documentation for
+/// the layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BootHeader {
+    /// Flash address of the image
+    pub image_offset: u32,
+    pub image_len: u32,
+    /// CRC-32 (IEEE) of the image
+    pub image_crc: u32,
+    /// HPC address the image is loaded to
+    pub load_addr: u64,
+    /// HPC address execution starts from
+    pub entry: u64,
+}
+
+impl BootHeader {
+    /// Parses a header. Returns `None` if the magic doesn't match.
+    pub fn parse(buf: &[u8; BOOT_HEADER_LEN]) -> Option<Self> {
+        if buf[0..4] != BOOT_MAGIC {
+            return None;
+        }
+        let u32_at = |ofs: usize| u32::from_le_bytes(buf[ofs..ofs + 4].try_into().unwrap());
+        let u64_at = |ofs: usize| u64::from_le_bytes(buf[ofs..ofs + 8].try_into().unwrap());
+        Some(Self {
+            image_offset: u32_at(0x4),
+            image_len: u32_at(0x8),
+            image_crc: u32_at(0xC),
+            load_addr: u64_at(0x10),
+            entry: u64_at(0x18),
+        })
+    }
+}
+
+/// Updates a CRC-32 (IEEE) with `data`. Start with `0`.
+pub fn crc32(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// SPI NOR flash on chip select 0 of the uDMA SPI-M
+pub struct SpiFlash<'u> {
+    spim: UdmaSpim<'u, Enabled>,
+}
+
+impl<'u> SpiFlash<'u> {
+    pub fn new(spim: UdmaSpim<'u, Enabled>) -> Self {
+        Self { spim }
+    }
+
+    /// Returns the SPI-M
+    pub fn release(self) -> UdmaSpim<'u, Enabled> {
+        self.spim
+    }
+
+    /// Starts a transaction with `cmd` followed by the optional 24-bit `addr`
+    fn begin(&mut self, cmd: u8, addr: Option<u32>) {
+        let mut cmds = [0u8; 20];
+        cmds[0..4].copy_from_slice(&SPI_CMD_SOT.to_ne_bytes());
+        cmds[4..8].copy_from_slice(&(SPI_CMD_SEND_CMD_BASE | cmd as u32).to_ne_bytes());
+        let len = match addr {
+            Some(addr) => {
+                for (idx, b) in addr.to_be_bytes()[1..].iter().enumerate() {
+                    let ofs = 8 + idx * 4;
+                    cmds[ofs..ofs + 4]
+                        .copy_from_slice(&(SPI_CMD_SEND_CMD_BASE | *b as u32).to_ne_bytes());
+                }
+                20
+            }
+            None => 8,
+        };
+        self.spim.write_cmd(&cmds[..len]);
+    }
+
+    fn end(&mut self) {
+        self.spim.send_eot();
+    }
+
+    /// Receives into `buf`, which may be shorter than [MIN_XFER]
+    fn receive(&mut self, buf: &mut [u8], quad: bool) {
+        let mut tmp = [0u8; MIN_XFER];
+        let dst = if buf.len() < MIN_XFER {
+            &mut tmp[..]
+        } else {
+            &mut *buf
+        };
+        // Infallible
+        let _ = if quad {
+            self.spim.receive_data_quad(dst)
+        } else {
+            self.spim.receive_data(dst)
+        };
+        if buf.len() < MIN_XFER {
+            buf.copy_from_slice(&tmp[..buf.len()]);
+        }
+    }
+
+    /// Reads the manufacturer ID and the two device ID bytes
+    pub fn jedec_id(&mut self) -> [u8; 3] {
+        let mut id = [0u8; 3];
+        self.begin(CMD_JEDEC_ID, None);
+        self.receive(&mut id, false);
+        self.end();
+        id
+    }
+
+    /// Reads status register 1
+    pub fn read_status(&mut self) -> u8 {
+        let mut status = [0u8];
+        self.begin(CMD_READ_STATUS, None);
+        self.receive(&mut status, false);
+        self.end();
+        status[0]
+    }
+
+    pub fn is_busy(&mut self) -> bool {
+        self.read_status() & STATUS_WIP != 0
+    }
+
+    /// Blocks until a program or erase is complete
+    pub fn wait_while_busy(&mut self) {
+        while self.is_busy() {}
+    }
+
+    fn write_enable(&mut self) {
+        self.begin(CMD_WRITE_ENABLE, None);
+        self.end();
+    }
+
+    /// Reads `buf.len()` bytes starting from `addr`
+    pub fn read(&mut self, addr: u32, buf: &mut [u8]) {
+        self.begin(CMD_READ, Some(addr));
+        self.receive(buf, false);
+        self.end();
+    }
+
+    /// Reads `buf.len()` bytes starting from `addr`, receiving on four lines
+    ///
+    /// The flash must have quad mode enabled, which is vendor specific, e.g.,
+    /// the QE bit in status register 2.
+    pub fn read_quad(&mut self, addr: u32, buf: &mut [u8]) {
+        self.begin(CMD_QUAD_OUTPUT_READ, Some(addr));
+        // 8 dummy cycles
+        self.spim.write_dummy();
+        self.receive(buf, true);
+        self.end();
+    }
+
+    /// Programs `data` to `addr` within a single page and waits for
+    /// completion. Bytes past the end of the page wrap to its beginning.
+    pub fn page_program(&mut self, addr: u32, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        // Programming 0xFF leaves the flash unchanged, so short writes can be
+        // padded
+        let mut tmp = [0xFFu8; MIN_XFER];
+        let src = if data.len() < MIN_XFER {
+            tmp[..data.len()].copy_from_slice(data);
+            &tmp[..]
+        } else {
+            data
+        };
+
+        self.write_enable();
+        self.begin(CMD_PAGE_PROGRAM, Some(addr));
+        self.spim.send_data(src);
+        self.end();
+        self.wait_while_busy();
+    }
+
+    /// Programs `data` starting from `addr`, split into pages. The affected
+    /// sectors must be erased first.
+    pub fn write(&mut self, mut addr: u32, mut data: &[u8]) {
+        while !data.is_empty() {
+            let page_left = (PAGE_SIZE - addr % PAGE_SIZE) as usize;
+            let (page, rest) = data.split_at(page_left.min(data.len()));
+            self.page_program(addr, page);
+            addr += page.len() as u32;
+            data = rest;
+        }
+    }
+
+    /// Erases the 4 KiB sector containing `addr` and waits for completion
+    pub fn erase_sector(&mut self, addr: u32) {
+        self.write_enable();
+        self.begin(CMD_SECTOR_ERASE, Some(addr & !(SECTOR_SIZE - 1)));
+        self.end();
+        self.wait_while_busy();
+    }
+
+    /// Reads the [BootHeader]
+    pub fn boot_header(&mut self) -> Option<BootHeader> {
+        let mut buf = [0u8; BOOT_HEADER_LEN];
+        self.read(BOOT_HEADER_ADDR, &mut buf);
+        BootHeader::parse(&buf)
+    }
+
+    /// Loads the image described by the [BootHeader] to HPC. Returns the entry
+    /// point.
+    ///
+    /// HPC memories must be powered on. [SpiFlash::boot_hpc] takes care of
+    /// that.
+    pub fn load_hpc_image(&mut self, loader: &mut Loader) -> Result<u64, BootError> {
+        let header = self.boot_header().ok_or(BootError::InvalidImage)?;
+
+        // Bounce through a local buffer, the uDMA may not reach HPC memories
+        let mut chunk = [0u8; PAGE_SIZE as usize];
+        let mut crc = 0;
+        let mut ofs = 0;
+        while ofs < header.image_len {
+            let len = (header.image_len - ofs).min(PAGE_SIZE) as usize;
+            let chunk = &mut chunk[..len];
+            self.read(header.image_offset + ofs, chunk);
+            crc = crc32(crc, chunk);
+            loader.load_raw(chunk, header.load_addr + ofs as u64)?;
+            ofs += len as u32;
+        }
+
+        if crc != header.image_crc {
+            return Err(BootError::InvalidImage);
+        }
+        Ok(header.entry)
+    }
+
+    /// Boots HPC from the image in flash. See [hpc_boot::boot].
+    pub fn boot_hpc(
+        &mut self,
+        report: impl FnMut(BootStep, Result<(), BootError>),
+    ) -> Result<(), BootError> {
+        hpc_boot::boot(|loader| self.load_hpc_image(loader), report)
+    }
+}
@@ -0,0 +1,254 @@
+//! Monotonic time and delays based on the SysCtrl timer
+//!
+//! The PULP timer at `0x1A10B000` is run as a single 64-bit counter by
+//! cascading its two 32-bit halves. Call [init] once before using the clock.
+//!
+//! SysCtrl has no CLINT, so delays busy-wait on the counter rather than sleep.
+use super::{itc, mmap};
+use crate::{read_u32, write_u32};
+use bit_field::BitField;
+
+/// Frequency of the SysCtrl timer
+///
+/// TODO: this is the rate modelled by Renode, verify on ASIC
+pub const TICK_HZ: u32 = 100_000;
+
+/// A point in time as measured by the SysCtrl timer
+pub type Instant = fugit::TimerInstantU64<TICK_HZ>;
+/// A span of time in SysCtrl timer ticks
+pub type Duration = fugit::TimerDurationU64<TICK_HZ>;
+
+/// ITC line raised by the timer's compare match
+pub const TIMER_IRQ: usize = 10;
+
+const CFG_ENABLE_BIT: usize = 0;
+const CFG_RESET_BIT: usize = 1;
+const CFG_IRQ_EN_BIT: usize = 2;
+const CFG_CASCADE_BIT: usize = 31;
+
+/// Resets and starts the timer in 64-bit mode
+pub fn init() {
+    let mut cfg = 0;
+    cfg.set_bit(CFG_ENABLE_BIT, true);
+    cfg.set_bit(CFG_RESET_BIT, true);
+    cfg.set_bit(CFG_CASCADE_BIT, true);
+    write_u32(mmap::TIMER_CMP_HI, u32::MAX);
+    write_u32(mmap::TIMER_CMP_LO, u32::MAX);
+    write_u32(mmap::TIMER_CFG_LO, cfg);
+}
+
+/// Returns the current value of the monotonic clock
+pub fn now() -> Instant {
+    // Re-read if the low word overflowed in between the reads
+    loop {
+        let hi = read_u32(mmap::TIMER_CNT_HI);
+        let lo = read_u32(mmap::TIMER_CNT_LO);
+        if hi == read_u32(mmap::TIMER_CNT_HI) {
+            return Instant::from_ticks(((hi as u64) << 32) | lo as u64);
+        }
+    }
+}
+
+/// Busy-waits until `deadline` has passed
+#[inline]
+pub fn delay_until(deadline: Instant) {
+    while now() < deadline {}
+}
+
+/// Busy-waits for at least `duration`
+///
+/// One tick is added, as the current tick may already be almost over.
+#[inline]
+pub fn delay(duration: Duration) {
+    delay_until(now() + duration + Duration::from_ticks(1))
+}
+
+/// Busy-waits for at least `ms` milliseconds
+#[inline]
+pub fn delay_ms(ms: u32) {
+    delay(Duration::millis_at_least(ms as u64))
+}
+
+/// Busy-waits for at least `us` microseconds
+///
+/// N.b. resolution is limited by [TICK_HZ].
+#[inline]
+pub fn delay_us(us: u32) {
+    delay(Duration::micros_at_least(us as u64))
+}
+
+/// [embedded_hal::delay::DelayNs] implementation based on [delay]
+///
+/// Busy-waits for at least the requested time, rounded up to the next tick.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Delay;
+
+impl embedded_hal::delay::DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        delay(Duration::nanos_at_least(ns as u64))
+    }
+
+    #[inline]
+    fn delay_us(&mut self, us: u32) {
+        delay_us(us)
+    }
+
+    #[inline]
+    fn delay_ms(&mut self, ms: u32) {
+        delay_ms(ms)
+    }
+}
+
+/// Sets the 64-bit compare value
+fn set_compare(ticks: u64) {
+    // Park the low word first to avoid a spurious match in between the writes
+    write_u32(mmap::TIMER_CMP_LO, u32::MAX);
+    write_u32(mmap::TIMER_CMP_HI, (ticks >> 32) as u32);
+    write_u32(mmap::TIMER_CMP_LO, ticks as u32);
+}
+
+/// Routes the compare match interrupt to the core
+fn enable_irq() {
+    let mut cfg = read_u32(mmap::TIMER_CFG_LO);
+    cfg.set_bit(CFG_IRQ_EN_BIT, true);
+    write_u32(mmap::TIMER_CFG_LO, cfg);
+    itc::enable(TIMER_IRQ);
+}
+
+fn disable_irq() {
+    itc::disable(TIMER_IRQ);
+}
+
+fn clear_irq() {
+    itc::unpend(TIMER_IRQ);
+}
+
+/// Monotonic timer for RTIC
+///
+/// The compare match is delivered on ITC line [TIMER_IRQ].
+#[cfg(feature = "rtic-monotonic")]
+pub struct MonoTimer;
+
+#[cfg(feature = "rtic-monotonic")]
+impl MonoTimer {
+    /// Starts the timer. See [init].
+    pub fn new() -> Self {
+        init();
+        Self
+    }
+}
+
+#[cfg(feature = "rtic-monotonic")]
+impl Default for MonoTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rtic-monotonic")]
+impl rtic_monotonic::Monotonic for MonoTimer {
+    type Instant = Instant;
+    type Duration = Duration;
+
+    #[inline]
+    fn now(&mut self) -> Self::Instant {
+        now()
+    }
+
+    fn set_compare(&mut self, instant: Self::Instant) {
+        set_compare(instant.ticks());
+    }
+
+    fn clear_compare_flag(&mut self) {
+        clear_irq();
+    }
+
+    #[inline]
+    fn zero() -> Self::Instant {
+        Instant::from_ticks(0)
+    }
+
+    unsafe fn reset(&mut self) {
+        init();
+    }
+
+    fn enable_timer(&mut self) {
+        enable_irq();
+    }
+
+    fn disable_timer(&mut self) {
+        disable_irq();
+    }
+}
+
+#[cfg(feature = "embassy-time-driver")]
+mod embassy {
+    use core::{cell::RefCell, task::Waker};
+    use critical_section::Mutex;
+    use embassy_time_driver::Driver;
+    use embassy_time_queue_utils::Queue;
+
+    const _: () = assert!(
+        embassy_time_driver::TICK_HZ == super::TICK_HZ as u64,
+        "embassy-time tick rate must match SysCtrl timer frequency"
+    );
+
+    /// embassy-time driver on the SysCtrl timer
+    struct TimerDriver {
+        queue: Mutex<RefCell<Queue>>,
+    }
+
+    impl TimerDriver {
+        /// Returns `false` if `at` has already passed
+        fn set_alarm(&self, at: u64) -> bool {
+            super::set_compare(at);
+            at == u64::MAX || self.now() < at
+        }
+
+        /// Wakes expired timers and arms the alarm for the next one
+        fn rearm(&self, queue: &mut Queue) {
+            loop {
+                let next = queue.next_expiration(self.now());
+                if self.set_alarm(next) {
+                    break;
+                }
+            }
+        }
+
+        fn on_interrupt(&self) {
+            super::clear_irq();
+            critical_section::with(|cs| self.rearm(&mut self.queue.borrow(cs).borrow_mut()))
+        }
+    }
+
+    impl Driver for TimerDriver {
+        #[inline]
+        fn now(&self) -> u64 {
+            super::now().ticks()
+        }
+
+        fn schedule_wake(&self, at: u64, waker: &Waker) {
+            critical_section::with(|cs| {
+                let mut queue = self.queue.borrow(cs).borrow_mut();
+                if queue.schedule_wake(at, waker) {
+                    self.rearm(&mut queue);
+                    super::enable_irq();
+                }
+            })
+        }
+    }
+
+    embassy_time_driver::time_driver_impl!(static DRIVER: TimerDriver = TimerDriver {
+        queue: Mutex::new(RefCell::new(Queue::new())),
+    });
+
+    /// Wakes expired timers and re-arms the alarm
+    ///
+    /// Call this from the handler of ITC line [TIMER_IRQ](super::TIMER_IRQ).
+    /// The timer must be started with [init](super::init) first.
+    pub fn on_timer_interrupt() {
+        DRIVER.on_interrupt();
+    }
+}
+#[cfg(feature = "embassy-time-driver")]
+pub use embassy::on_timer_interrupt;
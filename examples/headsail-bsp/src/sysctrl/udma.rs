@@ -1,20 +1,53 @@
+pub mod i2c;
 pub mod spim;
+pub mod transfer;
 pub mod uart;
 
 use core::marker::PhantomData;
 
 use crate::pac;
+pub use i2c::UdmaI2c;
 pub use spim::UdmaSpim;
-pub use uart::UdmaUart;
+pub use transfer::Transfer;
+pub use uart::{UdmaUart, UdmaUartRx};
 /// Type-state trait for uDMA peripherals in different states
 pub trait UdmaPeriphState {}
 
 // Type-states for uDMA peripherals
 pub struct Enabled;
 pub struct Disabled;
+/// UART with continuous reception running, see [UdmaUart::start_rx]
+pub struct Receiving;
 
 impl UdmaPeriphState for Enabled {}
 impl UdmaPeriphState for Disabled {}
+impl UdmaPeriphState for Receiving {}
+
+/// uDMA channel events as numbered by the SysCtrl event controller
+#[cfg(feature = "async")]
+pub(crate) mod event {
+    pub const SPIM_RX: u8 = 0;
+    pub const SPIM_TX: u8 = 1;
+    pub const SPIM_CMD: u8 = 2;
+    pub const UART_RX: u8 = 4;
+    pub const UART_TX: u8 = 5;
+    /// UART received a byte, enabled with `UART_IRQ_EN.RX`
+    pub const UART_RX_DATA: u8 = 6;
+}
+
+/// Blocks on drop until `done` returns `true`
+///
+/// Keeps a buffer borrowed by an async transfer alive until the uDMA is done
+/// with it, even if the future is dropped early.
+#[cfg(feature = "async")]
+pub(crate) struct WaitOnDrop<F: FnMut() -> bool>(pub(crate) F);
+
+#[cfg(feature = "async")]
+impl<F: FnMut() -> bool> Drop for WaitOnDrop<F> {
+    fn drop(&mut self) {
+        while !(self.0)() {}
+    }
+}
 
 /// Relocatable driver for uDMA IP
 pub struct Udma<'u>(pub &'u pac::sysctrl::Udma);
@@ -22,6 +55,7 @@ pub struct Udma<'u>(pub &'u pac::sysctrl::Udma);
 pub struct UdmaParts<'u> {
     pub uart: UdmaUart<'u, Disabled>,
     pub spim: UdmaSpim<'u, Disabled>,
+    pub i2c: UdmaI2c<'u, Disabled>,
 }
 
 impl<'u> Udma<'u> {
@@ -29,6 +63,7 @@ impl<'u> Udma<'u> {
         UdmaParts {
             uart: UdmaUart::<Disabled>(self.0, PhantomData),
             spim: UdmaSpim::<Disabled>(self.0, PhantomData),
+            i2c: UdmaI2c::<Disabled>(self.0, PhantomData),
         }
     }
 }
@@ -0,0 +1,297 @@
+//! uDMA I2C master
+//!
+//! The uDMA I2C is driven by a stream of commands sent on the TX channel.
+//! Bytes read from the bus are written by the RX channel. A transaction is
+//! built as follows:
+//!
+//! * start: `CMD_START`, `CMD_WR`, `address << 1 | rw`
+//! * write N: `CMD_WR`, `byte` for each byte
+//! * read N: `CMD_RD_ACK` for each but the last byte, `CMD_RD_NACK` for the last
+//! * stop: `CMD_STOP`
+//!
+//! The status is checked after the address and after each write, and a
+//! transaction not acknowledged by the target is stopped with
+//! [I2cError::NoAcknowledge].
+use core::marker::PhantomData;
+
+use super::{Disabled, Enabled};
+use crate::{
+    pac, read_u32,
+    sysctrl::{
+        mmap,
+        pad_mux::{signal, AltPad},
+    },
+    write_u32,
+};
+use embedded_hal::i2c::{
+    self, ErrorKind, ErrorType, NoAcknowledgeSource, Operation, SevenBitAddress,
+};
+
+pub const I2C_CMD_START: u8 = 0x00;
+pub const I2C_CMD_WAIT_EV: u8 = 0x10;
+pub const I2C_CMD_STOP: u8 = 0x20;
+pub const I2C_CMD_RD_ACK: u8 = 0x40;
+pub const I2C_CMD_RD_NACK: u8 = 0x60;
+pub const I2C_CMD_WR: u8 = 0x80;
+pub const I2C_CMD_WAIT: u8 = 0xA0;
+pub const I2C_CMD_RPT: u8 = 0xC0;
+pub const I2C_CMD_CFG: u8 = 0xE0;
+
+/// Clock gate of I2C0 in `ctrl_cfg_cg`
+const CG_I2C0: u32 = 1 << 2;
+
+/// Channel configuration bits
+const CFG_EN: u32 = 1 << 4;
+const CFG_PENDING: u32 = 1 << 5;
+
+const STATUS_BUSY: u32 = 1 << 0;
+const STATUS_ARB_LOST: u32 = 1 << 1;
+/// The last byte written was not acknowledged
+const STATUS_NACK: u32 = 1 << 2;
+
+const SETUP_DO_RST: u32 = 1 << 0;
+
+/// Length of the command buffer. Longer transactions are sent in several
+/// parts.
+const CMD_BUF_LEN: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum I2cError {
+    /// Another master took over the bus
+    ArbitrationLost,
+    /// The address or a data byte was not acknowledged
+    NoAcknowledge(NoAcknowledgeSource),
+}
+
+impl i2c::Error for I2cError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            I2cError::ArbitrationLost => ErrorKind::ArbitrationLoss,
+            I2cError::NoAcknowledge(source) => ErrorKind::NoAcknowledge(*source),
+        }
+    }
+}
+
+/// Obtain an instance by calling [Udma::split](super::Udma::split)
+pub struct UdmaI2c<'u, UdmaPeriphState>(
+    pub(crate) &'u pac::sysctrl::Udma,
+    pub(crate) PhantomData<UdmaPeriphState>,
+);
+
+impl<'u> UdmaI2c<'u, Disabled> {
+    /// Enables the uDMA clock gate for I2C, sets the SCL divider and routes
+    /// the I2C to `scl` and `sda`. The pads stay configured for the I2C.
+    ///
+    /// SCL frequency is the peripheral clock frequency divided by `clk_div`.
+    #[inline]
+    pub fn enable<const SCL: u32, const SDA: u32>(
+        self,
+        clk_div: u16,
+        _scl: AltPad<SCL, signal::I2cScl>,
+        _sda: AltPad<SDA, signal::I2cSda>,
+    ) -> UdmaI2c<'u, Enabled> {
+        // SAFETY: the pads are owned by the I2C from now on
+        unsafe { self.enable_unchecked(clk_div) }
+    }
+
+    /// Like [UdmaI2c::enable], but without claiming the pads
+    ///
+    /// # Safety
+    ///
+    /// Pads 8 and 9 carry the I2C by default. Nothing else may use them, e.g.,
+    /// as GPIO.
+    #[inline]
+    pub unsafe fn enable_unchecked(self, clk_div: u16) -> UdmaI2c<'u, Enabled> {
+        let udma = &self.0;
+
+        // Turn on the clock gates for I2C
+        udma.ctrl_cfg_cg()
+            .modify(|r, w| unsafe { w.bits(r.bits() | CG_I2C0) });
+
+        let mut i2c = UdmaI2c::<Enabled>(self.0, PhantomData);
+        i2c.set_clk_div(clk_div);
+        i2c
+    }
+}
+
+impl<'u> UdmaI2c<'u, Enabled> {
+    #[inline]
+    pub fn disable(self) -> UdmaI2c<'u, Disabled> {
+        self.0
+            .ctrl_cfg_cg()
+            .modify(|r, w| unsafe { w.bits(r.bits() & !CG_I2C0) });
+        UdmaI2c::<Disabled>(self.0, PhantomData)
+    }
+
+    /// # Safety
+    ///
+    /// This will not configure the I2C in any way.
+    #[inline]
+    pub unsafe fn steal(udma: &'static pac::sysctrl::Udma) -> Self {
+        Self(udma, PhantomData)
+    }
+
+    /// Sets the SCL divider
+    pub fn set_clk_div(&mut self, clk_div: u16) {
+        let [hi, lo] = clk_div.to_be_bytes();
+        self.write_cmds(&[I2C_CMD_CFG, hi, lo]);
+    }
+
+    /// Resets the I2C peripheral, e.g., after losing arbitration
+    pub fn reset(&mut self) {
+        write_u32(mmap::UDMA_I2C0_SETUP, SETUP_DO_RST);
+        write_u32(mmap::UDMA_I2C0_SETUP, 0);
+    }
+
+    /// Writes `cmds` to the TX channel and blocks until the uDMA is done with
+    /// them
+    fn write_cmds(&mut self, cmds: &[u8]) {
+        while read_u32(mmap::UDMA_I2C0_TX_CFG) & CFG_PENDING != 0 {}
+
+        // Write buffer location & len
+        write_u32(mmap::UDMA_I2C0_TX_SADDR, cmds.as_ptr() as u32);
+        write_u32(mmap::UDMA_I2C0_TX_SIZE, cmds.len() as u32);
+
+        // Dispatch transmission
+        write_u32(mmap::UDMA_I2C0_TX_CFG, CFG_EN);
+
+        // Poll until finished (prevents `cmds` leakage)
+        while read_u32(mmap::UDMA_I2C0_TX_SADDR) != 0 {}
+    }
+
+    /// Arms the RX channel to receive into `buf`
+    ///
+    /// # Safety
+    ///
+    /// `buf` must outlive the transfer. Call [UdmaI2c::wait_rx] before
+    /// releasing `buf`.
+    unsafe fn enqueue_rx(&mut self, buf: &mut [u8]) {
+        while read_u32(mmap::UDMA_I2C0_RX_CFG) & CFG_PENDING != 0 {}
+
+        // Write buffer location & len
+        write_u32(mmap::UDMA_I2C0_RX_SADDR, buf.as_mut_ptr() as u32);
+        write_u32(mmap::UDMA_I2C0_RX_SIZE, buf.len() as u32);
+
+        // Dispatch reception
+        write_u32(mmap::UDMA_I2C0_RX_CFG, CFG_EN);
+    }
+
+    fn wait_rx(&mut self) {
+        while read_u32(mmap::UDMA_I2C0_RX_SADDR) != 0 {}
+    }
+
+    /// Blocks until the commands sent so far are executed. Returns an error
+    /// if arbitration was lost or a byte written was not acknowledged, in
+    /// which case the bus is released.
+    ///
+    /// `source` tells what the written bytes were for.
+    fn wait_idle(&mut self, source: NoAcknowledgeSource) -> Result<(), I2cError> {
+        loop {
+            let status = read_u32(mmap::UDMA_I2C0_STATUS);
+            if status & STATUS_ARB_LOST != 0 {
+                self.reset();
+                return Err(I2cError::ArbitrationLost);
+            }
+            if status & STATUS_BUSY == 0 {
+                if status & STATUS_NACK != 0 {
+                    self.write_cmds(&[I2C_CMD_STOP]);
+                    self.reset();
+                    return Err(I2cError::NoAcknowledge(source));
+                }
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Collects I2C commands and sends them in parts of [CMD_BUF_LEN] bytes
+struct CmdBuf<'a, 'u> {
+    i2c: &'a mut UdmaI2c<'u, Enabled>,
+    buf: [u8; CMD_BUF_LEN],
+    len: usize,
+}
+
+impl<'a, 'u> CmdBuf<'a, 'u> {
+    fn new(i2c: &'a mut UdmaI2c<'u, Enabled>) -> Self {
+        Self {
+            i2c,
+            buf: [0; CMD_BUF_LEN],
+            len: 0,
+        }
+    }
+
+    /// Appends `cmds`, which are kept in the same part
+    fn push(&mut self, cmds: &[u8]) {
+        if self.len + cmds.len() > CMD_BUF_LEN {
+            self.flush();
+        }
+        self.buf[self.len..self.len + cmds.len()].copy_from_slice(cmds);
+        self.len += cmds.len();
+    }
+
+    fn flush(&mut self) {
+        if self.len != 0 {
+            self.i2c.write_cmds(&self.buf[..self.len]);
+            self.len = 0;
+        }
+    }
+}
+
+impl<'u> ErrorType for UdmaI2c<'u, Enabled> {
+    type Error = I2cError;
+}
+
+impl<'u> i2c::I2c<SevenBitAddress> for UdmaI2c<'u, Enabled> {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut cmds = CmdBuf::new(self);
+        let mut prev_is_read = None;
+
+        let op_count = operations.len();
+        for idx in 0..op_count {
+            let is_read = matches!(operations[idx], Operation::Read(_));
+            // Consecutive operations of the same kind are merged, otherwise
+            // a (repeated) start condition is required
+            if prev_is_read != Some(is_read) {
+                cmds.push(&[I2C_CMD_START, I2C_CMD_WR, address << 1 | is_read as u8]);
+                cmds.flush();
+                cmds.i2c.wait_idle(NoAcknowledgeSource::Address)?;
+            }
+            let next_is_read = matches!(operations.get(idx + 1), Some(Operation::Read(_)));
+
+            match &mut operations[idx] {
+                Operation::Write(bytes) => {
+                    for &b in bytes.iter() {
+                        cmds.push(&[I2C_CMD_WR, b]);
+                    }
+                    cmds.flush();
+                    cmds.i2c.wait_idle(NoAcknowledgeSource::Data)?;
+                }
+                Operation::Read(buf) if buf.is_empty() => {}
+                Operation::Read(buf) => {
+                    // SAFETY: the reception is waited for below, before `buf` is released
+                    unsafe { cmds.i2c.enqueue_rx(buf) };
+                    for _ in 1..buf.len() {
+                        cmds.push(&[I2C_CMD_RD_ACK]);
+                    }
+                    // The last byte of a read is NACKed, unless the read continues
+                    cmds.push(&[if next_is_read {
+                        I2C_CMD_RD_ACK
+                    } else {
+                        I2C_CMD_RD_NACK
+                    }]);
+                    cmds.flush();
+                    cmds.i2c.wait_rx();
+                }
+            }
+            prev_is_read = Some(is_read);
+        }
+
+        cmds.push(&[I2C_CMD_STOP]);
+        cmds.flush();
+        self.wait_idle(NoAcknowledgeSource::Unknown)
+    }
+}
@@ -1,8 +1,21 @@
 use core::marker::PhantomData;
 
-use super::{Disabled, Enabled};
-use crate::pac;
+use super::{
+    transfer::{Channel, Transfer},
+    Disabled, Enabled,
+};
+use crate::{
+    pac,
+    sysctrl::pad_mux::{signal, AltPad},
+};
+use embedded_dma::{ReadBuffer, WriteBuffer};
+use embedded_hal::spi::{self, Operation};
 use embedded_io::ErrorType;
+#[cfg(feature = "async")]
+use {
+    super::{event, WaitOnDrop},
+    crate::sysctrl::itc::wait_for_event,
+};
 
 pub const SPI_CMD_SOT: u32 = 0x10000000;
 pub const SPI_CMD_EOT: u32 = 0x90000000;
@@ -13,6 +26,15 @@ pub const SPI_CMD_RX_DATA: u32 = 0x74000000;
 pub const SPI_CMD_TX_DATA: u32 = 0x64000000;
 pub const SPI_CMD_SETUP_UCA: u32 = 0xD0000000;
 pub const SPI_CMD_SETUP_UCS: u32 = 0xE0000000;
+/// Transfer data on all four lines. Applies to `SPI_CMD_TX_DATA` and `SPI_CMD_RX_DATA`.
+pub const SPI_CMD_QPI: u32 = 0x08000000;
+pub const SPI_CMD_FULL_DUPLEX: u32 = 0xC4000000;
+
+/// Length of the bounce buffer used by in-place transfers
+const IN_PLACE_CHUNK: usize = 64;
+
+/// Most words moved by one data command, whose length field is 16 bits wide
+const MAX_WORDS_PER_CMD: usize = u16::MAX as usize;
 
 /// Obtain an instance by calling [Udma::split]
 pub struct UdmaSpim<'u, UdmaPeriphState>(
@@ -21,9 +43,29 @@ pub struct UdmaSpim<'u, UdmaPeriphState>(
 );
 
 impl<'u> UdmaSpim<'u, Disabled> {
-    /// Enables the uDMA clock gate for SPI-M
+    /// Enables the uDMA clock gate for SPI-M and routes it to the given pads.
+    /// SDIO0 is the output and SDIO1 the input in single mode. The pads stay
+    /// configured for the SPI-M.
+    #[inline]
+    pub fn enable<const SCK: u32, const CS0: u32, const SDIO0: u32, const SDIO1: u32>(
+        self,
+        _sck: AltPad<SCK, signal::SpimSck>,
+        _cs0: AltPad<CS0, signal::SpimCs0>,
+        _sdio0: AltPad<SDIO0, signal::SpimSdio0>,
+        _sdio1: AltPad<SDIO1, signal::SpimSdio1>,
+    ) -> UdmaSpim<'u, Enabled> {
+        // SAFETY: the pads are owned by the SPI-M from now on
+        unsafe { self.enable_unchecked() }
+    }
+
+    /// Like [UdmaSpim::enable], but without claiming the pads
+    ///
+    /// # Safety
+    ///
+    /// Pads 2 to 5 carry the SPI-M by default. Nothing else may use them,
+    /// e.g., as GPIO.
     #[inline(always)]
-    pub fn enable(self) -> UdmaSpim<'u, Enabled> {
+    pub unsafe fn enable_unchecked(self) -> UdmaSpim<'u, Enabled> {
         let spim = &self.0;
 
         // Turn on the clock gates for SPIM
@@ -153,18 +195,7 @@ impl<'u> UdmaSpim<'u, Enabled> {
     /// ```
     #[inline(always)]
     pub fn send_data(&mut self, data: &[u8]) {
-        let mut cmd_data: [u8; 12] = [0; 12];
-
-        cmd_data[0..4].copy_from_slice(
-            &(SPI_CMD_SETUP_UCA | (data.as_ptr() as u32 & 0x0000FFFF)).to_ne_bytes(),
-        );
-        cmd_data[4..8]
-            // 4 byte but change this to depend on data i.e: ((data.len() - 2) as u32)
-            .copy_from_slice(&(SPI_CMD_SETUP_UCS | (data.len() - 2) as u32).to_ne_bytes());
-        cmd_data[8..12].copy_from_slice(
-            &(SPI_CMD_TX_DATA | (data.len() - 1) as u32 | (7 << 16)).to_ne_bytes(),
-        );
-
+        let cmd_data = Self::data_cmds(SPI_CMD_TX_DATA, data);
         self.write_cmd(&cmd_data);
         self.write_tx(data);
     }
@@ -182,6 +213,233 @@ impl<'u> UdmaSpim<'u, Enabled> {
     /// ```
     #[inline(always)]
     pub fn receive_data(&mut self, data: &mut [u8]) -> Result<usize, SpimError> {
+        let cmd_data = Self::data_cmds(SPI_CMD_RX_DATA, data);
+        self.write_cmd(&cmd_data);
+        self.read_rx(data)
+    }
+
+    /// Quad version of [UdmaSpim::receive_data]. Data is received on all four lines.
+    #[inline(always)]
+    pub fn receive_data_quad(&mut self, data: &mut [u8]) -> Result<usize, SpimError> {
+        let cmd_data = Self::data_cmds(SPI_CMD_RX_DATA | SPI_CMD_QPI, data);
+        self.write_cmd(&cmd_data);
+        self.read_rx(data)
+    }
+
+    /// Starts writing `buf` to the CMD channel without blocking
+    ///
+    /// The returned [Transfer] gives back `buf` and the SPI-M once complete.
+    pub fn write_cmd_dma<B>(mut self, buf: B) -> Transfer<'u, B, Self>
+    where
+        B: ReadBuffer<Word = u8>,
+    {
+        while !self.can_enqueue_cmd() {}
+
+        // SAFETY: `buf` is owned by the transfer until the uDMA is done with it
+        unsafe {
+            let (ptr, len) = buf.read_buffer();
+            self.enqueue_cmd(core::slice::from_raw_parts(ptr, len));
+        }
+
+        Transfer::new(self.0, Channel::SpimCmd, buf, self)
+    }
+
+    /// Starts writing `buf` to the TX channel without blocking
+    ///
+    /// The returned [Transfer] gives back `buf` and the SPI-M once complete.
+    pub fn write_tx_dma<B>(mut self, buf: B) -> Transfer<'u, B, Self>
+    where
+        B: ReadBuffer<Word = u8>,
+    {
+        while !self.can_enqueue_tx() {}
+
+        // SAFETY: `buf` is owned by the transfer until the uDMA is done with it
+        unsafe {
+            let (ptr, len) = buf.read_buffer();
+            self.enqueue_tx(core::slice::from_raw_parts(ptr, len));
+        }
+
+        Transfer::new(self.0, Channel::SpimTx, buf, self)
+    }
+
+    /// Starts reading into `buf` from the RX channel without blocking
+    ///
+    /// The returned [Transfer] gives back `buf` and the SPI-M once complete.
+    pub fn read_rx_dma<B>(mut self, mut buf: B) -> Transfer<'u, B, Self>
+    where
+        B: WriteBuffer<Word = u8>,
+    {
+        while !self.can_enqueue_rx() {}
+
+        // SAFETY: `buf` is owned by the transfer until the uDMA is done with it
+        unsafe {
+            let (ptr, len) = buf.write_buffer();
+            self.enqueue_rx(core::slice::from_raw_parts_mut(ptr, len));
+        }
+
+        Transfer::new(self.0, Channel::SpimRx, buf, self)
+    }
+
+    /// Non-blocking version of [UdmaSpim::send_data]. Only the data phase is
+    /// non-blocking, the commands are written before returning.
+    pub fn send_data_dma<B>(mut self, data: B) -> Transfer<'u, B, Self>
+    where
+        B: ReadBuffer<Word = u8>,
+    {
+        // SAFETY: only used for building the commands
+        let cmd_data = unsafe {
+            let (ptr, len) = data.read_buffer();
+            Self::data_cmds(SPI_CMD_TX_DATA, core::slice::from_raw_parts(ptr, len))
+        };
+        self.write_cmd(&cmd_data);
+        self.write_tx_dma(data)
+    }
+
+    /// Non-blocking version of [UdmaSpim::receive_data]. Only the data phase
+    /// is non-blocking, the commands are written before returning.
+    pub fn receive_data_dma<B>(mut self, mut data: B) -> Transfer<'u, B, Self>
+    where
+        B: WriteBuffer<Word = u8>,
+    {
+        // SAFETY: only used for building the commands
+        let cmd_data = unsafe {
+            let (ptr, len) = data.write_buffer();
+            Self::data_cmds(SPI_CMD_RX_DATA, core::slice::from_raw_parts(ptr, len))
+        };
+        self.write_cmd(&cmd_data);
+        self.read_rx_dma(data)
+    }
+
+    /// Async version of [UdmaSpim::write_cmd]
+    #[cfg(feature = "async")]
+    pub async fn write_cmd_async(&mut self, buf: &[u8]) {
+        while !self.can_enqueue_cmd() {}
+
+        // SAFETY: `WaitOnDrop` makes sure the transfer is complete before `buf` can be dropped
+        unsafe { self.enqueue_cmd(buf) };
+
+        let spim = &self.0;
+        let is_done = || spim.spim_cmd_saddr().read().bits() == 0;
+        let _guard = WaitOnDrop(is_done);
+        wait_for_event(event::SPIM_CMD, is_done).await;
+    }
+
+    /// Async version of [UdmaSpim::write_tx]
+    #[cfg(feature = "async")]
+    pub async fn write_tx_async(&mut self, buf: &[u8]) {
+        while !self.can_enqueue_tx() {}
+
+        // SAFETY: `WaitOnDrop` makes sure the transfer is complete before `buf` can be dropped
+        unsafe { self.enqueue_tx(buf) };
+
+        let spim = &self.0;
+        let is_done = || spim.spim_tx_saddr().read().bits() == 0;
+        let _guard = WaitOnDrop(is_done);
+        wait_for_event(event::SPIM_TX, is_done).await;
+    }
+
+    /// Async version of [UdmaSpim::read_rx]
+    #[cfg(feature = "async")]
+    pub async fn read_rx_async(&mut self, buf: &mut [u8]) -> Result<usize, SpimError> {
+        while !self.can_enqueue_rx() {}
+
+        // SAFETY: `WaitOnDrop` makes sure the transfer is complete before `buf` can be dropped
+        unsafe { self.enqueue_rx(buf) };
+
+        let spim = &self.0;
+        let is_done = || spim.spim_rx_saddr().read().bits() == 0;
+        let _guard = WaitOnDrop(is_done);
+        wait_for_event(event::SPIM_RX, is_done).await;
+
+        Ok(buf.len())
+    }
+
+    /// Async version of [UdmaSpim::send_data]
+    #[cfg(feature = "async")]
+    pub async fn send_data_async(&mut self, data: &[u8]) {
+        let cmd_data = Self::data_cmds(SPI_CMD_TX_DATA, data);
+        self.write_cmd_async(&cmd_data).await;
+        self.write_tx_async(data).await;
+    }
+
+    /// Async version of [UdmaSpim::receive_data]
+    #[cfg(feature = "async")]
+    pub async fn receive_data_async(&mut self, data: &mut [u8]) -> Result<usize, SpimError> {
+        let cmd_data = Self::data_cmds(SPI_CMD_RX_DATA, data);
+        self.write_cmd_async(&cmd_data).await;
+        self.read_rx_async(data).await
+    }
+
+    /// Clocks out `len` bytes of `0xFF` with chip select deasserted
+    ///
+    /// Call outside a SOT/EOT pair, e.g., to give an SD card the 74+ clock
+    /// cycles it needs before the first command.
+    pub fn send_idle_clocks(&mut self, len: usize) {
+        let ones = [0xFFu8; 16];
+        let mut left = len;
+        while left > 0 {
+            let n = left.min(ones.len());
+            self.spi_write(&ones[..n]);
+            left -= n;
+        }
+    }
+
+    /// Writes `data` with TX data commands of at most [MAX_WORDS_PER_CMD]
+    /// bytes. Unlike [UdmaSpim::send_data], any length is supported.
+    fn spi_write(&mut self, data: &[u8]) {
+        for chunk in data.chunks(MAX_WORDS_PER_CMD) {
+            while !self.can_enqueue_tx() {}
+            // SAFETY: we spin lock on spim_tx_saddr to make sure the transfer is complete before
+            // `chunk` is released
+            unsafe { self.enqueue_tx(chunk) };
+            self.write_cmd(&Self::words_cmd(SPI_CMD_TX_DATA, chunk.len()));
+            while self.0.spim_tx_saddr().read().bits() != 0 {}
+        }
+    }
+
+    /// Reads into `buf` with RX data commands of at most [MAX_WORDS_PER_CMD]
+    /// bytes. Unlike [UdmaSpim::receive_data], any length is supported.
+    fn spi_read(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(MAX_WORDS_PER_CMD) {
+            while !self.can_enqueue_rx() {}
+            // SAFETY: we spin lock on spim_rx_saddr to make sure the transfer is complete before
+            // `chunk` is released
+            unsafe { self.enqueue_rx(chunk) };
+            self.write_cmd(&Self::words_cmd(SPI_CMD_RX_DATA, chunk.len()));
+            while self.0.spim_rx_saddr().read().bits() != 0 {}
+        }
+    }
+
+    /// Writes `write` while reading into `read`. Both must be of the same
+    /// length.
+    fn spi_transfer(&mut self, read: &mut [u8], write: &[u8]) {
+        debug_assert_eq!(read.len(), write.len());
+        for (read, write) in read
+            .chunks_mut(MAX_WORDS_PER_CMD)
+            .zip(write.chunks(MAX_WORDS_PER_CMD))
+        {
+            while !self.can_enqueue_rx() || !self.can_enqueue_tx() {}
+            // SAFETY: we spin lock on both channels to make sure the transfer is complete before
+            // the buffers are released
+            unsafe {
+                self.enqueue_rx(read);
+                self.enqueue_tx(write);
+            }
+            self.write_cmd(&Self::words_cmd(SPI_CMD_FULL_DUPLEX, read.len()));
+            let spim = &self.0;
+            while spim.spim_tx_saddr().read().bits() != 0 || spim.spim_rx_saddr().read().bits() != 0
+            {
+            }
+        }
+    }
+
+    /// Command for transferring `len` 8-bit words with `data_cmd`
+    fn words_cmd(data_cmd: u32, len: usize) -> [u8; 4] {
+        (data_cmd | (len - 1) as u32 | (7 << 16)).to_ne_bytes()
+    }
+
+    /// Builds the command sequence for transferring `data` with `data_cmd`
+    fn data_cmds(data_cmd: u32, data: &[u8]) -> [u8; 12] {
         let mut cmd_data: [u8; 12] = [0; 12];
 
         cmd_data[0..4].copy_from_slice(
@@ -189,13 +447,9 @@ impl<'u> UdmaSpim<'u, Enabled> {
         );
         cmd_data[4..8]
             .copy_from_slice(&(SPI_CMD_SETUP_UCS | (data.len() - 2) as u32).to_ne_bytes());
-        cmd_data[8..12].copy_from_slice(
-            &(SPI_CMD_RX_DATA | (data.len() - 1) as u32 | (7 << 16)).to_ne_bytes(),
-        );
-
-        self.write_cmd(&cmd_data);
-
-        self.read_rx(data)
+        cmd_data[8..12]
+            .copy_from_slice(&(data_cmd | (data.len() - 1) as u32 | (7 << 16)).to_ne_bytes());
+        cmd_data
     }
 
     /// Can a new transfer be enqueued to the CMD channel?
@@ -291,3 +545,51 @@ impl embedded_io::Error for SpimError {
 impl<'u> ErrorType for UdmaSpim<'u, Enabled> {
     type Error = SpimError;
 }
+
+impl spi::Error for SpimError {
+    fn kind(&self) -> spi::ErrorKind {
+        spi::ErrorKind::Other
+    }
+}
+
+impl<'u> spi::ErrorType for UdmaSpim<'u, Enabled> {
+    type Error = SpimError;
+}
+
+/// Each transaction is framed by SOT and EOT, which assert and deassert the
+/// chip select
+impl<'u> spi::SpiDevice for UdmaSpim<'u, Enabled> {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        self.send_sot();
+        for op in operations {
+            match op {
+                Operation::Read(buf) => self.spi_read(buf),
+                Operation::Write(data) => self.spi_write(data),
+                Operation::Transfer(read, write) => {
+                    // The longer buffer is padded by a plain read or write
+                    let common = read.len().min(write.len());
+                    let (read, read_rest) = read.split_at_mut(common);
+                    let (write, write_rest) = write.split_at(common);
+                    self.spi_transfer(read, write);
+                    self.spi_read(read_rest);
+                    self.spi_write(write_rest);
+                }
+                Operation::TransferInPlace(buf) => {
+                    // The uDMA channels can't share a buffer, so the outgoing
+                    // words are copied aside first
+                    let mut tx = [0u8; IN_PLACE_CHUNK];
+                    for chunk in buf.chunks_mut(IN_PLACE_CHUNK) {
+                        let tx = &mut tx[..chunk.len()];
+                        tx.copy_from_slice(chunk);
+                        self.spi_transfer(chunk, tx);
+                    }
+                }
+                Operation::DelayNs(ns) => {
+                    embedded_hal::delay::DelayNs::delay_ns(&mut crate::sysctrl::time::Delay, *ns)
+                }
+            }
+        }
+        self.send_eot();
+        Ok(())
+    }
+}
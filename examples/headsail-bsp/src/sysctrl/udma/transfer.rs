@@ -0,0 +1,101 @@
+//! Non-blocking uDMA transfers
+//!
+//! A [Transfer] owns the buffer and the peripheral for the duration of the
+//! transfer and hands both back once the uDMA is done with the buffer. Buffers
+//! must implement [embedded_dma::ReadBuffer] or [embedded_dma::WriteBuffer],
+//! e.g., `&'static [u8]` or `&'static mut [u8; N]`.
+use core::{mem::ManuallyDrop, ptr};
+
+use crate::pac;
+#[cfg(feature = "async")]
+use crate::sysctrl::itc::wait_for_event;
+
+/// uDMA channel used by a [Transfer]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Channel {
+    UartTx,
+    SpimTx,
+    SpimRx,
+    SpimCmd,
+}
+
+impl Channel {
+    /// The channel clears its start address once the transfer is complete
+    fn is_done(self, udma: &pac::sysctrl::Udma) -> bool {
+        let saddr = match self {
+            Channel::UartTx => udma.uart_tx_saddr().read().bits(),
+            Channel::SpimTx => udma.spim_tx_saddr().read().bits(),
+            Channel::SpimRx => udma.spim_rx_saddr().read().bits(),
+            Channel::SpimCmd => udma.spim_cmd_saddr().read().bits(),
+        };
+        saddr == 0
+    }
+
+    #[cfg(feature = "async")]
+    fn event(self) -> u8 {
+        use super::event;
+        match self {
+            Channel::UartTx => event::UART_TX,
+            Channel::SpimTx => event::SPIM_TX,
+            Channel::SpimRx => event::SPIM_RX,
+            Channel::SpimCmd => event::SPIM_CMD,
+        }
+    }
+}
+
+/// An ongoing uDMA transfer of `buf` by `periph`
+///
+/// Dropping the transfer blocks until it completes, so the buffer is never
+/// released while the uDMA is still accessing it.
+#[must_use = "dropping a transfer blocks until it's complete"]
+pub struct Transfer<'u, B, P> {
+    udma: &'u pac::sysctrl::Udma,
+    channel: Channel,
+    buf: B,
+    periph: P,
+}
+
+impl<'u, B, P> Transfer<'u, B, P> {
+    pub(crate) fn new(udma: &'u pac::sysctrl::Udma, channel: Channel, buf: B, periph: P) -> Self {
+        Self {
+            udma,
+            channel,
+            buf,
+            periph,
+        }
+    }
+
+    /// Returns `true` when the uDMA is done with the buffer
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.channel.is_done(self.udma)
+    }
+
+    /// Blocks until the transfer is complete. Returns the buffer and the
+    /// peripheral.
+    pub fn wait(self) -> (B, P) {
+        while !self.is_done() {}
+        self.release()
+    }
+
+    /// Waits for the transfer to complete on the uDMA channel event. Returns
+    /// the buffer and the peripheral.
+    #[cfg(feature = "async")]
+    pub async fn wait_async(self) -> (B, P) {
+        wait_for_event(self.channel.event(), || self.is_done()).await;
+        self.release()
+    }
+
+    /// Moves the buffer and the peripheral out without running `Drop`
+    fn release(self) -> (B, P) {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped again
+        unsafe { (ptr::read(&this.buf), ptr::read(&this.periph)) }
+    }
+}
+
+impl<'u, B, P> Drop for Transfer<'u, B, P> {
+    fn drop(&mut self) {
+        while !self.is_done() {}
+    }
+}
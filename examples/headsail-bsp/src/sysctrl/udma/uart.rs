@@ -1,7 +1,19 @@
 use core::marker::PhantomData;
 
-use super::{Disabled, Enabled};
-use crate::pac;
+use super::{
+    transfer::{Channel, Transfer},
+    Disabled, Enabled, Receiving, UdmaPeriphState,
+};
+use crate::{
+    pac,
+    sysctrl::pad_mux::{signal, AltPad},
+};
+use embedded_dma::ReadBuffer;
+#[cfg(feature = "async")]
+use {
+    super::{event, WaitOnDrop},
+    crate::sysctrl::itc::{wait_for_event, wait_for_events},
+};
 
 /// Obtain an instance by calling [Udma::split]
 pub struct UdmaUart<'u, UdmaPeriphState>(
@@ -11,11 +23,37 @@ pub struct UdmaUart<'u, UdmaPeriphState>(
 
 type UartSetupW = pac::sysctrl::udma::uart_setup::W;
 
+/// States in which the UART can transmit
+pub trait TxState: UdmaPeriphState {}
+
+impl TxState for Enabled {}
+impl TxState for Receiving {}
+
 impl<'u> UdmaUart<'u, Disabled> {
     /// Enables the uDMA clock gate for UART and sets up the peripheral with the provided register
-    /// writer.
+    /// writer. The UART is routed to `tx` and `rx`, which stay configured for the UART.
+    #[inline]
+    pub fn enable<F, const TX: u32, const RX: u32>(
+        self,
+        _tx: AltPad<TX, signal::UdmaUartTx>,
+        _rx: AltPad<RX, signal::UdmaUartRx>,
+        setup_spec: F,
+    ) -> UdmaUart<'u, Enabled>
+    where
+        F: FnOnce(&mut UartSetupW) -> &mut UartSetupW,
+    {
+        // SAFETY: the pads are owned by the UART from now on
+        unsafe { self.enable_unchecked(setup_spec) }
+    }
+
+    /// Like [UdmaUart::enable], but without claiming the pads
+    ///
+    /// # Safety
+    ///
+    /// Pads 0 and 1 carry the UART by default. Nothing else may use them,
+    /// e.g., as GPIO.
     #[inline]
-    pub fn enable<F>(self, setup_spec: F) -> UdmaUart<'u, Enabled>
+    pub unsafe fn enable_unchecked<F>(self, setup_spec: F) -> UdmaUart<'u, Enabled>
     where
         F: FnOnce(&mut UartSetupW) -> &mut UartSetupW,
     {
@@ -52,6 +90,48 @@ impl<'u> UdmaUart<'u, Enabled> {
         Self(udma, PhantomData)
     }
 
+    /// Starts receiving into `ring` in continuous mode
+    ///
+    /// The uDMA keeps writing received bytes around `ring`, starting over from
+    /// the beginning when it's full. Bytes are read out with the returned
+    /// [UdmaUartRx]. Bytes not read before the uDMA wraps around are
+    /// overwritten. Requires RX to be enabled in the UART setup.
+    ///
+    /// The UART can still transmit while receiving. Use [UdmaUartRx::stop] to
+    /// get the ring and the enabled UART back.
+    ///
+    /// # Panics
+    ///
+    /// If `ring` is empty.
+    pub fn start_rx(self, ring: &'static mut [u8]) -> (UdmaUart<'u, Receiving>, UdmaUartRx<'u>) {
+        assert!(!ring.is_empty(), "uDMA UART RX ring is empty");
+        let udma = &self.0;
+
+        // Write buffer location & len
+        udma.uart_rx_saddr()
+            .write(|w| unsafe { w.bits(ring.as_ptr() as u32) });
+        udma.uart_rx_size()
+            .write(|w| unsafe { w.bits(ring.len() as u32) });
+
+        // Raise an event on every received byte, not only when the ring wraps
+        udma.uart_irq_en().modify(|_r, w| w.rx().set_bit());
+
+        // Dispatch reception
+        udma.uart_rx_cfg()
+            .write(|w| w.continous().set_bit().en().set_bit());
+
+        (
+            UdmaUart(self.0, PhantomData),
+            UdmaUartRx {
+                udma: self.0,
+                ring,
+                read_idx: 0,
+            },
+        )
+    }
+}
+
+impl<'u, S: TxState> UdmaUart<'u, S> {
     #[inline]
     pub fn write(&mut self, buf: &[u8]) {
         let udma = &self.0;
@@ -69,13 +149,174 @@ impl<'u> UdmaUart<'u, Enabled> {
         while udma.uart_tx_saddr().read().bits() != 0 {}
     }
 
+    /// Starts writing `buf` without blocking
+    ///
+    /// The returned [Transfer] gives back `buf` and the UART once complete.
+    pub fn write_dma<B>(self, buf: B) -> Transfer<'u, B, Self>
+    where
+        B: ReadBuffer<Word = u8>,
+    {
+        let udma = &self.0;
+        // SAFETY: `buf` is owned by the transfer until the uDMA is done with it
+        let (ptr, len) = unsafe { buf.read_buffer() };
+
+        // Write buffer location & len
+        udma.uart_tx_saddr()
+            .write(|w| unsafe { w.bits(ptr as u32) });
+        udma.uart_tx_size().write(|w| unsafe { w.bits(len as u32) });
+
+        // Dispatch transmission
+        udma.uart_tx_cfg().write(|w| w.en().set_bit());
+
+        Transfer::new(self.0, Channel::UartTx, buf, self)
+    }
+
     #[inline]
     pub fn write_str(&mut self, s: &str) {
         self.write(s.as_bytes());
     }
+
+    /// Writes `buf`, waiting for the uDMA TX event instead of polling
+    #[cfg(feature = "async")]
+    pub async fn write_async(&mut self, buf: &[u8]) {
+        let udma = &self.0;
+
+        // Write buffer location & len
+        udma.uart_tx_saddr()
+            .write(|w| unsafe { w.bits(buf.as_ptr() as u32) });
+        udma.uart_tx_size()
+            .write(|w| unsafe { w.bits(buf.len() as u32) });
+
+        // Dispatch transmission
+        udma.uart_tx_cfg().write(|w| w.en().set_bit());
+
+        let is_done = || udma.uart_tx_saddr().read().bits() == 0;
+        let _guard = WaitOnDrop(is_done);
+        wait_for_event(event::UART_TX, is_done).await;
+    }
+}
+
+/// Receiving half of the uDMA UART. Obtain an instance by calling
+/// [UdmaUart::start_rx].
+pub struct UdmaUartRx<'u> {
+    udma: &'u pac::sysctrl::Udma,
+    /// Ring buffer written by the uDMA, never empty
+    ring: &'static mut [u8],
+    /// Index of the next byte to read in `ring`
+    read_idx: usize,
+}
+
+impl<'u> UdmaUartRx<'u> {
+    /// Index in the ring the uDMA writes the next byte to
+    fn write_idx(&self) -> usize {
+        let cur = self.udma.uart_rx_saddr().read().bits() as usize;
+        match cur.wrapping_sub(self.ring.as_ptr() as usize) {
+            idx if idx < self.ring.len() => idx,
+            // The channel is reloading
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of received bytes that have not been read yet
+    #[inline]
+    pub fn available(&self) -> usize {
+        let len = self.ring.len();
+        (self.write_idx() + len - self.read_idx) % len
+    }
+
+    /// Copies the received bytes into `buf` without blocking. Returns the
+    /// number of bytes copied.
+    pub fn read_available(&mut self, buf: &mut [u8]) -> usize {
+        let count = self.available().min(buf.len());
+        let ring = self.ring.as_ptr();
+        for b in &mut buf[..count] {
+            // SAFETY: `read_idx` stays within `ring`, which is owned by `self`
+            *b = unsafe { core::ptr::read_volatile(ring.add(self.read_idx)) };
+            self.read_idx = (self.read_idx + 1) % self.ring.len();
+        }
+        count
+    }
+
+    /// Stops reception. Returns the UART, no longer receiving, and the ring.
+    pub fn stop(self, uart: UdmaUart<'u, Receiving>) -> (UdmaUart<'u, Enabled>, &'static mut [u8]) {
+        let udma = &self.udma;
+        udma.uart_rx_cfg().write(|w| w.clr().set_bit());
+        udma.uart_irq_en().modify(|_r, w| w.rx().clear_bit());
+        (UdmaUart(uart.0, PhantomData), self.ring)
+    }
+
+    /// Blocks until at least one byte is received, then copies the received
+    /// bytes into `buf`. Returns the number of bytes copied.
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        if buf.is_empty() {
+            return 0;
+        }
+        while self.available() == 0 {}
+        self.read_available(buf)
+    }
+
+    /// Blocks until a byte is received and returns it
+    pub fn read_byte(&mut self) -> u8 {
+        let mut b = [0u8];
+        self.read(&mut b);
+        b[0]
+    }
+
+    /// Waits for at least one byte to be received, then copies the received
+    /// bytes into `buf`. Returns the number of bytes copied.
+    ///
+    /// The waiting task is woken up by the event raised for each received
+    /// byte, as well as the one raised when the uDMA wraps around the ring.
+    #[cfg(feature = "async")]
+    pub async fn read_async(&mut self, buf: &mut [u8]) -> usize {
+        if buf.is_empty() {
+            return 0;
+        }
+        wait_for_events(&[event::UART_RX_DATA, event::UART_RX], || {
+            self.available() != 0
+        })
+        .await;
+        self.read_available(buf)
+    }
+}
+
+impl<'u> embedded_io::ErrorType for UdmaUartRx<'u> {
+    type Error = core::convert::Infallible;
+}
+
+impl<'u> embedded_io::Read for UdmaUartRx<'u> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        Ok(UdmaUartRx::read(self, buf))
+    }
+}
+
+impl<'u> embedded_io::ReadReady for UdmaUartRx<'u> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.available() != 0)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'u> embedded_io_async::Read for UdmaUartRx<'u> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        Ok(self.read_async(buf).await)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'u, S: TxState> embedded_io::ErrorType for UdmaUart<'u, S> {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "async")]
+impl<'u, S: TxState> embedded_io_async::Write for UdmaUart<'u, S> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.write_async(buf).await;
+        Ok(buf.len())
+    }
 }
 
-impl<'a> ufmt_write::uWrite for UdmaUart<'a, Enabled> {
+impl<'a, S: TxState> ufmt_write::uWrite for UdmaUart<'a, S> {
     type Error = core::convert::Infallible;
 
     fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
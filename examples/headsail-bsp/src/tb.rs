@@ -1,6 +1,31 @@
 //! Common testbench definitions to retain a consistent test setup
+//!
+//! # Test harness
+//!
+//! Tests are `fn() -> TestResult` registered with [headsail_test]. The
+//! registered tests are placed in the `.headsail_tests` link section, so the
+//! binary must be linked with `-Ttb.x`, which is done for the examples of this
+//! crate.
+//!
+//! [headsail_tests](crate::headsail_tests) generates an entry point that runs
+//! the tests and reports each on APB UART0 on its own line, followed by a
+//! summary:
+//!
+//! ```text
+//! [TEST] name=uart_loopback result=pass cycles=1234
+//! [TEST] name=timer_wraps result=fail cycles=567 at=src/main.rs:42 msg=assertion failed: t == 10, left: 12, right: 10
+//! [DONE] total=2 passed=1 failed=1
+//! [FAIL]
+//! ```
+//!
+//! `msg` is the rest of the line. The harness then exits via [exit], with the
+//! number of failed tests as the exit code.
 
 use crate::apb_uart::ApbUart0;
+use core::{convert::Infallible, fmt};
+pub use headsail_bsp_macros::headsail_test;
+use riscv::register::mcycle;
+use ufmt::{uWrite, uwriteln};
 
 pub const TAG_FAIL: &str = "[FAIL]";
 pub const TAG_PASS: &str = "[PASS]";
@@ -8,6 +33,12 @@ pub const TAG_PASS: &str = "[PASS]";
 /// Tag signifies partial success in test, i.e. part of the test succeeded, but doesn't implicate that whole test has been either succesful/[PASS] or unsuccesful/[FAIL].
 pub const TAG_OK: &str = "[OK]";
 
+/// Tag for the result line of a single test case
+pub const TAG_TEST: &str = "[TEST]";
+
+/// Tag for the summary line printed after all test cases
+pub const TAG_DONE: &str = "[DONE]";
+
 pub fn report_pass() {
     let mut uart = unsafe { ApbUart0::instance() };
     uart.write_str(TAG_PASS);
@@ -22,3 +53,293 @@ pub fn report_ok() {
     let mut uart = unsafe { ApbUart0::instance() };
     uart.write_str(TAG_OK);
 }
+
+/// Capacity of [FailureMsg] in bytes
+pub const FAILURE_MSG_LEN: usize = 128;
+
+/// Message of a [TestFailure], formatted into a fixed buffer. Longer messages
+/// are truncated.
+#[derive(Clone, Copy)]
+pub struct FailureMsg {
+    buf: [u8; FAILURE_MSG_LEN],
+    len: usize,
+}
+
+impl FailureMsg {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; FAILURE_MSG_LEN],
+            len: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only whole characters are ever written
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Default for FailureMsg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl uWrite for FailureMsg {
+    type Error = Infallible;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        let mut n = s.len().min(FAILURE_MSG_LEN - self.len);
+        while !s.is_char_boundary(n) {
+            n -= 1;
+        }
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FailureMsg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// Location and cause of a failed test, sa. [tb_assert](crate::tb_assert)
+#[derive(Clone, Copy, Debug)]
+pub struct TestFailure {
+    pub msg: FailureMsg,
+    pub file: &'static str,
+    pub line: u32,
+}
+
+pub type TestResult = Result<(), TestFailure>;
+
+pub struct TestCase {
+    pub name: &'static str,
+    pub function: fn() -> TestResult,
+}
+
+/// Returns the tests registered with [headsail_test], in link order
+pub fn registered_tests() -> &'static [TestCase] {
+    extern "C" {
+        static __headsail_tests_start: TestCase;
+        static __headsail_tests_end: TestCase;
+    }
+    // SAFETY: `tb.x` places only `TestCase`s between the symbols
+    unsafe {
+        let start = core::ptr::addr_of!(__headsail_tests_start);
+        let end = core::ptr::addr_of!(__headsail_tests_end);
+        core::slice::from_raw_parts(start, end.offset_from(start) as usize)
+    }
+}
+
+/// Returns early from a test with a [TestFailure] unless `cond` holds
+///
+/// The message is formatted with `ufmt`, e.g.,
+/// `tb_assert!(n < 10, "n = {}", n)`, which requires `ufmt` in scope.
+#[macro_export]
+macro_rules! tb_assert {
+    ($cond:expr $(,)?) => {
+        $crate::tb_assert!($cond, concat!("assertion failed: ", stringify!($cond)))
+    };
+    ($cond:expr, $msg:expr $(,)?) => {
+        if !$cond {
+            let mut msg = $crate::tb::FailureMsg::new();
+            let _ = $crate::ufmt::uWrite::write_str(&mut msg, $msg);
+            return Err($crate::tb::TestFailure {
+                msg,
+                file: file!(),
+                line: line!(),
+            });
+        }
+    };
+    ($cond:expr, $fmt:literal, $($arg:tt)+) => {
+        if !$cond {
+            let mut msg = $crate::tb::FailureMsg::new();
+            let _ = $crate::ufmt::uwrite!(msg, $fmt, $($arg)+);
+            return Err($crate::tb::TestFailure {
+                msg,
+                file: file!(),
+                line: line!(),
+            });
+        }
+    };
+}
+
+/// Returns early from a test with a [TestFailure] unless `left == right`. The
+/// message includes both values, which must implement `ufmt::uDebug`.
+#[macro_export]
+macro_rules! tb_assert_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    let mut msg = $crate::tb::FailureMsg::new();
+                    let _ = $crate::ufmt::uWrite::write_str(
+                        &mut msg,
+                        concat!(
+                            "assertion failed: ",
+                            stringify!($left),
+                            " == ",
+                            stringify!($right),
+                            ", left: "
+                        ),
+                    );
+                    let _ = $crate::ufmt::uDebug::fmt(
+                        left,
+                        &mut $crate::ufmt::Formatter::new(&mut msg),
+                    );
+                    let _ = $crate::ufmt::uWrite::write_str(&mut msg, ", right: ");
+                    let _ = $crate::ufmt::uDebug::fmt(
+                        right,
+                        &mut $crate::ufmt::Formatter::new(&mut msg),
+                    );
+                    return Err($crate::tb::TestFailure {
+                        msg,
+                        file: file!(),
+                        line: line!(),
+                    });
+                }
+            }
+        }
+    };
+}
+
+/// Generates the entry point for running the tests registered with
+/// [headsail_test], or the given `tests`, with [run_tests]
+///
+/// # Example
+///
+/// ```ignore
+/// use headsail_bsp::{headsail_tests, tb::{headsail_test, TestResult}, tb_assert_eq};
+///
+/// #[headsail_test]
+/// fn adds() -> TestResult {
+///     tb_assert_eq!(1 + 1, 2);
+///     Ok(())
+/// }
+///
+/// headsail_tests!();
+/// ```
+#[macro_export]
+macro_rules! headsail_tests {
+    () => {
+        #[$crate::rt::entry]
+        fn main() -> ! {
+            $crate::tb::run_tests($crate::tb::registered_tests())
+        }
+    };
+    ($($test:path),+ $(,)?) => {
+        #[$crate::rt::entry]
+        fn main() -> ! {
+            $crate::tb::run_tests(&[$(
+                $crate::tb::TestCase {
+                    name: stringify!($test),
+                    function: $test,
+                },
+            )*])
+        }
+    };
+}
+
+/// Runs `tests`, reports the results on APB UART0 and [exit]s with the number
+/// of failures
+///
+/// Test duration is reported in `mcycle` cycles.
+pub fn run_tests(tests: &[TestCase]) -> ! {
+    // 30 MHz
+    let (soc_freq, baud) = (30_000_000, 115_200);
+    let mut uart = ApbUart0::init(soc_freq, baud);
+
+    let mut failed = 0;
+    for t in tests {
+        let start = mcycle::read64();
+        let result = (t.function)();
+        let cycles = mcycle::read64().wrapping_sub(start);
+        match result {
+            Ok(()) => uwriteln!(
+                uart,
+                "{} name={} result=pass cycles={}",
+                TAG_TEST,
+                t.name,
+                cycles
+            )
+            .unwrap(),
+            Err(f) => {
+                failed += 1;
+                uwriteln!(
+                    uart,
+                    "{} name={} result=fail cycles={} at={}:{} msg={}",
+                    TAG_TEST,
+                    t.name,
+                    cycles,
+                    f.file,
+                    f.line,
+                    f.msg.as_str()
+                )
+                .unwrap()
+            }
+        }
+    }
+    uwriteln!(
+        uart,
+        "{} total={} passed={} failed={}",
+        TAG_DONE,
+        tests.len(),
+        tests.len() - failed,
+        failed
+    )
+    .unwrap();
+
+    // Keep the tags for existing scripts that wait for them
+    if failed == 0 {
+        report_pass();
+    } else {
+        report_fail();
+    }
+    uart.write_str("\n");
+
+    exit(failed as u32)
+}
+
+/// Ends the test run with exit code `code`, where `0` means success
+///
+/// With `-Fsemihosting`, exits through semihosting `SYS_EXIT_EXTENDED`. On
+/// VP, writes `code` into the test finisher, which logs "Test finished with
+/// code `code`". Otherwise, waits for interrupts forever.
+pub fn exit(code: u32) -> ! {
+    #[cfg(feature = "semihosting")]
+    semihosting_exit(code);
+    #[cfg(all(feature = "vp", not(feature = "semihosting")))]
+    crate::write_u32(crate::mmap::TEST_FINISHER_ADDR, code);
+    #[cfg(not(any(feature = "vp", feature = "semihosting")))]
+    let _ = code;
+
+    loop {
+        riscv::asm::wfi();
+    }
+}
+
+#[cfg(feature = "semihosting")]
+fn semihosting_exit(code: u32) {
+    const SYS_EXIT_EXTENDED: usize = 0x20;
+    const ADP_STOPPED_APPLICATION_EXIT: usize = 0x20026;
+    let args = [ADP_STOPPED_APPLICATION_EXIT, code as usize];
+    // The semihosting call is marked by this exact sequence of uncompressed
+    // instructions, which may not cross a page boundary
+    unsafe {
+        core::arch::asm!(
+            ".option push",
+            ".option norvc",
+            ".balign 16",
+            "slli x0, x0, 0x1f",
+            "ebreak",
+            "srai x0, x0, 7",
+            ".option pop",
+            inout("a0") SYS_EXIT_EXTENDED => _,
+            in("a1") args.as_ptr(),
+            options(nostack),
+        );
+    }
+}
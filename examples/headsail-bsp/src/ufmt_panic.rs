@@ -1,14 +1,24 @@
 //! Set panicking behavior to print into UART
+//!
+//! With `panic-verbose`, panics print the HART, trap CSRs, a backtrace and
+//! the formatted message, and exceptions are reported the same way.
 
 use core::panic::PanicInfo;
 
+#[cfg(not(feature = "panic-verbose"))]
 use crate::ufmt::uDisplay;
+#[cfg(not(feature = "panic-verbose"))]
 use ufmt::uwrite;
 
+#[cfg(feature = "panic-verbose")]
+mod verbose;
+
 pub(crate) static mut PANIC_UART_IS_INIT: bool = false;
 
+#[cfg(not(feature = "panic-verbose"))]
 pub(crate) struct PanicInfoWrapper<'a>(pub(crate) &'a PanicInfo<'a>);
 
+#[cfg(not(feature = "panic-verbose"))]
 impl uDisplay for PanicInfoWrapper<'_> {
     fn fmt<W>(&self, f: &mut crate::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
     where
@@ -33,37 +43,31 @@ impl uDisplay for PanicInfoWrapper<'_> {
 }
 
 #[cfg(feature = "panic-apb-uart0")]
-#[panic_handler]
-fn panic(info: &PanicInfo) -> ! {
+pub(crate) fn panic_serial() -> crate::apb_uart::ApbUart0 {
     if !unsafe { crate::ufmt_panic::PANIC_UART_IS_INIT } {
         // If UART is not already initialized, init with hale mary values
         crate::apb_uart::ApbUart0::init(30_000_000, 115_200);
     }
 
-    ufmt::uwrite!(
-        unsafe { crate::apb_uart::ApbUart0::instance() },
-        "{}",
-        PanicInfoWrapper(info)
-    )
-    .unwrap();
-
-    loop {}
+    unsafe { crate::apb_uart::ApbUart0::instance() }
 }
 
 #[cfg(feature = "panic-sysctrl-uart")]
-#[panic_handler]
-fn panic(info: &PanicInfo) -> ! {
+pub(crate) fn panic_serial(
+) -> crate::sysctrl::udma::UdmaUart<'static, crate::sysctrl::udma::Enabled> {
     let sysctrl = crate::pac::Sysctrl::ptr();
     let udma = unsafe { (*sysctrl).udma() };
 
-    let mut serial = if !unsafe { crate::ufmt_panic::PANIC_UART_IS_INIT } {
+    if !unsafe { crate::ufmt_panic::PANIC_UART_IS_INIT } {
         // If UART is not already initialized, init with hale mary values
         let (soc_freq, baud) = (30_000_000, 9600_u32);
         let clk_div: u16 = (soc_freq / baud) as u16;
 
         let udma = crate::sysctrl::udma::Udma(udma);
-        udma.split().uart.enable(|w| {
-            unsafe {
+        let uart = udma.split().uart;
+        // SAFETY: the application has panicked, so pads 0 and 1 are free for the UART
+        unsafe {
+            uart.enable_unchecked(|w| {
                 w
                     // Use this if using parity bit
                     .parity_ena()
@@ -79,12 +83,21 @@ fn panic(info: &PanicInfo) -> ! {
                     .bit(true)
                     .clkdiv()
                     .bits(clk_div)
-            }
-        })
+            })
+        }
     } else {
         unsafe { crate::sysctrl::udma::UdmaUart::steal(udma) }
-    };
+    }
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    let mut serial = panic_serial();
+
+    #[cfg(not(feature = "panic-verbose"))]
     ufmt::uwrite!(serial, "{}", PanicInfoWrapper(info)).unwrap();
+    #[cfg(feature = "panic-verbose")]
+    verbose::report_panic(&mut serial, info);
 
     loop {}
 }
@@ -0,0 +1,131 @@
+//! Verbose panic & trap reporter
+//!
+//! Prints the HART, the trap CSRs and a backtrace of return addresses, which
+//! can be symbolized on the host, e.g.:
+//!
+//! ```sh
+//! riscv64-unknown-elf-addr2line -fpe <elf> 0x80000a3c 0x80000b10
+//! ```
+//!
+//! The backtrace follows the frame pointer chain, so build with
+//! `RUSTFLAGS="-C force-frame-pointers=yes"`. Without frame pointers the
+//! backtrace is cut short or empty.
+//!
+//! Unlike the default reporter, this one prints formatted panic messages and
+//! thus depends on `core::fmt`.
+use core::{
+    fmt::{self, Write},
+    panic::PanicInfo,
+};
+
+use riscv::register::{mcause, mepc, mhartid, mtval};
+use ufmt::uWrite;
+
+/// Maximum number of frames printed
+const MAX_DEPTH: usize = 32;
+
+/// Adapts a [uWrite] into [fmt::Write]
+struct FmtWriter<'w, W: uWrite>(&'w mut W);
+
+impl<W: uWrite> Write for FmtWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+fn write_csrs(w: &mut impl Write) -> fmt::Result {
+    writeln!(w, "mepc:   {:#x}", mepc::read())?;
+    writeln!(w, "mcause: {:#x}", mcause::read().bits())?;
+    writeln!(w, "mtval:  {:#x}", mtval::read())
+}
+
+/// Returns the current frame pointer
+#[inline(always)]
+fn frame_pointer() -> usize {
+    let fp: usize;
+    unsafe { core::arch::asm!("mv {}, s0", out(reg) fp) };
+    fp
+}
+
+/// Prints the return addresses on the frame pointer chain starting at `fp`
+fn write_backtrace(w: &mut impl Write, mut fp: usize) -> fmt::Result {
+    // riscv-rt linker symbols; all HART stacks are carved out below
+    // `_stack_start`
+    extern "C" {
+        static _stack_start: u8;
+        static _max_hart_id: u8;
+        static _hart_stack_size: u8;
+    }
+    let (stack_top, stack_bottom) = unsafe {
+        let top = core::ptr::addr_of!(_stack_start) as usize;
+        let harts = core::ptr::addr_of!(_max_hart_id) as usize + 1;
+        let size = core::ptr::addr_of!(_hart_stack_size) as usize;
+        (top, top.saturating_sub(harts.saturating_mul(size)))
+    };
+    let word = core::mem::size_of::<usize>();
+
+    writeln!(w, "backtrace:")?;
+    for depth in 0..MAX_DEPTH {
+        // The frame record `{ prev fp, ra }` is just below the frame pointer
+        if fp % word != 0 || fp < stack_bottom + 2 * word || fp > stack_top {
+            break;
+        }
+        let (ra, prev) = unsafe {
+            (
+                core::ptr::read_volatile((fp - word) as *const usize),
+                core::ptr::read_volatile((fp - 2 * word) as *const usize),
+            )
+        };
+        if ra == 0 {
+            break;
+        }
+        writeln!(w, "  #{} {:#x}", depth, ra)?;
+        // The stack grows down, so the callers' frames are at strictly higher
+        // addresses. Anything else is a corrupt chain or its end.
+        if prev <= fp {
+            break;
+        }
+        fp = prev;
+    }
+    Ok(())
+}
+
+/// Prints the HART, location, message, trap CSRs and backtrace of a panic
+pub(crate) fn report_panic<W: uWrite>(w: &mut W, info: &PanicInfo) {
+    let w = &mut FmtWriter(w);
+    let _ = (|| {
+        write!(w, "panic on hart {}", mhartid::read())?;
+        if let Some(loc) = info.location() {
+            write!(w, " at ({}:{})", loc.file(), loc.line())?;
+        }
+        writeln!(w, "\n{}", info.message())?;
+        write_csrs(w)?;
+        write_backtrace(w, frame_pointer())
+    })();
+}
+
+/// Prints the HART, trap CSRs and backtrace of an exception, e.g., an illegal
+/// instruction or an access fault
+#[cfg(feature = "rt")]
+fn report_exception<W: uWrite>(w: &mut W, trap_frame: &riscv_rt::TrapFrame) {
+    let w = &mut FmtWriter(w);
+    let _ = (|| {
+        writeln!(w, "exception on hart {}", mhartid::read())?;
+        write_csrs(w)?;
+        writeln!(w, "ra:     {:#x}", trap_frame.ra)?;
+        // The trap entry doesn't touch `s0`, so the chain continues into the
+        // interrupted code
+        write_backtrace(w, frame_pointer())
+    })();
+}
+
+/// Reports exceptions and halts
+///
+/// Replaces the default riscv-rt exception handler, which loops silently.
+#[cfg(feature = "rt")]
+#[export_name = "ExceptionHandler"]
+fn exception_handler(trap_frame: &riscv_rt::TrapFrame) -> ! {
+    let mut serial = super::panic_serial();
+    report_exception(&mut serial, trap_frame);
+    loop {}
+}
@@ -0,0 +1,11 @@
+/* Tests registered with `#[headsail_test]`, sa. `headsail_bsp::tb` */
+SECTIONS
+{
+  .headsail_tests : ALIGN(4)
+  {
+    __headsail_tests_start = .;
+    KEEP(*(.headsail_tests));
+    __headsail_tests_end = .;
+  } > REGION_RODATA
+}
+INSERT AFTER .rodata;
@@ -1,3 +1,4 @@
+#include <stdbool.h>
 #include <stdint.h>
 #include <string.h>
 #include <stdlib.h>
@@ -5,20 +6,73 @@
 
 #include "dla_driver.h"
 
+// Heap for the DLA driver, taken from newlib so the two allocators don't overlap
+#define DLA_ARENA_SIZE (64 * 1024)
+
 int main()
 {
     printf("DLA FFI Test start!\n");
-    dla_init();
+    void* arena = malloc(DLA_ARENA_SIZE);
+    if (!arena) {
+        printf("Failed to allocate DLA arena\n");
+        return 1;
+    }
+    if (dla_init(arena, DLA_ARENA_SIZE) != DlaStatus_Ok) {
+        printf("dla_init failed: %s\n", dla_last_error_message());
+        return 1;
+    }
     int8_t A[9] = {1,2,3,4,5,6,7,8,9};
     int8_t B[4] = {1,2,3,4};
-    int16_t bias[1] = {20};
     char * input_order = "CHW";
     char * kernel_order = "KCHW";
     int8_t* C = malloc(4);
-    dla_conv2d_relu(A, 1, 3, 3, input_order, B, 1, 1, 2, 2, kernel_order, 0, 0, 0, 0, 0, 1, 1, 0 ,0, C);
-    for(int i = 0; i < 4; ++i) {
-        printf("%d ", C[i]);
+    DlaStatus status = dla_conv2d_relu(A, 9, B, 4, C, 4,
+                                       1, 3, 3, input_order,
+                                       1, 1, 2, 2, kernel_order,
+                                       0, 0, 0, 0, 0, 1, 1, 0, 0);
+    if (status != DlaStatus_Ok) {
+        printf("dla_conv2d_relu failed (%d): %s\n", status, dla_last_error_message());
+    } else {
+        for(int i = 0; i < 4; ++i) {
+            printf("%d ", C[i]);
+        }
+    }
+
+    // Depthwise, each kernel has one channel and sees its own input channel
+    int8_t E[18] = {1,2,3,4,5,6,7,8,9, 9,8,7,6,5,4,3,2,1};
+    int8_t F[8] = {1,2,3,4, 1,0,0,1};
+    int32_t bias[2] = {0, 0};
+    int8_t G[8] = {0};
+    status = dla_depthwise_conv2d_bias(E, 18, F, 8, bias, 2, G, 8,
+                                       2, 3, 3, input_order,
+                                       2, 1, 2, 2, kernel_order,
+                                       0, 0, 0, 0, 0, 1, 1, 0, 0, 0);
+    if (status != DlaStatus_Ok) {
+        printf("dla_depthwise_conv2d_bias failed (%d): %s\n", status, dla_last_error_message());
+    } else {
+        for(int i = 0; i < 8; ++i) {
+            printf("%d ", G[i]);
+        }
+    }
+
+    // Same layer with handles, kernels are packed once and can be reused
+    DlaTensor *input, *kernels, *output;
+    int8_t D[4] = {0};
+    dla_tensor_from_ptr(A, 9, 1, 3, 3, "HWC", &input);
+    dla_kernels_create(B, 4, 1, 1, 2, 2, kernel_order, &kernels);
+    dla_tensor_from_ptr(D, 4, 1, 2, 2, "HWC", &output);
+    status = dla_tensor_conv2d(input, kernels, NULL, 0, true, output,
+                               0, 0, 0, 0, 0, 1, 1, 0, 0, 0);
+    if (status != DlaStatus_Ok) {
+        printf("dla_tensor_conv2d failed (%d): %s\n", status, dla_last_error_message());
+    } else {
+        for(int i = 0; i < 4; ++i) {
+            printf("%d ", D[i]);
+        }
     }
+    dla_tensor_destroy(output);
+    dla_tensor_destroy(kernels);
+    dla_tensor_destroy(input);
     free(C);
     printf("Done!\n");
     for(;;){}
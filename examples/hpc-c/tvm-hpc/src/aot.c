@@ -1,5 +1,6 @@
 #include <stdio.h>
 #include <assert.h>
+#include <stdbool.h>
 #include <float.h>
 #include <stdlib.h>
 #include <stdint.h>
@@ -56,8 +57,22 @@ void write_prediction(int8_t* prediction, size_t len) {
     printf("\n");
 }
 
-void init_tvm() {
-    dla_init();
+// Heap for the DLA driver, taken from newlib so the two allocators don't overlap
+#define DLA_ARENA_SIZE (16 * 1024 * 1024)
+
+// Returns false if the DLA driver couldn't be initialized
+bool init_tvm() {
+    void* arena = malloc(DLA_ARENA_SIZE);
+    if (!arena) {
+        printf("Failed to allocate DLA arena\n");
+        return false;
+    }
+    DlaStatus status = dla_init(arena, DLA_ARENA_SIZE);
+    if (status != DlaStatus_Ok) {
+        printf("dla_init failed: %s\n", dla_last_error_message());
+        return false;
+    }
+    return true;
 }
 
 void run_inference() {
@@ -98,7 +113,9 @@ void run_inference() {
 int main(void)
 {
     printf("Program started!\n");
-    init_tvm();
+    if (!init_tvm()) {
+        return 1;
+    }
 
     for(;;) {
         run_inference();
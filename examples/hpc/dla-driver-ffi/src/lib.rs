@@ -1,89 +1,447 @@
 //! # DLA driver FFI
 //!
 //! Makes DLA's highlevel API availeable from C via FFI.
+//!
+//! Every `dla_*` function returns a [DlaStatus]. Buffers are passed as a
+//! pointer followed by their length in elements, which is checked against the
+//! given dimensions before anything is read.
 #![no_std]
 #![no_main]
 
 extern crate alloc;
+
+mod status;
+mod tensor;
+
 use alloc::vec::Vec;
 use core::ffi::{c_char, CStr};
 use core::slice;
-use dla_driver::layers::{conv2d, conv2d_bias, conv2d_bias_relu, conv2d_relu, grouped_conv2d};
+use dla_driver::layers::{
+    self, conv2d, conv2d_batch_view, conv2d_bias, conv2d_bias_i32, conv2d_bias_relu, conv2d_relu,
+    dense, grouped_conv2d, relu,
+};
+use dla_driver::ops::QuantParams;
 use dla_driver::tensor3::{Order3, Tensor3};
-use dla_driver::tensor4::{Order4, Tensor4};
-use dla_driver::utils::optimal_pp_bias_heuristic;
+use dla_driver::tensor4::{Order4, Tensor4, Tensor4View};
+use dla_driver::utils::{fit_bias_i32, optimal_pp_bias_heuristic};
 use dla_driver::{Padding, Stride};
-use headsail_bsp::init_heap;
+use headsail_bsp::{init_heap, init_heap_at, is_heap_initialized};
+pub use status::{dla_last_error_message, DlaStatus};
+use status::{report, run, FfiError, FfiResult};
+pub use tensor::DlaTensor;
+
+/// Largest clip amounts supported by the DLA
+const MAX_MAC_CLIP: u32 = 21;
+const MAX_PP_CLIP: u32 = 0x1F;
+
+/// `pp_clip` of `dla_tvm_qnn_conv2d_bias`, which keeps the upper bits of
+/// DLA's 16-bit sum. The output is shifted back into the scale of the
+/// accumulator afterwards.
+const TVM_CONV2D_PP_CLIP: u32 = 7;
+
+/// Returns `ptr` as a slice of `len` elements
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` elements.
+unsafe fn ffi_slice<'a, T>(ptr: *const T, len: usize) -> FfiResult<&'a [T]> {
+    if ptr.is_null() {
+        return Err(FfiError::new(
+            DlaStatus::NullPointer,
+            c"buffer pointer is null",
+        ));
+    }
+    Ok(unsafe { slice::from_raw_parts(ptr, len) })
+}
+
+/// Parses a NUL-terminated layout string
+///
+/// # Safety
+///
+/// `order` must be null or point to a NUL-terminated string.
+unsafe fn ffi_str<'a>(order: *const c_char) -> FfiResult<&'a str> {
+    if order.is_null() {
+        return Err(FfiError::new(
+            DlaStatus::NullPointer,
+            c"layout string is null",
+        ));
+    }
+    unsafe { CStr::from_ptr(order) }.to_str().map_err(|_| {
+        FfiError::new(
+            DlaStatus::InvalidLayout,
+            c"layout string is not valid UTF-8",
+        )
+    })
+}
+
+/// Parses a 3D layout string, e.g., "HWC"
+///
+/// # Safety
+///
+/// `order` must be null or point to a NUL-terminated string.
+unsafe fn ffi_order3(order: *const c_char) -> FfiResult<Order3> {
+    Order3::try_from(unsafe { ffi_str(order) }?).map_err(|_| {
+        FfiError::new(
+            DlaStatus::InvalidLayout,
+            c"input layout is not a permutation of \"CHW\"",
+        )
+    })
+}
+
+/// Parses a 4D layout string, e.g., "HWKC"
+///
+/// # Safety
+///
+/// `order` must be null or point to a NUL-terminated string.
+unsafe fn ffi_order4(order: *const c_char) -> FfiResult<Order4> {
+    Order4::try_from(unsafe { ffi_str(order) }?).map_err(|_| {
+        FfiError::new(
+            DlaStatus::InvalidLayout,
+            c"kernel layout is not a permutation of \"KCHW\"",
+        )
+    })
+}
+
+/// Parses a 4D activation layout string with a batch axis, e.g., "NHWC"
+///
+/// The batch axis is stored as the kernel axis of [Order4].
+///
+/// # Safety
+///
+/// `order` must be null or point to a NUL-terminated string.
+unsafe fn ffi_batch_order(order: *const c_char) -> FfiResult<Order4> {
+    let order = unsafe { ffi_str(order) }?;
+    let mut buf = [0u8; 4];
+    if order.len() != buf.len() || order.contains('K') {
+        return Err(FfiError::new(
+            DlaStatus::InvalidLayout,
+            c"batch layout is not a permutation of \"NCHW\"",
+        ));
+    }
+    for (b, c) in buf.iter_mut().zip(order.bytes()) {
+        *b = if c == b'N' { b'K' } else { c };
+    }
+    core::str::from_utf8(&buf)
+        .ok()
+        .and_then(|s| Order4::try_from(s).ok())
+        .ok_or(FfiError::new(
+            DlaStatus::InvalidLayout,
+            c"batch layout is not a permutation of \"NCHW\"",
+        ))
+}
+
+/// Checks that a buffer of `len` elements holds exactly `dims`
+fn check_size(dims: &[usize], len: usize, msg: &'static CStr) -> FfiResult<()> {
+    if dims.contains(&0) {
+        return Err(FfiError::new(
+            DlaStatus::InvalidArgument,
+            c"tensor dimensions must be non-zero",
+        ));
+    }
+    let size = dims.iter().try_fold(1usize, |acc, &d| acc.checked_mul(d));
+    if size != Some(len) {
+        return Err(FfiError::new(DlaStatus::SizeMismatch, msg));
+    }
+    Ok(())
+}
+
+/// Checks convolution parameters that the DLA can't handle
+///
+/// `input` and `kernel` are given as (channels, height, width).
+fn check_conv_params(
+    input: (usize, usize, usize),
+    kernel: (usize, usize, usize),
+    padding: &Padding,
+    stride: &Stride,
+    mac_clip: u32,
+    pp_clip: u32,
+) -> FfiResult<()> {
+    if stride.x == 0 || stride.y == 0 {
+        return Err(FfiError::new(
+            DlaStatus::InvalidArgument,
+            c"stride must be non-zero",
+        ));
+    }
+    if kernel.0 != input.0 {
+        return Err(FfiError::new(
+            DlaStatus::SizeMismatch,
+            c"kernel channels don't match input channels",
+        ));
+    }
+    let padded_height = input.1 + (padding.top + padding.bottom) as usize;
+    let padded_width = input.2 + (padding.left + padding.right) as usize;
+    if kernel.1 > padded_height || kernel.2 > padded_width {
+        return Err(FfiError::new(
+            DlaStatus::InvalidArgument,
+            c"kernel is larger than the padded input",
+        ));
+    }
+    if mac_clip > MAX_MAC_CLIP || pp_clip > MAX_PP_CLIP {
+        return Err(FfiError::new(
+            DlaStatus::InvalidArgument,
+            c"mac_clip must be at most 21 and pp_clip at most 31",
+        ));
+    }
+    Ok(())
+}
+
+/// Converts the `bias_addr` of the exports, where 0 places bias after the
+/// output banks
+fn ffi_bias_addr(bias_addr: u32) -> Option<u32> {
+    (bias_addr != 0).then_some(bias_addr)
+}
+
+/// Writes the largest bias scaling error to `out` unless it's null
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes of one element.
+unsafe fn ffi_export_max_bias_error(max_error: u32, out: *mut u32) {
+    if !out.is_null() {
+        unsafe { out.write(max_error) };
+    }
+}
+
+/// Converts the error of a layer with bias, which only fails if there's no
+/// `bias_addr` and no memory bank left for the bias
+fn ffi_layer_error(_: &'static str) -> FfiError {
+    FfiError::new(
+        DlaStatus::OutOfMemoryBanks,
+        c"no memory bank left for bias, pass bias_addr",
+    )
+}
+
+/// Converts a C buffer to a DLA Tensor3
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` elements and `order` must be null
+/// or point to a NUL-terminated string.
+unsafe fn ffi_tensor3_import(
+    data: *const i8,
+    len: usize,
+    channels: usize,
+    height: usize,
+    width: usize,
+    order: *const c_char,
+) -> FfiResult<Tensor3<i8>> {
+    check_size(
+        &[channels, height, width],
+        len,
+        c"input dimensions don't match input_len",
+    )?;
+    let order = unsafe { ffi_order3(order) }?;
+    let data: Vec<i8> = unsafe { ffi_slice(data, len) }?.to_vec();
+    Tensor3::from_data_buffer(channels, height, width, data, order).map_err(|_| {
+        FfiError::new(
+            DlaStatus::SizeMismatch,
+            c"input dimensions don't match input_len",
+        )
+    })
+}
 
 /// Converts C-types to DLA Tensors for use with the highlevel layer
+///
+/// # Safety
+///
+/// The data pointers must be valid for reads of their given lengths and the
+/// layout pointers must be null or point to NUL-terminated strings.
 #[allow(clippy::too_many_arguments)]
 unsafe fn ffi_data_import(
     input_data: *const i8,
+    input_len: usize,
     input_channels: usize,
     input_height: usize,
     input_width: usize,
     input_order: *const c_char,
     kernel_data: *const i8,
+    kernel_len: usize,
     kernel_amount: usize,
     kernel_channels: usize,
     kernel_height: usize,
     kernel_width: usize,
     kernel_order: *const c_char,
-) -> (Tensor3<i8>, Tensor4<i8>) {
-    let input_data: Vec<i8> = unsafe {
-        slice::from_raw_parts(input_data, input_channels * input_height * input_width).to_vec()
-    };
-
-    let input_order_string = unsafe { CStr::from_ptr(input_order).to_str().unwrap_unchecked() };
+) -> FfiResult<(Tensor3<i8>, Tensor4<i8>)> {
     let input_tensor = unsafe {
-        Tensor3::from_data_buffer(
+        ffi_tensor3_import(
+            input_data,
+            input_len,
             input_channels,
             input_height,
             input_width,
-            input_data,
-            Order3::try_from(input_order_string).unwrap_unchecked(),
+            input_order,
         )
-        .unwrap_unchecked()
-    };
+    }?;
 
-    let kernels_data: Vec<i8> = unsafe {
-        slice::from_raw_parts(
-            kernel_data,
-            kernel_amount * kernel_channels * kernel_height * kernel_width,
+    check_size(
+        &[kernel_amount, kernel_channels, kernel_height, kernel_width],
+        kernel_len,
+        c"kernel dimensions don't match kernel_len",
+    )?;
+    let kernel_order = unsafe { ffi_order4(kernel_order) }?;
+    let kernels_data: Vec<i8> = unsafe { ffi_slice(kernel_data, kernel_len) }?.to_vec();
+    let kernels_tensor = Tensor4::from_data_buffer(
+        kernel_amount,
+        kernel_channels,
+        kernel_height,
+        kernel_width,
+        kernels_data,
+        kernel_order,
+    )
+    .map_err(|_| {
+        FfiError::new(
+            DlaStatus::SizeMismatch,
+            c"kernel dimensions don't match kernel_len",
         )
-        .to_vec()
-    };
+    })?;
 
-    let kernel_order_string = unsafe { CStr::from_ptr(kernel_order).to_str().unwrap_unchecked() };
-    let kernels_tensor = unsafe {
-        Tensor4::from_data_buffer(
-            kernel_amount,
-            kernel_channels,
-            kernel_height,
-            kernel_width,
-            kernels_data,
-            Order4::try_from(kernel_order_string).unwrap_unchecked(),
-        )
-        .unwrap_unchecked()
-    };
+    Ok((input_tensor, kernels_tensor))
+}
+
+/// Converts a C input vector and (units, input_len) weights for `layers::dense`
+///
+/// The input is mapped to channels of a 1x1 feature map, so the weights can be
+/// used as KCHW kernels as they are.
+///
+/// # Safety
+///
+/// The data pointers must be valid for reads of their given lengths.
+unsafe fn ffi_dense_import(
+    input_data: *const i8,
+    input_len: usize,
+    weight_data: *const i8,
+    weight_len: usize,
+    units: usize,
+) -> FfiResult<(Tensor3<i8>, Vec<i8>)> {
+    check_size(
+        &[units, input_len],
+        weight_len,
+        c"weight_len doesn't match units * input_len",
+    )?;
+    let data: Vec<i8> = unsafe { ffi_slice(input_data, input_len) }?.to_vec();
+    let input_tensor =
+        Tensor3::from_data_buffer(input_len, 1, 1, data, Order3::CHW).map_err(|_| {
+            FfiError::new(
+                DlaStatus::SizeMismatch,
+                c"input dimensions don't match input_len",
+            )
+        })?;
+    let weights = unsafe { ffi_slice(weight_data, weight_len) }?.to_vec();
+    Ok((input_tensor, weights))
+}
+
+/// Reads one 32-bit bias per kernel
+///
+/// # Safety
+///
+/// `bias` must be valid for reads of `bias_length` elements.
+unsafe fn ffi_bias_i32_import<'a>(
+    bias: *const i32,
+    bias_length: usize,
+    kernel_amount: usize,
+) -> FfiResult<&'a [i32]> {
+    if bias_length != kernel_amount {
+        return Err(FfiError::new(
+            DlaStatus::SizeMismatch,
+            c"bias_length doesn't match kernel_amount",
+        ));
+    }
+    unsafe { ffi_slice(bias, bias_length) }
+}
+
+/// Reads one 32-bit bias per kernel and checks that it fits in the 16 bits
+/// supported by DLA
+///
+/// # Safety
+///
+/// `bias` must be valid for reads of `bias_length` elements.
+unsafe fn ffi_bias_import(
+    bias: *const i32,
+    bias_length: usize,
+    kernel_amount: usize,
+) -> FfiResult<Vec<i16>> {
+    unsafe { ffi_bias_i32_import(bias, bias_length, kernel_amount) }?
+        .iter()
+        .map(|&x| i16::try_from(x))
+        .collect::<Result<_, _>>()
+        .map_err(|_| FfiError::new(DlaStatus::BiasOutOfRange, c"bias doesn't fit in 16 bits"))
+}
 
-    (input_tensor, kernels_tensor)
+/// Copies `data` into `output`
+///
+/// # Safety
+///
+/// `output` must be valid for writes of `output_len` elements.
+unsafe fn ffi_data_export<T: Copy>(data: &[T], output: *mut T, output_len: usize) -> FfiResult<()> {
+    if output.is_null() {
+        return Err(FfiError::new(
+            DlaStatus::NullPointer,
+            c"output pointer is null",
+        ));
+    }
+    if data.len() > output_len {
+        return Err(FfiError::new(
+            DlaStatus::OutputTooSmall,
+            c"output_len is smaller than the layer output",
+        ));
+    }
+    unsafe { core::ptr::copy_nonoverlapping(data.as_ptr(), output, data.len()) };
+    Ok(())
 }
 
-/// Initializes DLA by setting up necessary heap allocator from headsail-bsp. This should be called only once in the program.
+/// Alignment of the start of a caller provided arena
+const ARENA_ALIGN: usize = 16;
+
+/// Initializes DLA by setting up the heap allocator used by the driver.
+///
+/// Calling this again, or after the heap has been set up from Rust, has no
+/// effect and returns `DlaStatus_Ok`. All other `dla_*` functions return
+/// `DlaStatus_NotInitialized` until this has been called.
+///
+/// # Arguments
+///
+/// * `arena` - Memory for the driver's allocations, e.g., from `malloc`. If null, the default
+///   headsail-bsp heap region is used, which may collide with newlib's heap.
+/// * `arena_size` - Size of `arena` in bytes. Ignored if `arena` is null.
 #[no_mangle]
-pub unsafe extern "C" fn dla_init() {
-    // SAFETY: `init_heap` must be called once only
-    unsafe { init_heap() };
+pub unsafe extern "C" fn dla_init(arena: *mut u8, arena_size: usize) -> DlaStatus {
+    if is_heap_initialized() {
+        return DlaStatus::Ok;
+    }
+    if arena.is_null() {
+        // SAFETY: checked above that the heap is not initialized
+        unsafe { init_heap() };
+        return DlaStatus::Ok;
+    }
+
+    let start = arena as usize;
+    let aligned = start.next_multiple_of(ARENA_ALIGN);
+    let size = arena_size.saturating_sub(aligned - start);
+    if size == 0 {
+        return report(Err(FfiError::new(
+            DlaStatus::InvalidArgument,
+            c"arena is too small",
+        )));
+    }
+    // SAFETY: the caller hands the arena over to the driver
+    unsafe { init_heap_at(aligned, size) };
+    DlaStatus::Ok
 }
 
 /// Executes Conv2D on DLA with given parameters and writes result to output buffer.
+///
+/// # Arguments
+///
+/// * `input_len`, `kernel_len`, `output_len` - Lengths of the respective buffers in elements
 #[no_mangle]
 #[allow(clippy::too_many_arguments)]
 pub unsafe extern "C" fn dla_conv2d(
     input_data: *const i8,
+    input_len: usize,
     kernel_data: *const i8,
+    kernel_len: usize,
     output: *mut i8,
+    output_len: usize,
     input_channels: usize,
     input_height: usize,
     input_width: usize,
@@ -102,53 +460,72 @@ pub unsafe extern "C" fn dla_conv2d(
     stride_y: u32,
     mac_clip: u32,
     pp_clip: u32,
-) {
-    let (input_tensor, kernels_tensor) = unsafe {
-        ffi_data_import(
-            input_data,
-            input_channels,
-            input_height,
-            input_width,
-            input_order,
-            kernel_data,
-            kernel_amount,
-            kernel_channels,
-            kernel_height,
-            kernel_width,
-            kernel_order,
-        )
-    };
-
-    let result: Tensor3<i8> = conv2d(
-        input_tensor,
-        kernels_tensor,
-        Some(Padding {
+) -> DlaStatus {
+    run(|| {
+        let (input_tensor, kernels_tensor) = unsafe {
+            ffi_data_import(
+                input_data,
+                input_len,
+                input_channels,
+                input_height,
+                input_width,
+                input_order,
+                kernel_data,
+                kernel_len,
+                kernel_amount,
+                kernel_channels,
+                kernel_height,
+                kernel_width,
+                kernel_order,
+            )
+        }?;
+        let padding = Padding {
             top: pad_top,
             right: pad_right,
             left: pad_left,
             bottom: pad_bottom,
             padding_value: pad_value,
-        }),
-        Some(Stride {
+        };
+        let stride = Stride {
             x: stride_x,
             y: stride_y,
-        }),
-        Some(mac_clip),
-        Some(pp_clip),
-        None,
-    );
-    unsafe {
-        core::ptr::copy_nonoverlapping(result.to_buffer().as_mut_ptr(), output, result.get_size())
-    };
+        };
+        check_conv_params(
+            (input_channels, input_height, input_width),
+            (kernel_channels, kernel_height, kernel_width),
+            &padding,
+            &stride,
+            mac_clip,
+            pp_clip,
+        )?;
+
+        let result: Tensor3<i8> = conv2d(
+            input_tensor,
+            kernels_tensor,
+            Some(padding),
+            Some(stride),
+            Some(mac_clip),
+            Some(pp_clip),
+            None,
+        );
+        unsafe { ffi_data_export(&result.to_buffer(), output, output_len) }
+    })
 }
 
 /// Executes Conv2D + ReLU on DLA with given parameters and writes result to output buffer.
+///
+/// # Arguments
+///
+/// * `input_len`, `kernel_len`, `output_len` - Lengths of the respective buffers in elements
 #[no_mangle]
 #[allow(clippy::too_many_arguments)]
 pub unsafe extern "C" fn dla_conv2d_relu(
     input_data: *const i8,
+    input_len: usize,
     kernel_data: *const i8,
+    kernel_len: usize,
     output: *mut i8,
+    output_len: usize,
     input_channels: usize,
     input_height: usize,
     input_width: usize,
@@ -167,58 +544,77 @@ pub unsafe extern "C" fn dla_conv2d_relu(
     stride_y: u32,
     mac_clip: u32,
     pp_clip: u32,
-) {
-    let (input_tensor, kernels_tensor) = unsafe {
-        ffi_data_import(
-            input_data,
-            input_channels,
-            input_height,
-            input_width,
-            input_order,
-            kernel_data,
-            kernel_amount,
-            kernel_channels,
-            kernel_height,
-            kernel_width,
-            kernel_order,
-        )
-    };
-
-    let result: Tensor3<i8> = conv2d_relu(
-        input_tensor,
-        kernels_tensor,
-        Some(Padding {
+) -> DlaStatus {
+    run(|| {
+        let (input_tensor, kernels_tensor) = unsafe {
+            ffi_data_import(
+                input_data,
+                input_len,
+                input_channels,
+                input_height,
+                input_width,
+                input_order,
+                kernel_data,
+                kernel_len,
+                kernel_amount,
+                kernel_channels,
+                kernel_height,
+                kernel_width,
+                kernel_order,
+            )
+        }?;
+        let padding = Padding {
             top: pad_top,
             right: pad_right,
             left: pad_left,
             bottom: pad_bottom,
             padding_value: pad_value,
-        }),
-        Some(Stride {
+        };
+        let stride = Stride {
             x: stride_x,
             y: stride_y,
-        }),
-        Some(mac_clip),
-        Some(pp_clip),
-        None,
-    );
-    unsafe {
-        core::ptr::copy_nonoverlapping(result.to_buffer().as_mut_ptr(), output, result.get_size())
-    };
+        };
+        check_conv_params(
+            (input_channels, input_height, input_width),
+            (kernel_channels, kernel_height, kernel_width),
+            &padding,
+            &stride,
+            mac_clip,
+            pp_clip,
+        )?;
+
+        let result: Tensor3<i8> = conv2d_relu(
+            input_tensor,
+            kernels_tensor,
+            Some(padding),
+            Some(stride),
+            Some(mac_clip),
+            Some(pp_clip),
+            None,
+        );
+        unsafe { ffi_data_export(&result.to_buffer(), output, output_len) }
+    })
 }
 
 /// Executes Conv2D + Bias on DLA with given parameters and writes result to output buffer.
+///
 /// # Arguments
 ///
 /// * `bias` - Bias is actually i16 in hardware, here we use 32 for TVM compatibility
+/// * `bias_length` - Number of biases, one per kernel
+/// * `input_len`, `kernel_len`, `output_len` - Lengths of the respective buffers in elements
+/// * `bias_addr` - Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. 0 places it after the output banks.
 #[no_mangle]
 #[allow(clippy::too_many_arguments)]
 pub unsafe extern "C" fn dla_conv2d_bias(
     input_data: *const i8,
+    input_len: usize,
     kernel_data: *const i8,
-    // NOTE:
+    kernel_len: usize,
     bias: *const i32,
+    bias_length: usize,
     output: *mut i8,
+    output_len: usize,
     input_channels: usize,
     input_height: usize,
     input_width: usize,
@@ -228,7 +624,6 @@ pub unsafe extern "C" fn dla_conv2d_bias(
     kernel_height: usize,
     kernel_width: usize,
     kernel_order: *const c_char,
-    bias_length: usize,
     pad_top: u32,
     pad_right: u32,
     pad_left: u32,
@@ -238,61 +633,82 @@ pub unsafe extern "C" fn dla_conv2d_bias(
     stride_y: u32,
     mac_clip: u32,
     pp_clip: u32,
-) {
-    let (input_tensor, kernels_tensor) = unsafe {
-        ffi_data_import(
-            input_data,
-            input_channels,
-            input_height,
-            input_width,
-            input_order,
-            kernel_data,
-            kernel_amount,
-            kernel_channels,
-            kernel_height,
-            kernel_width,
-            kernel_order,
-        )
-    };
-
-    let bias: Vec<i16> = unsafe { slice::from_raw_parts(bias as *const i16, bias_length).to_vec() };
-
-    let result = conv2d_bias(
-        input_tensor,
-        kernels_tensor,
-        bias,
-        Some(Padding {
+    bias_addr: u32,
+) -> DlaStatus {
+    run(|| {
+        let (input_tensor, kernels_tensor) = unsafe {
+            ffi_data_import(
+                input_data,
+                input_len,
+                input_channels,
+                input_height,
+                input_width,
+                input_order,
+                kernel_data,
+                kernel_len,
+                kernel_amount,
+                kernel_channels,
+                kernel_height,
+                kernel_width,
+                kernel_order,
+            )
+        }?;
+        let bias = unsafe { ffi_bias_import(bias, bias_length, kernel_amount) }?;
+        let padding = Padding {
             top: pad_top,
             right: pad_right,
             left: pad_left,
             bottom: pad_bottom,
             padding_value: pad_value,
-        }),
-        Some(Stride {
+        };
+        let stride = Stride {
             x: stride_x,
             y: stride_y,
-        }),
-        Some(mac_clip),
-        Some(pp_clip),
-        None,
-    );
-    unsafe {
-        core::ptr::copy_nonoverlapping(result.to_buffer().as_mut_ptr(), output, result.get_size())
-    };
+        };
+        check_conv_params(
+            (input_channels, input_height, input_width),
+            (kernel_channels, kernel_height, kernel_width),
+            &padding,
+            &stride,
+            mac_clip,
+            pp_clip,
+        )?;
+
+        let result: Tensor3<i8> = conv2d_bias(
+            input_tensor,
+            kernels_tensor,
+            bias,
+            ffi_bias_addr(bias_addr),
+            Some(padding),
+            Some(stride),
+            Some(mac_clip),
+            Some(pp_clip),
+            None,
+        )
+        .map_err(ffi_layer_error)?;
+        unsafe { ffi_data_export(&result.to_buffer(), output, output_len) }
+    })
 }
 
 /// Executes Conv2D + Bias + ReLU on DLA with given parameters and writes result to output buffer.
 ///
 /// # Arguments
 ///
-/// * `bias` - Buffer containing bias data. NOTE: Bias is actually i16 in hardware, here we use 32 for TVM compatibility
+/// * `bias` - Buffer containing bias data. NOTE: Bias is actually i16 in hardware, here we use 32 for TVM compatibility. Must fit in i16.
+/// * `bias_length` - Number of biases, one per kernel
+/// * `input_len`, `kernel_len`, `output_len` - Lengths of the respective buffers in elements
+/// * `bias_addr` - Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. 0 places it after the output banks.
 #[no_mangle]
 #[allow(clippy::too_many_arguments)]
 pub unsafe extern "C" fn dla_conv2d_bias_relu(
     input_data: *const i8,
+    input_len: usize,
     kernel_data: *const i8,
+    kernel_len: usize,
     bias: *const i32,
+    bias_length: usize,
     output: *mut i8,
+    output_len: usize,
     input_channels: usize,
     input_height: usize,
     input_width: usize,
@@ -302,7 +718,6 @@ pub unsafe extern "C" fn dla_conv2d_bias_relu(
     kernel_height: usize,
     kernel_width: usize,
     kernel_order: *const c_char,
-    bias_length: usize,
     pad_top: u32,
     pad_right: u32,
     pad_left: u32,
@@ -312,72 +727,88 @@ pub unsafe extern "C" fn dla_conv2d_bias_relu(
     stride_y: u32,
     mac_clip: u32,
     pp_clip: u32,
-) {
-    let (input_tensor, kernels_tensor) = unsafe {
-        ffi_data_import(
-            input_data,
-            input_channels,
-            input_height,
-            input_width,
-            input_order,
-            kernel_data,
-            kernel_amount,
-            kernel_channels,
-            kernel_height,
-            kernel_width,
-            kernel_order,
-        )
-    };
-
-    let bias: Vec<i16> = unsafe {
-        slice::from_raw_parts(bias as *const i32, bias_length)
-            .into_iter()
-            .map(|x| (*x).clamp(i16::MIN as i32, i16::MAX as i32) as i16)
-            .collect()
-    };
-
-    let result = conv2d_bias_relu(
-        input_tensor,
-        kernels_tensor,
-        bias,
-        Some(Padding {
+    bias_addr: u32,
+) -> DlaStatus {
+    run(|| {
+        let output_order = unsafe { ffi_order3(input_order) }?;
+        let (input_tensor, kernels_tensor) = unsafe {
+            ffi_data_import(
+                input_data,
+                input_len,
+                input_channels,
+                input_height,
+                input_width,
+                input_order,
+                kernel_data,
+                kernel_len,
+                kernel_amount,
+                kernel_channels,
+                kernel_height,
+                kernel_width,
+                kernel_order,
+            )
+        }?;
+        let bias = unsafe { ffi_bias_import(bias, bias_length, kernel_amount) }?;
+        let padding = Padding {
             top: pad_top,
             right: pad_right,
             left: pad_left,
             bottom: pad_bottom,
             padding_value: pad_value,
-        }),
-        Some(Stride {
+        };
+        let stride = Stride {
             x: stride_x,
             y: stride_y,
-        }),
-        Some(mac_clip),
-        Some(pp_clip),
-        None,
-    );
+        };
+        check_conv_params(
+            (input_channels, input_height, input_width),
+            (kernel_channels, kernel_height, kernel_width),
+            &padding,
+            &stride,
+            mac_clip,
+            pp_clip,
+        )?;
 
-    let input_order_string = unsafe { CStr::from_ptr(input_order).to_str().unwrap_unchecked() };
-    unsafe {
-        core::ptr::copy_nonoverlapping(
-            result
-                .to_buffer_with_order(Order3::try_from(input_order_string).unwrap_unchecked())
-                .as_mut_ptr(),
-            output,
-            result.get_size(),
+        let result: Tensor3<i8> = conv2d_bias_relu(
+            input_tensor,
+            kernels_tensor,
+            bias,
+            ffi_bias_addr(bias_addr),
+            Some(padding),
+            Some(stride),
+            Some(mac_clip),
+            Some(pp_clip),
+            None,
         )
-    };
+        .map_err(ffi_layer_error)?;
+        unsafe {
+            ffi_data_export(
+                &result.to_buffer_with_order(output_order),
+                output,
+                output_len,
+            )
+        }
+    })
 }
 
 /// # Arguments
 ///
-/// * `bias` - Buffer containing bias data. NOTE: Bias is actually i16 in hardware, here we use 32 for TVM compatibility
+/// * `bias` - Buffer containing 32-bit bias data in the scale of the accumulator. Scaled into DLA's 16 bits together with the accumulator instead of being clipped.
+/// * `bias_length` - Number of biases, one per kernel
+/// * `input_len`, `kernel_len`, `output_len` - Lengths of the respective buffers in elements
+/// * `max_bias_error` - If not null, receives the largest rounding error of a single bias from scaling it into 16 bits, in units of the 32-bit bias
+/// * `bias_addr` - Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. 0 places it after the output banks.
 #[no_mangle]
 #[allow(clippy::too_many_arguments)]
 pub unsafe extern "C" fn dla_tvm_qnn_conv2d_bias(
     input_data: *const i8,
+    input_len: usize,
     kernel_data: *const i8,
+    kernel_len: usize,
     bias: *const i32,
+    bias_length: usize,
     output: *mut i32,
+    output_len: usize,
     input_channels: usize,
     input_height: usize,
     input_width: usize,
@@ -387,7 +818,6 @@ pub unsafe extern "C" fn dla_tvm_qnn_conv2d_bias(
     kernel_height: usize,
     kernel_width: usize,
     kernel_order: *const c_char,
-    bias_length: usize,
     pad_top: u32,
     pad_right: u32,
     pad_left: u32,
@@ -397,80 +827,106 @@ pub unsafe extern "C" fn dla_tvm_qnn_conv2d_bias(
     stride_y: u32,
     mac_clip: u32,
     _pp_clip: u32,
-) {
-    let (input_tensor, kernels_tensor) = unsafe {
-        ffi_data_import(
-            input_data,
-            input_channels,
-            input_height,
-            input_width,
-            input_order,
-            kernel_data,
-            kernel_amount,
-            kernel_channels,
-            kernel_height,
-            kernel_width,
-            kernel_order,
-        )
-    };
-
-    // NOTE:(20241025 vaino-waltteri.granat@tuni.fi) TVM expects 32-bit bias, but DLA only support 16-bit bias, so we clip the incoming bias
-    // to range suitable for DLA
-    let bias: Vec<i16> = unsafe {
-        slice::from_raw_parts(bias as *const i32, bias_length)
-            .into_iter()
-            .map(|x| (*x).clamp(i16::MIN as i32, i16::MAX as i32) as i16)
-            .collect()
-    };
-
-    //let optimized_pp = optimal_pp_bias_heuristic(&bias);
-    let optimized_pp = 7;
-
-    let result: Tensor3<i8> = conv2d_bias(
-        input_tensor,
-        kernels_tensor,
-        bias,
-        Some(Padding {
+    bias_addr: u32,
+    max_bias_error: *mut u32,
+) -> DlaStatus {
+    run(|| {
+        let (input_tensor, kernels_tensor) = unsafe {
+            ffi_data_import(
+                input_data,
+                input_len,
+                input_channels,
+                input_height,
+                input_width,
+                input_order,
+                kernel_data,
+                kernel_len,
+                kernel_amount,
+                kernel_channels,
+                kernel_height,
+                kernel_width,
+                kernel_order,
+            )
+        }?;
+
+        // TVM expects 32-bit bias, but DLA only supports 16-bit bias, so the bias is scaled down
+        // together with the accumulator
+        let bias = unsafe { ffi_bias_i32_import(bias, bias_length, kernel_amount) }?;
+
+        let pp_clip = TVM_CONV2D_PP_CLIP;
+
+        let padding = Padding {
             top: pad_top,
             right: pad_right,
             left: pad_left,
             bottom: pad_bottom,
             padding_value: 0,
-        }),
-        Some(Stride {
+        };
+        let stride = Stride {
             x: stride_x,
             y: stride_y,
-        }),
-        Some(mac_clip),
-        Some(optimized_pp),
-        None,
-    );
-
-    // TVM requantization and clip
-    // NOTE:(20240927 vaino-waltteri.granat@tuni.fi) on DLA clipping behaviour with TVM.
-    // DLA's conv2d arithmetic is done at 16 bit width, but the output of the DLA is limited to 8 bits.
-    // To comply with TVM's expected value range our solution is to bit shift/clip the 16-bit result of
-    // conv2d by 8 bits and shift if back in the driver. This causes some amount of data loss due to
-    // the lost granularity of the values. The clipping amount is set by the pp_clip argument.
-    let mut res_i32: Vec<i32> = result
-        .to_buffer()
-        .iter()
-        .map(|x: &i8| ((*x as i32) << optimized_pp))
-        .collect();
+        };
+        check_conv_params(
+            (input_channels, input_height, input_width),
+            (kernel_channels, kernel_height, kernel_width),
+            &padding,
+            &stride,
+            mac_clip,
+            pp_clip,
+        )?;
+
+        let (result, scaled_bias): (Tensor3<i8>, _) = conv2d_bias_i32(
+            input_tensor,
+            kernels_tensor,
+            bias,
+            ffi_bias_addr(bias_addr),
+            false,
+            Some(padding),
+            Some(stride),
+            Some(mac_clip),
+            Some(pp_clip),
+            None,
+        )
+        .map_err(ffi_layer_error)?;
+        unsafe { ffi_export_max_bias_error(scaled_bias.max_error, max_bias_error) };
+        // Return to the scale of `mac_clip`
+        let output_shift = scaled_bias.shift - mac_clip + pp_clip;
 
-    unsafe { core::ptr::copy_nonoverlapping(res_i32.as_mut_ptr(), output, result.get_size()) };
+        // TVM requantization and clip
+        // NOTE:(20240927 vaino-waltteri.granat@tuni.fi) on DLA clipping behaviour with TVM.
+        // DLA's conv2d arithmetic is done at 16 bit width, but the output of the DLA is limited to 8 bits.
+        // To comply with TVM's expected value range our solution is to bit shift/clip the 16-bit result of
+        // conv2d by 8 bits and shift if back in the driver. This causes some amount of data loss due to
+        // the lost granularity of the values. The clipping amount is set by the pp_clip argument.
+        let res_i32: Vec<i32> = result
+            .to_buffer()
+            .iter()
+            .map(|x: &i8| ((*x as i32) << output_shift))
+            .collect();
+
+        unsafe { ffi_data_export(&res_i32, output, output_len) }
+    })
 }
 
 /// # Arguments
 ///
-/// * `bias` - Buffer containing bias data. NOTE: Bias is actually i16 in hardware, here we use 32 for TVM compatibility
+/// * `bias` - Buffer containing 32-bit bias data in the scale of the accumulator. Scaled into DLA's 16 bits together with the accumulator instead of being clipped.
+/// * `bias_length` - Number of biases, one per kernel
+/// * `input_len`, `kernel_len`, `output_len` - Lengths of the respective buffers in elements
+/// * `max_bias_error` - If not null, receives the largest rounding error of a single bias from scaling it into 16 bits, in units of the 32-bit bias
+/// * `groups` - Number of groups, must divide both `input_channels` and `kernel_amount`
+/// * `bias_addr` - Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. 0 places it after the output banks.
 #[no_mangle]
 #[allow(clippy::too_many_arguments)]
 pub unsafe extern "C" fn dla_tvm_qnn_conv2d_grouped_bias(
     input_data: *const i8,
+    input_len: usize,
     kernel_data: *const i8,
+    kernel_len: usize,
     bias: *const i32,
+    bias_length: usize,
     output: *mut i32,
+    output_len: usize,
     input_channels: usize,
     input_height: usize,
     input_width: usize,
@@ -481,7 +937,6 @@ pub unsafe extern "C" fn dla_tvm_qnn_conv2d_grouped_bias(
     kernel_width: usize,
     kernel_order: *const c_char,
     groups: usize,
-    bias_length: usize,
     pad_top: u32,
     pad_right: u32,
     pad_left: u32,
@@ -491,66 +946,671 @@ pub unsafe extern "C" fn dla_tvm_qnn_conv2d_grouped_bias(
     stride_y: u32,
     mac_clip: u32,
     _pp_clip: u32,
-) {
-    let (input_tensor, kernels_tensor) = unsafe {
-        ffi_data_import(
+    bias_addr: u32,
+    max_bias_error: *mut u32,
+) -> DlaStatus {
+    run(|| {
+        if groups == 0 || input_channels % groups != 0 || kernel_amount % groups != 0 {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"groups must divide input_channels and kernel_amount",
+            ));
+        }
+        let (input_tensor, kernels_tensor) = unsafe {
+            ffi_data_import(
+                input_data,
+                input_len,
+                input_channels,
+                input_height,
+                input_width,
+                input_order,
+                kernel_data,
+                kernel_len,
+                kernel_amount,
+                kernel_channels,
+                kernel_height,
+                kernel_width,
+                kernel_order,
+            )
+        }?;
+
+        // TVM expects 32-bit bias, but DLA only supports 16-bit bias, so the bias is scaled down
+        // together with the accumulator
+        let bias = unsafe { ffi_bias_i32_import(bias, bias_length, kernel_amount) }?;
+
+        let padding = Padding {
+            top: pad_top,
+            right: pad_right,
+            left: pad_left,
+            bottom: pad_bottom,
+            padding_value: 0,
+        };
+        let stride = Stride {
+            x: stride_x,
+            y: stride_y,
+        };
+        // Each group of kernels sees `input_channels / groups` channels. `pp_clip` is picked
+        // below from the scaled bias.
+        check_conv_params(
+            (input_channels, input_height, input_width),
+            (
+                kernel_channels.saturating_mul(groups),
+                kernel_height,
+                kernel_width,
+            ),
+            &padding,
+            &stride,
+            mac_clip,
+            0,
+        )?;
+        let scaled_bias = fit_bias_i32(bias, mac_clip);
+        let optimized_pp = optimal_pp_bias_heuristic(&scaled_bias.bias);
+        unsafe { ffi_export_max_bias_error(scaled_bias.max_error, max_bias_error) };
+
+        let result: Tensor3<i8> = grouped_conv2d(
+            input_tensor,
+            kernels_tensor,
+            scaled_bias.bias,
+            ffi_bias_addr(bias_addr),
+            Some(padding),
+            Some(stride),
+            Some(scaled_bias.shift),
+            Some(optimized_pp),
+            None,
+            groups,
+        )
+        .map_err(ffi_layer_error)?;
+        // Return to the scale of `mac_clip`
+        let output_shift = scaled_bias.shift - mac_clip + optimized_pp;
+
+        // TVM requantization and clip
+        // NOTE:(20240927 vaino-waltteri.granat@tuni.fi) on DLA clipping behaviour with TVM.
+        // DLA's conv2d arithmetic is done at 16 bit width, but the output of the DLA is limited to 8 bits.
+        // To comply with TVM's expected value range our solution is to bit shift/clip the 16-bit result of
+        // conv2d by 8 bits and shift if back in the driver. This causes some amount of data loss due to
+        // the lost granularity of the values. The clipping amount is set by the pp_clip argument.
+        let res_i32: Vec<i32> = result
+            .to_buffer()
+            .iter()
+            .map(|x: &i8| ((*x as i32) << output_shift))
+            .collect();
+
+        unsafe { ffi_data_export(&res_i32, output, output_len) }
+    })
+}
+
+/// Executes Conv2D + Bias on DLA in `groups` groups and writes result to output buffer.
+///
+/// # Arguments
+///
+/// * `bias` - Buffer containing bias data. NOTE: Bias is actually i16 in hardware, here we use 32 for TVM compatibility. Must fit in i16.
+/// * `bias_length` - Number of biases, one per kernel
+/// * `input_len`, `kernel_len`, `output_len` - Lengths of the respective buffers in elements
+/// * `groups` - Number of groups, must divide both `input_channels` and `kernel_amount`
+/// * `kernel_channels` - Channels of each kernel, i.e., `input_channels / groups`
+/// * `bias_addr` - Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. 0 places it after the output banks.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dla_grouped_conv2d_bias(
+    input_data: *const i8,
+    input_len: usize,
+    kernel_data: *const i8,
+    kernel_len: usize,
+    bias: *const i32,
+    bias_length: usize,
+    output: *mut i8,
+    output_len: usize,
+    input_channels: usize,
+    input_height: usize,
+    input_width: usize,
+    input_order: *const c_char,
+    kernel_amount: usize,
+    kernel_channels: usize,
+    kernel_height: usize,
+    kernel_width: usize,
+    kernel_order: *const c_char,
+    groups: usize,
+    pad_top: u32,
+    pad_right: u32,
+    pad_left: u32,
+    pad_bottom: u32,
+    pad_value: i32,
+    stride_x: u32,
+    stride_y: u32,
+    mac_clip: u32,
+    pp_clip: u32,
+    bias_addr: u32,
+) -> DlaStatus {
+    run(|| {
+        if groups == 0 || input_channels % groups != 0 || kernel_amount % groups != 0 {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"groups must divide input_channels and kernel_amount",
+            ));
+        }
+        let (input_tensor, kernels_tensor) = unsafe {
+            ffi_data_import(
+                input_data,
+                input_len,
+                input_channels,
+                input_height,
+                input_width,
+                input_order,
+                kernel_data,
+                kernel_len,
+                kernel_amount,
+                kernel_channels,
+                kernel_height,
+                kernel_width,
+                kernel_order,
+            )
+        }?;
+        let bias = unsafe { ffi_bias_import(bias, bias_length, kernel_amount) }?;
+        let padding = Padding {
+            top: pad_top,
+            right: pad_right,
+            left: pad_left,
+            bottom: pad_bottom,
+            padding_value: pad_value,
+        };
+        let stride = Stride {
+            x: stride_x,
+            y: stride_y,
+        };
+        // Each group of kernels sees `input_channels / groups` channels
+        check_conv_params(
+            (input_channels, input_height, input_width),
+            (
+                kernel_channels.saturating_mul(groups),
+                kernel_height,
+                kernel_width,
+            ),
+            &padding,
+            &stride,
+            mac_clip,
+            pp_clip,
+        )?;
+
+        let result: Tensor3<i8> = grouped_conv2d(
+            input_tensor,
+            kernels_tensor,
+            bias,
+            ffi_bias_addr(bias_addr),
+            Some(padding),
+            Some(stride),
+            Some(mac_clip),
+            Some(pp_clip),
+            None,
+            groups,
+        )
+        .map_err(ffi_layer_error)?;
+        unsafe { ffi_data_export(&result.to_buffer(), output, output_len) }
+    })
+}
+
+/// Executes depthwise Conv2D + Bias on DLA, i.e., grouped Conv2D with one group per input channel.
+///
+/// Arguments are the same as for `dla_grouped_conv2d_bias`. Kernels have one
+/// channel each and `kernel_amount` must be a multiple of `input_channels`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dla_depthwise_conv2d_bias(
+    input_data: *const i8,
+    input_len: usize,
+    kernel_data: *const i8,
+    kernel_len: usize,
+    bias: *const i32,
+    bias_length: usize,
+    output: *mut i8,
+    output_len: usize,
+    input_channels: usize,
+    input_height: usize,
+    input_width: usize,
+    input_order: *const c_char,
+    kernel_amount: usize,
+    kernel_channels: usize,
+    kernel_height: usize,
+    kernel_width: usize,
+    kernel_order: *const c_char,
+    pad_top: u32,
+    pad_right: u32,
+    pad_left: u32,
+    pad_bottom: u32,
+    pad_value: i32,
+    stride_x: u32,
+    stride_y: u32,
+    mac_clip: u32,
+    pp_clip: u32,
+    bias_addr: u32,
+) -> DlaStatus {
+    unsafe {
+        dla_grouped_conv2d_bias(
             input_data,
+            input_len,
+            kernel_data,
+            kernel_len,
+            bias,
+            bias_length,
+            output,
+            output_len,
             input_channels,
             input_height,
             input_width,
             input_order,
-            kernel_data,
             kernel_amount,
             kernel_channels,
             kernel_height,
             kernel_width,
             kernel_order,
+            input_channels,
+            pad_top,
+            pad_right,
+            pad_left,
+            pad_bottom,
+            pad_value,
+            stride_x,
+            stride_y,
+            mac_clip,
+            pp_clip,
+            bias_addr,
         )
-    };
-
-    // NOTE:(20241025 vaino-waltteri.granat@tuni.fi) TVM expects 32-bit bias, but DLA only support 16-bit bias, so we clip the incoming bias
-    // to range suitable for DLA
-    let bias: Vec<i16> = unsafe {
-        slice::from_raw_parts(bias as *const i32, bias_length)
-            .into_iter()
-            .map(|x| (*x).clamp(i16::MIN as i32, i16::MAX as i32) as i16)
-            .collect()
-    };
-
-    let optimized_pp = optimal_pp_bias_heuristic(&bias);
-
-    let result: Tensor3<i8> = grouped_conv2d(
-        input_tensor,
-        kernels_tensor,
-        bias,
-        Some(Padding {
+    }
+}
+
+/// Executes ReLU on DLA and writes result to output buffer in the input order.
+///
+/// # Arguments
+///
+/// * `input_len`, `output_len` - Lengths of the respective buffers in elements
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dla_relu(
+    input_data: *const i8,
+    input_len: usize,
+    output: *mut i8,
+    output_len: usize,
+    input_channels: usize,
+    input_height: usize,
+    input_width: usize,
+    input_order: *const c_char,
+    pp_clip: u32,
+) -> DlaStatus {
+    run(|| {
+        let input_tensor = unsafe {
+            ffi_tensor3_import(
+                input_data,
+                input_len,
+                input_channels,
+                input_height,
+                input_width,
+                input_order,
+            )
+        }?;
+        let order = input_tensor.order();
+        if pp_clip > MAX_PP_CLIP {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"pp_clip must be at most 31",
+            ));
+        }
+
+        let result = relu(input_tensor, Some(pp_clip));
+        unsafe { ffi_data_export(&result.to_buffer_with_order(order), output, output_len) }
+    })
+}
+
+/// Adds a bias to each channel on DLA and writes result to output buffer in the input order.
+///
+/// # Arguments
+///
+/// * `bias` - Buffer containing bias data. NOTE: Bias is actually i16 in hardware, here we use 32 for TVM compatibility. Must fit in i16.
+/// * `bias_length` - Number of biases, one per input channel
+/// * `input_len`, `output_len` - Lengths of the respective buffers in elements
+/// * `bias_addr` - Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. 0 places it after the output banks.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dla_bias(
+    input_data: *const i8,
+    input_len: usize,
+    bias: *const i32,
+    bias_length: usize,
+    output: *mut i8,
+    output_len: usize,
+    input_channels: usize,
+    input_height: usize,
+    input_width: usize,
+    input_order: *const c_char,
+    pp_clip: u32,
+    bias_addr: u32,
+) -> DlaStatus {
+    run(|| {
+        let input_tensor = unsafe {
+            ffi_tensor3_import(
+                input_data,
+                input_len,
+                input_channels,
+                input_height,
+                input_width,
+                input_order,
+            )
+        }?;
+        let order = input_tensor.order();
+        let bias = unsafe { ffi_bias_import(bias, bias_length, input_channels) }?;
+        if pp_clip > MAX_PP_CLIP {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"pp_clip must be at most 31",
+            ));
+        }
+
+        let result = layers::bias(input_tensor, bias, ffi_bias_addr(bias_addr), Some(pp_clip))
+            .map_err(ffi_layer_error)?;
+        unsafe { ffi_data_export(&result.to_buffer_with_order(order), output, output_len) }
+    })
+}
+
+/// Executes a fully connected layer on DLA and writes the raw accumulators to output buffer.
+///
+/// # Arguments
+///
+/// * `input_data`, `input_len` - Input vector
+/// * `weight_data`, `weight_len` - Weights in (units, input_len) order, as used by TVM's dense
+/// * `units` - Number of outputs
+/// * `output_len` - Length of output buffer in elements, at least `units`
+#[no_mangle]
+pub unsafe extern "C" fn dla_dense(
+    input_data: *const i8,
+    input_len: usize,
+    weight_data: *const i8,
+    weight_len: usize,
+    output: *mut i32,
+    output_len: usize,
+    units: usize,
+) -> DlaStatus {
+    run(|| {
+        let (input_tensor, weights) =
+            unsafe { ffi_dense_import(input_data, input_len, weight_data, weight_len, units) }?;
+        let result = dense(units, input_tensor, weights);
+        unsafe { ffi_data_export(&result, output, output_len) }
+    })
+}
+
+/// Executes a quantized fully connected layer on DLA, adds bias and requantizes the result to i8,
+/// matching TVM's `qnn.dense` followed by `qnn.requantize`.
+///
+/// Zero points are handled on the CPU, so DLA only sees the raw operands.
+///
+/// # Arguments
+///
+/// * `input_data`, `input_len` - Input vector
+/// * `weight_data`, `weight_len` - Weights in (units, input_len) order
+/// * `bias`, `bias_length` - One 32-bit bias per unit in the accumulator scale, or null and 0 for none
+/// * `output_len` - Length of output buffer in elements, at least `units`
+/// * `input_scale`, `kernel_scale`, `output_scale` - Quantization scales, must be positive
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dla_tvm_qnn_dense(
+    input_data: *const i8,
+    input_len: usize,
+    weight_data: *const i8,
+    weight_len: usize,
+    bias: *const i32,
+    bias_length: usize,
+    output: *mut i8,
+    output_len: usize,
+    units: usize,
+    input_zero_point: i32,
+    kernel_zero_point: i32,
+    output_zero_point: i32,
+    input_scale: f32,
+    kernel_scale: f32,
+    output_scale: f32,
+) -> DlaStatus {
+    run(|| {
+        let scale = input_scale * kernel_scale / output_scale;
+        if !(input_scale > 0.0 && kernel_scale > 0.0 && output_scale > 0.0 && scale.is_finite()) {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"quantization scales must be positive",
+            ));
+        }
+        let (input_tensor, weights) =
+            unsafe { ffi_dense_import(input_data, input_len, weight_data, weight_len, units) }?;
+        let bias: Option<&[i32]> = if bias.is_null() && bias_length == 0 {
+            None
+        } else if bias_length != units {
+            return Err(FfiError::new(
+                DlaStatus::SizeMismatch,
+                c"bias_length doesn't match units",
+            ));
+        } else {
+            Some(unsafe { ffi_slice(bias, bias_length) }?)
+        };
+
+        // sum((x - zx) * (w - zw)) = sum(x * w) - zw * sum(x) - zx * sum(w) + n * zx * zw
+        //
+        // The zero point terms overflow i32 for realistic lengths and zero points, so they're
+        // computed in i64 and saturate on pathological ones
+        let (input_zero_point, kernel_zero_point) =
+            (input_zero_point as i64, kernel_zero_point as i64);
+        let input_sum: i64 = input_tensor.to_buffer().iter().map(|&x| x as i64).sum();
+        let weight_sums: Vec<i64> = weights
+            .chunks_exact(input_len)
+            .map(|row| row.iter().map(|&w| w as i64).sum())
+            .collect();
+        let zero_point_term = (input_len as i64)
+            .saturating_mul(input_zero_point)
+            .saturating_mul(kernel_zero_point)
+            .saturating_sub(kernel_zero_point.saturating_mul(input_sum));
+
+        let acc = dense(units, input_tensor, weights);
+        let result: Vec<i8> = acc
+            .iter()
+            .zip(weight_sums.iter())
+            .enumerate()
+            .map(|(unit, (&acc, &weight_sum))| {
+                let acc = (acc as i64)
+                    .saturating_sub(input_zero_point.saturating_mul(weight_sum))
+                    .saturating_add(zero_point_term)
+                    .saturating_add(bias.map_or(0, |b| b[unit] as i64));
+                // Round half away from zero, `f32::round` is not available in core. The cast
+                // saturates.
+                let scaled = acc as f32 * scale;
+                let rounded = if scaled < 0.0 {
+                    scaled - 0.5
+                } else {
+                    scaled + 0.5
+                } as i64;
+                let value = rounded.saturating_add(output_zero_point as i64);
+                value.clamp(i8::MIN as i64, i8::MAX as i64) as i8
+            })
+            .collect();
+        unsafe { ffi_data_export(&result, output, output_len) }
+    })
+}
+
+/// Adds two quantized tensors of the same shape and requantizes the result to i8,
+/// matching TVM's `qnn.add`. Runs on DLA when the scales allow it, otherwise on the CPU.
+///
+/// # Arguments
+///
+/// * `a_data`, `a_len`, `b_data`, `b_len` - Input buffers, both in `order`
+/// * `output_len` - Length of output buffer in elements. The output is written in `order`.
+/// * `a_scale`, `b_scale`, `output_scale` - Quantization scales, must be positive
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dla_tvm_qnn_add(
+    a_data: *const i8,
+    a_len: usize,
+    b_data: *const i8,
+    b_len: usize,
+    output: *mut i8,
+    output_len: usize,
+    channels: usize,
+    height: usize,
+    width: usize,
+    order: *const c_char,
+    a_zero_point: i32,
+    b_zero_point: i32,
+    output_zero_point: i32,
+    a_scale: f32,
+    b_scale: f32,
+    output_scale: f32,
+) -> DlaStatus {
+    run(|| {
+        if !(a_scale > 0.0 && b_scale > 0.0 && output_scale > 0.0) {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"quantization scales must be positive",
+            ));
+        }
+        let a = unsafe { ffi_tensor3_import(a_data, a_len, channels, height, width, order) }?;
+        let b = unsafe { ffi_tensor3_import(b_data, b_len, channels, height, width, order) }?;
+        let quant = |scale, zero_point| QuantParams { scale, zero_point };
+
+        let result = layers::add(
+            &a,
+            &b,
+            quant(a_scale, a_zero_point),
+            quant(b_scale, b_zero_point),
+            quant(output_scale, output_zero_point),
+        )
+        .map_err(|_| FfiError::new(DlaStatus::SizeMismatch, c"input dimensions don't match"))?;
+        unsafe { ffi_data_export(&result.to_buffer(), output, output_len) }
+    })
+}
+
+/// Executes Conv2D with optional bias and ReLU on a batch of inputs. Kernels
+/// and bias are uploaded to DLA once for the whole batch.
+///
+/// # Arguments
+///
+/// * `bias` - Buffer containing bias data, or null to disable bias. NOTE: Bias is actually i16 in hardware, here we use 32 for TVM compatibility. Must fit in i16.
+/// * `bias_length` - Number of biases, one per kernel, or 0 if `bias` is null
+/// * `relu` - Enables ReLU
+/// * `input_len`, `kernel_len`, `output_len` - Lengths of the respective buffers in elements
+/// * `batch` - Number of inputs
+/// * `input_order` - Layout of the inputs including the batch axis, e.g., "NHWC". The output uses the same layout.
+/// * `bias_addr` - Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. 0 places it after the output banks.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dla_conv2d_batch(
+    input_data: *const i8,
+    input_len: usize,
+    kernel_data: *const i8,
+    kernel_len: usize,
+    bias: *const i32,
+    bias_length: usize,
+    relu: bool,
+    output: *mut i8,
+    output_len: usize,
+    batch: usize,
+    input_channels: usize,
+    input_height: usize,
+    input_width: usize,
+    input_order: *const c_char,
+    kernel_amount: usize,
+    kernel_channels: usize,
+    kernel_height: usize,
+    kernel_width: usize,
+    kernel_order: *const c_char,
+    pad_top: u32,
+    pad_right: u32,
+    pad_left: u32,
+    pad_bottom: u32,
+    pad_value: i32,
+    stride_x: u32,
+    stride_y: u32,
+    mac_clip: u32,
+    pp_clip: u32,
+    bias_addr: u32,
+) -> DlaStatus {
+    run(|| {
+        check_size(
+            &[batch, input_channels, input_height, input_width],
+            input_len,
+            c"input dimensions don't match input_len",
+        )?;
+        check_size(
+            &[kernel_amount, kernel_channels, kernel_height, kernel_width],
+            kernel_len,
+            c"kernel dimensions don't match kernel_len",
+        )?;
+        let input_order = unsafe { ffi_batch_order(input_order) }?;
+        let kernel_order = unsafe { ffi_order4(kernel_order) }?;
+        let bias = if bias.is_null() && bias_length == 0 {
+            None
+        } else {
+            Some(unsafe { ffi_bias_import(bias, bias_length, kernel_amount) }?)
+        };
+        let padding = Padding {
             top: pad_top,
             right: pad_right,
             left: pad_left,
             bottom: pad_bottom,
-            padding_value: 0,
-        }),
-        Some(Stride {
+            padding_value: pad_value,
+        };
+        let stride = Stride {
             x: stride_x,
             y: stride_y,
-        }),
-        Some(mac_clip),
-        Some(optimized_pp),
-        None,
-        groups,
-    );
-
-    // TVM requantization and clip
-    // NOTE:(20240927 vaino-waltteri.granat@tuni.fi) on DLA clipping behaviour with TVM.
-    // DLA's conv2d arithmetic is done at 16 bit width, but the output of the DLA is limited to 8 bits.
-    // To comply with TVM's expected value range our solution is to bit shift/clip the 16-bit result of
-    // conv2d by 8 bits and shift if back in the driver. This causes some amount of data loss due to
-    // the lost granularity of the values. The clipping amount is set by the pp_clip argument.
-    let mut res_i32: Vec<i32> = result
-        .to_buffer()
-        .iter()
-        .map(|x: &i8| ((*x as i32) << optimized_pp))
-        .collect();
+        };
+        check_conv_params(
+            (input_channels, input_height, input_width),
+            (kernel_channels, kernel_height, kernel_width),
+            &padding,
+            &stride,
+            mac_clip,
+            pp_clip,
+        )?;
 
-    unsafe { core::ptr::copy_nonoverlapping(res_i32.as_mut_ptr(), output, result.get_size()) };
+        let inputs = Tensor4View::new(
+            batch,
+            input_channels,
+            input_height,
+            input_width,
+            unsafe { ffi_slice(input_data, input_len) }?,
+            input_order,
+        )
+        .map_err(|_| {
+            FfiError::new(
+                DlaStatus::SizeMismatch,
+                c"input dimensions don't match input_len",
+            )
+        })?;
+        let kernels = Tensor4View::new(
+            kernel_amount,
+            kernel_channels,
+            kernel_height,
+            kernel_width,
+            unsafe { ffi_slice(kernel_data, kernel_len) }?,
+            kernel_order,
+        )
+        .map_err(|_| {
+            FfiError::new(
+                DlaStatus::SizeMismatch,
+                c"kernel dimensions don't match kernel_len",
+            )
+        })?;
+
+        let result: Tensor4<i8> = conv2d_batch_view(
+            inputs,
+            kernels,
+            bias.as_deref(),
+            ffi_bias_addr(bias_addr),
+            relu,
+            Some(padding),
+            Some(stride),
+            Some(mac_clip),
+            Some(pp_clip),
+            None,
+        )
+        .map_err(ffi_layer_error)?;
+        unsafe {
+            ffi_data_export(
+                &result.to_buffer_with_order(input_order),
+                output,
+                output_len,
+            )
+        }
+    })
 }
@@ -0,0 +1,79 @@
+//! Status codes returned over FFI
+
+use core::ffi::{c_char, CStr};
+
+/// Status returned by the `dla_*` functions. On anything but `Ok`,
+/// `dla_last_error_message` describes what went wrong.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DlaStatus {
+    /// Operation succeeded
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    /// A layout string was not valid UTF-8 or not a known order, e.g., "NHWC"
+    /// where "HWC" is expected
+    InvalidLayout = 2,
+    /// The dimensions don't match the given buffer length
+    SizeMismatch = 3,
+    /// The output buffer is too small for the result
+    OutputTooSmall = 4,
+    /// A parameter is out of range, e.g., a zero dimension or stride
+    InvalidArgument = 5,
+    /// `dla_init` has not been called
+    NotInitialized = 6,
+    /// A bias doesn't fit in the 16 bits supported by DLA. The `dla_tvm_qnn_*`
+    /// functions scale 32-bit bias instead.
+    BiasOutOfRange = 7,
+    /// The layer doesn't fit DLA's memory banks, e.g., no bank is left for
+    /// the bias after the output
+    OutOfMemoryBanks = 8,
+}
+
+/// Failure of an FFI call, with a message for `dla_last_error_message`
+pub(crate) struct FfiError {
+    pub status: DlaStatus,
+    pub msg: &'static CStr,
+}
+
+impl FfiError {
+    pub const fn new(status: DlaStatus, msg: &'static CStr) -> Self {
+        Self { status, msg }
+    }
+}
+
+pub(crate) type FfiResult<T> = Result<T, FfiError>;
+
+static mut LAST_ERROR: &CStr = c"";
+
+/// Converts `result` into a status, storing the message on failure
+pub(crate) fn report(result: FfiResult<()>) -> DlaStatus {
+    match result {
+        Ok(()) => DlaStatus::Ok,
+        Err(e) => {
+            // SAFETY: the C API is single-threaded
+            unsafe { LAST_ERROR = e.msg };
+            e.status
+        }
+    }
+}
+
+/// Runs `f` once the heap is available and converts its result into a status
+pub(crate) fn run(f: impl FnOnce() -> FfiResult<()>) -> DlaStatus {
+    if !headsail_bsp::is_heap_initialized() {
+        return report(Err(FfiError::new(
+            DlaStatus::NotInitialized,
+            c"dla_init must be called first",
+        )));
+    }
+    report(f())
+}
+
+/// Returns a description of the last failed call as a NUL-terminated string.
+/// The string is static and must not be freed.
+#[no_mangle]
+pub extern "C" fn dla_last_error_message() -> *const c_char {
+    // SAFETY: the C API is single-threaded
+    let msg: &CStr = unsafe { LAST_ERROR };
+    msg.as_ptr()
+}
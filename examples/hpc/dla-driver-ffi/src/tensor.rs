@@ -0,0 +1,402 @@
+//! Opaque tensor handles
+//!
+//! Handles let C callers keep data in the layout DLA reads it across calls.
+//! Feature maps can borrow a caller owned buffer and kernels are packed into
+//! HWKC once on creation, so running a layer with handles skips the per-call
+//! copies done by `dla_conv2d*`. Inputs are streamed to DLA in any order.
+
+use crate::status::{run, DlaStatus, FfiError, FfiResult};
+use crate::{
+    check_conv_params, check_size, ffi_bias_addr, ffi_bias_import, ffi_layer_error, ffi_order3,
+    ffi_order4, ffi_slice,
+};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::c_char;
+use core::slice;
+use dla_driver::layers::{conv2d_packed, conv2d_packed_into};
+use dla_driver::tensor3::{Order3, Tensor3, Tensor3View};
+use dla_driver::tensor4::{PackedKernels, Tensor4};
+use dla_driver::utils::calculate_conv2d_out_param_dim;
+use dla_driver::{Padding, Stride};
+
+/// Storage of a feature map
+enum Buffer {
+    Owned(Vec<i8>),
+    /// Caller owned buffer, which must outlive the handle
+    Borrowed(*mut i8, usize),
+}
+
+enum Tensor {
+    Feature {
+        channels: usize,
+        height: usize,
+        width: usize,
+        order: Order3,
+        buffer: Buffer,
+    },
+    Kernels(PackedKernels),
+}
+
+/// Opaque handle to a feature map or a set of packed kernels
+pub struct DlaTensor(Tensor);
+
+impl DlaTensor {
+    fn data(&self) -> &[i8] {
+        match &self.0 {
+            Tensor::Feature { buffer, .. } => match buffer {
+                Buffer::Owned(v) => v,
+                // SAFETY: validity of borrowed buffers is guaranteed by the caller of `dla_tensor_from_ptr`
+                Buffer::Borrowed(ptr, len) => unsafe { slice::from_raw_parts(*ptr, *len) },
+            },
+            Tensor::Kernels(k) => k.as_slice(),
+        }
+    }
+
+    /// Returns true if the data of the handles share any memory
+    fn overlaps(&self, other: &DlaTensor) -> bool {
+        let (a, b) = (self.data().as_ptr_range(), other.data().as_ptr_range());
+        a.start < b.end && b.start < a.end
+    }
+}
+
+/// Stores `tensor` behind `out`
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+unsafe fn ffi_handle_export(tensor: DlaTensor, out: *mut *mut DlaTensor) -> FfiResult<()> {
+    if out.is_null() {
+        return Err(FfiError::new(
+            DlaStatus::NullPointer,
+            c"handle output pointer is null",
+        ));
+    }
+    unsafe { *out = Box::into_raw(Box::new(tensor)) };
+    Ok(())
+}
+
+/// Dereferences a handle
+///
+/// # Safety
+///
+/// `tensor` must be null or a live handle.
+unsafe fn ffi_handle<'a>(tensor: *const DlaTensor) -> FfiResult<&'a DlaTensor> {
+    unsafe { tensor.as_ref() }.ok_or(FfiError::new(
+        DlaStatus::NullPointer,
+        c"tensor handle is null",
+    ))
+}
+
+/// Creates a zero filled feature map owned by the handle. Use
+/// `dla_tensor_data` to fill it.
+///
+/// # Arguments
+///
+/// * `order` - Layout of the data, e.g., "HWC"
+/// * `out` - Receives the new handle, which must be released with `dla_tensor_destroy`
+#[no_mangle]
+pub unsafe extern "C" fn dla_tensor_create(
+    channels: usize,
+    height: usize,
+    width: usize,
+    order: *const c_char,
+    out: *mut *mut DlaTensor,
+) -> DlaStatus {
+    run(|| {
+        let order = unsafe { ffi_order3(order) }?;
+        let len = match channels
+            .checked_mul(height)
+            .and_then(|x| x.checked_mul(width))
+        {
+            Some(len) if len > 0 => len,
+            _ => {
+                return Err(FfiError::new(
+                    DlaStatus::InvalidArgument,
+                    c"tensor dimensions must be non-zero and fit in memory",
+                ))
+            }
+        };
+        let tensor = DlaTensor(Tensor::Feature {
+            channels,
+            height,
+            width,
+            order,
+            buffer: Buffer::Owned(vec![0; len]),
+        });
+        unsafe { ffi_handle_export(tensor, out) }
+    })
+}
+
+/// Creates a feature map handle over a caller owned buffer without copying
+/// it. The buffer must stay valid until the handle is destroyed.
+///
+/// # Arguments
+///
+/// * `data`, `len` - Buffer and its length in elements
+/// * `order` - Layout of the data, e.g., "HWC"
+/// * `out` - Receives the new handle, which must be released with `dla_tensor_destroy`
+#[no_mangle]
+pub unsafe extern "C" fn dla_tensor_from_ptr(
+    data: *mut i8,
+    len: usize,
+    channels: usize,
+    height: usize,
+    width: usize,
+    order: *const c_char,
+    out: *mut *mut DlaTensor,
+) -> DlaStatus {
+    run(|| {
+        if data.is_null() {
+            return Err(FfiError::new(
+                DlaStatus::NullPointer,
+                c"buffer pointer is null",
+            ));
+        }
+        check_size(
+            &[channels, height, width],
+            len,
+            c"tensor dimensions don't match len",
+        )?;
+        let order = unsafe { ffi_order3(order) }?;
+        let tensor = DlaTensor(Tensor::Feature {
+            channels,
+            height,
+            width,
+            order,
+            buffer: Buffer::Borrowed(data, len),
+        });
+        unsafe { ffi_handle_export(tensor, out) }
+    })
+}
+
+/// Packs kernels into the HWKC layout used by DLA. The data is copied once,
+/// so `data` may be freed after the call.
+///
+/// # Arguments
+///
+/// * `data`, `len` - Kernel buffer and its length in elements
+/// * `order` - Layout of the data, e.g., "HWCK" as produced by TVM
+/// * `out` - Receives the new handle, which must be released with `dla_tensor_destroy`
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dla_kernels_create(
+    data: *const i8,
+    len: usize,
+    kernels: usize,
+    channels: usize,
+    height: usize,
+    width: usize,
+    order: *const c_char,
+    out: *mut *mut DlaTensor,
+) -> DlaStatus {
+    run(|| {
+        check_size(
+            &[kernels, channels, height, width],
+            len,
+            c"kernel dimensions don't match len",
+        )?;
+        let order = unsafe { ffi_order4(order) }?;
+        let data = unsafe { ffi_slice(data, len) }?.to_vec();
+        let tensor = Tensor4::from_data_buffer(kernels, channels, height, width, data, order)
+            .map_err(|_| {
+                FfiError::new(
+                    DlaStatus::SizeMismatch,
+                    c"kernel dimensions don't match len",
+                )
+            })?;
+        unsafe { ffi_handle_export(DlaTensor(Tensor::Kernels(PackedKernels::new(&tensor))), out) }
+    })
+}
+
+/// Returns the data of a feature map handle, or null for kernel handles
+#[no_mangle]
+pub unsafe extern "C" fn dla_tensor_data(tensor: *mut DlaTensor) -> *mut i8 {
+    match unsafe { tensor.as_mut() } {
+        Some(DlaTensor(Tensor::Feature { buffer, .. })) => match buffer {
+            Buffer::Owned(v) => v.as_mut_ptr(),
+            Buffer::Borrowed(ptr, _) => *ptr,
+        },
+        _ => core::ptr::null_mut(),
+    }
+}
+
+/// Releases a handle. Borrowed buffers are not freed. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn dla_tensor_destroy(tensor: *mut DlaTensor) {
+    if !tensor.is_null() {
+        drop(unsafe { Box::from_raw(tensor) });
+    }
+}
+
+/// Executes Conv2D with optional bias and ReLU on tensor handles and writes
+/// the result into the `output` feature map in its own order.
+///
+/// # Arguments
+///
+/// * `input` - Feature map handle
+/// * `kernels` - Handle from `dla_kernels_create`
+/// * `bias`, `bias_length` - One bias per kernel, or null and 0 to disable bias. Must fit in i16.
+/// * `relu` - Enables ReLU
+/// * `output` - Feature map handle matching the output dimensions. Must be a
+///   different handle from `input` and `kernels`, and its buffer must not
+///   overlap theirs.
+/// * `bias_addr` - Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. 0 places it after the output banks.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dla_tensor_conv2d(
+    input: *const DlaTensor,
+    kernels: *const DlaTensor,
+    bias: *const i32,
+    bias_length: usize,
+    relu: bool,
+    output: *mut DlaTensor,
+    pad_top: u32,
+    pad_right: u32,
+    pad_left: u32,
+    pad_bottom: u32,
+    pad_value: i32,
+    stride_x: u32,
+    stride_y: u32,
+    mac_clip: u32,
+    pp_clip: u32,
+    bias_addr: u32,
+) -> DlaStatus {
+    run(|| {
+        // Checked before any `&mut` to the output is created
+        if core::ptr::eq(input, output) || core::ptr::eq(kernels, output) {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"output must be a different handle from input and kernels",
+            ));
+        }
+        let input = unsafe { ffi_handle(input) }?;
+        let Tensor::Feature {
+            channels,
+            height,
+            width,
+            order,
+            ..
+        } = input.0
+        else {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"input must be a feature map",
+            ));
+        };
+        let kernels_handle = unsafe { ffi_handle(kernels) }?;
+        let Tensor::Kernels(kernels) = &kernels_handle.0 else {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"kernels must be created with dla_kernels_create",
+            ));
+        };
+        let bias = if bias.is_null() && bias_length == 0 {
+            None
+        } else {
+            Some(unsafe { ffi_bias_import(bias, bias_length, kernels.kernels()) }?)
+        };
+
+        let padding = Padding {
+            top: pad_top,
+            right: pad_right,
+            left: pad_left,
+            bottom: pad_bottom,
+            padding_value: pad_value,
+        };
+        let stride = Stride {
+            x: stride_x,
+            y: stride_y,
+        };
+        check_conv_params(
+            (channels, height, width),
+            (kernels.channels(), kernels.height(), kernels.width()),
+            &padding,
+            &stride,
+            mac_clip,
+            pp_clip,
+        )?;
+
+        let out_dims = calculate_conv2d_out_param_dim(
+            (width as u32, height as u32),
+            (kernels.width() as u32, kernels.height() as u32),
+            Some(padding.clone()),
+            Some(stride.clone()),
+        );
+        let output_handle = unsafe { ffi_handle(output) }?;
+        if output_handle.overlaps(input) || output_handle.overlaps(kernels_handle) {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"output buffer must not overlap input or kernels",
+            ));
+        }
+        // SAFETY: `output` is non-null and a different handle, and its buffer
+        // doesn't overlap the ones borrowed from `input` and `kernels`
+        let DlaTensor(Tensor::Feature {
+            channels: out_channels,
+            height: out_height,
+            width: out_width,
+            order: out_order,
+            buffer: out_buffer,
+        }) = (unsafe { &mut *output })
+        else {
+            return Err(FfiError::new(
+                DlaStatus::InvalidArgument,
+                c"output must be a feature map",
+            ));
+        };
+        if (*out_channels, *out_height, *out_width) != (kernels.kernels(), out_dims.1, out_dims.0) {
+            return Err(FfiError::new(
+                DlaStatus::SizeMismatch,
+                c"output dimensions don't match the layer output",
+            ));
+        }
+
+        let input_view =
+            Tensor3View::new(channels, height, width, input.data(), order).map_err(|_| {
+                FfiError::new(
+                    DlaStatus::SizeMismatch,
+                    c"input dimensions don't match its buffer",
+                )
+            })?;
+
+        let out: &mut [i8] = match out_buffer {
+            Buffer::Owned(v) => v,
+            // SAFETY: validity of borrowed buffers is guaranteed by the caller of `dla_tensor_from_ptr`
+            Buffer::Borrowed(ptr, len) => unsafe { slice::from_raw_parts_mut(*ptr, *len) },
+        };
+        // DLA writes HWC, which can go straight into the output
+        if *out_order == Order3::HWC {
+            conv2d_packed_into(
+                input_view,
+                kernels,
+                bias.as_deref(),
+                ffi_bias_addr(bias_addr),
+                relu,
+                Some(padding),
+                Some(stride),
+                Some(mac_clip),
+                Some(pp_clip),
+                out,
+            )
+            .map_err(ffi_layer_error)?;
+        } else {
+            let result: Tensor3<i8> = conv2d_packed(
+                input_view,
+                kernels,
+                bias.as_deref(),
+                ffi_bias_addr(bias_addr),
+                relu,
+                Some(padding),
+                Some(stride),
+                Some(mac_clip),
+                Some(pp_clip),
+                None,
+            )
+            .map_err(ffi_layer_error)?;
+            out.copy_from_slice(&result.to_buffer_with_order(*out_order));
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,154 @@
+//! Checks that batched layers give the same outputs as running each batch
+//! item on its own, i.e., that kernels and bias stay resident in DLA's memory
+//! banks across `init_layer` and handshake.
+//!
+//! Run with `scripts/robot/dla_batch.robot`.
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate alloc;
+
+use dla_driver::layers::{
+    bias, bias_batch, conv2d, conv2d_batch, conv2d_bias_relu, conv2d_bias_relu_batch, dense,
+    dense_batch, relu, relu_batch,
+};
+use dla_driver::tensor3::{Order3, Tensor3};
+use dla_driver::tensor4::{Order4, Tensor4};
+use headsail_bsp::{headsail_tests, init_heap, tb::TestResult, tb_assert, tb_assert_eq, ufmt};
+use panic_halt as _;
+
+use alloc::vec::Vec;
+
+const BATCH: usize = 3;
+const CHANNELS: usize = 2;
+const HEIGHT: usize = 4;
+const WIDTH: usize = 4;
+const ITEM_SIZE: usize = CHANNELS * HEIGHT * WIDTH;
+
+/// Deterministic inputs in NHWC, different for each batch item
+fn input_data() -> Vec<i8> {
+    (0..BATCH * ITEM_SIZE)
+        .map(|i| ((i * 37 + i / ITEM_SIZE * 11) % 15) as i8 - 7)
+        .collect()
+}
+
+fn inputs() -> Tensor4<i8> {
+    Tensor4::from_data_buffer(BATCH, CHANNELS, HEIGHT, WIDTH, input_data(), Order4::KHWC).unwrap()
+}
+
+fn input_item(n: usize) -> Tensor3<i8> {
+    let data = input_data()[n * ITEM_SIZE..(n + 1) * ITEM_SIZE].to_vec();
+    Tensor3::from_data_buffer(CHANNELS, HEIGHT, WIDTH, data, Order3::HWC).unwrap()
+}
+
+fn kernels() -> Tensor4<i8> {
+    let data = (0..2 * CHANNELS * 3 * 3)
+        .map(|i| (i % 5) as i8 - 2)
+        .collect();
+    Tensor4::from_data_buffer(2, CHANNELS, 3, 3, data, Order4::KCHW).unwrap()
+}
+
+/// Compares the batched outputs against the concatenated unbatched ones
+fn compare<T: PartialEq>(batched: &[T], unbatched: &[T]) -> TestResult {
+    tb_assert_eq!(batched.len(), unbatched.len());
+    let mismatch = batched.iter().zip(unbatched).position(|(b, u)| b != u);
+    tb_assert!(
+        mismatch.is_none(),
+        "first mismatch at {}",
+        mismatch.unwrap_or(0)
+    );
+    Ok(())
+}
+
+fn conv2d_batch_matches() -> TestResult {
+    let batched: Tensor4<i8> = conv2d_batch(inputs(), kernels(), None, None, None, None, None);
+    let mut unbatched = Vec::new();
+    for n in 0..BATCH {
+        let output: Tensor3<i8> = conv2d(input_item(n), kernels(), None, None, None, None, None);
+        unbatched.extend(output.to_buffer_with_order(Order3::HWC));
+    }
+    compare(&batched.to_buffer_with_order(Order4::KHWC), &unbatched)
+}
+
+fn conv2d_bias_relu_batch_matches() -> TestResult {
+    let bias_values = vec![-3, 5];
+    let batched: Tensor4<i8> = conv2d_bias_relu_batch(
+        inputs(),
+        kernels(),
+        bias_values.clone(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let mut unbatched = Vec::new();
+    for n in 0..BATCH {
+        let output: Tensor3<i8> = conv2d_bias_relu(
+            input_item(n),
+            kernels(),
+            bias_values.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        unbatched.extend(output.to_buffer_with_order(Order3::HWC));
+    }
+    compare(&batched.to_buffer_with_order(Order4::KHWC), &unbatched)
+}
+
+fn relu_batch_matches() -> TestResult {
+    let batched = relu_batch(inputs(), None);
+    let mut unbatched = Vec::new();
+    for n in 0..BATCH {
+        unbatched.extend(relu(input_item(n), None).to_buffer_with_order(Order3::HWC));
+    }
+    compare(&batched.to_buffer_with_order(Order4::KHWC), &unbatched)
+}
+
+fn bias_batch_matches() -> TestResult {
+    let bias_values = vec![7, -7];
+    let batched = bias_batch(inputs(), bias_values.clone(), None, None).unwrap();
+    let mut unbatched = Vec::new();
+    for n in 0..BATCH {
+        let output = bias(input_item(n), bias_values.clone(), None, None).unwrap();
+        unbatched.extend(output.to_buffer_with_order(Order3::HWC));
+    }
+    compare(&batched.to_buffer_with_order(Order4::KHWC), &unbatched)
+}
+
+fn dense_batch_matches() -> TestResult {
+    let outputs = 4;
+    let weights: Vec<i8> = (0..outputs * ITEM_SIZE)
+        .map(|i| (i % 3) as i8 - 1)
+        .collect();
+    let batched = dense_batch(outputs, inputs(), weights.clone()).unwrap();
+    tb_assert_eq!(batched.len(), BATCH);
+    for (n, batched) in batched.iter().enumerate() {
+        compare(batched, &dense(outputs, input_item(n), weights.clone()))?;
+    }
+    Ok(())
+}
+
+/// Sets up the heap for the tests after it, as tests run in order
+fn heap_init() -> TestResult {
+    // SAFETY: `init_heap` must be called once only, and this test runs once
+    unsafe { init_heap() };
+    Ok(())
+}
+
+headsail_tests!(
+    heap_init,
+    conv2d_batch_matches,
+    conv2d_bias_relu_batch_matches,
+    relu_batch_matches,
+    bias_batch_matches,
+    dense_batch_matches,
+);
@@ -80,11 +80,11 @@ fn conv_test() {
 	let bias: Vec<i16> = vec![-16, 16, 0, 0, 8, -8, -36, 36];
 
     let din_tensor: Tensor3<i8> = Tensor3::from_data_buffer(4, 5, 5, din, Order3::CHW).unwrap();
-    let wgt_tensor: Tensor4<i8> = Tensor4::from_data_buffer(2, 4, 3, 3, wgt, Order4::KCHW).unwrap();
+    let wgt_tensor: Tensor4<i8> = Tensor4::from_data_buffer(8, 1, 3, 3, wgt, Order4::KCHW).unwrap();
 
     sprintln!("Data loaded");
     let mut output: Tensor3<i8> =
-        dla_driver::layers::grouped_conv2d(din_tensor, wgt_tensor, bias, None, None, None, None, None, 4);
+        dla_driver::layers::grouped_conv2d(din_tensor, wgt_tensor, bias, None, None, None, None, None, None, 4).unwrap();
     output.permute(Order3::CWH);
 
     sprintln!("Output dim: {} {} {}", output.dimensions().0, output.dimensions().1, output.dimensions().2);
@@ -58,8 +58,8 @@ fn run_random_layer(
     kernel_height: u32,
     seed: u64,
 ) -> Vec<i8> {
-    let mut input = generate_random_matrix(input_width, input_height, seed);
-    let mut kernel = generate_random_matrix_small(kernel_width, kernel_height, seed * 2);
+    let input = generate_random_matrix(input_width, input_height, seed);
+    let kernel = generate_random_matrix_small(kernel_width, kernel_height, seed * 2);
 
     // Calculate output size
     let (output_width, output_height) = calculate_conv2d_out_param_dim(
@@ -70,8 +70,8 @@ fn run_random_layer(
         (1, 1),
     );
 
-    let mut bias = generate_random_matrix(output_height, output_width, seed * 4);
-    dla.write_data_bank(MEMORY_BANK_12_OFFSET, &mut bias);
+    let bias = generate_random_matrix(output_height, output_width, seed * 4);
+    dla.write_data_bank(MEMORY_BANK_12_OFFSET, &bias);
 
     // Initalize layer
     let config = LayerConfig {
@@ -109,8 +109,8 @@ fn run_random_layer(
     dla.init_layer(config);
 
     // Write input and kernel to buffer
-    dla.write_input(&mut input);
-    dla.write_kernel(&mut kernel);
+    dla.write_input(&input);
+    dla.write_kernel(&kernel);
 
     // Mark data ready to start calculations
     dla.kernel_data_ready(true);
@@ -155,12 +155,14 @@ fn validate_conv2d_bias() -> bool {
         din_tensor,
         wgt_tensor,
         bias_i16,
+        None,
         Some(padding),
         Some(stride),
         Some(6),
         Some(4),
         None,
-    );
+    )
+    .unwrap();
 
     output.permute(Order3::HWC);
 
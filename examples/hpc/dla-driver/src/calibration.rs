@@ -0,0 +1,194 @@
+//! Calibration of `mac_clip` and `pp_clip` from sample data
+//!
+//! DLA keeps 16 bits of the accumulator after the `mac_clip` shift and 8 bits
+//! of the biased sum after the `pp_clip` shift. [calibrate_conv2d] computes the
+//! exact accumulators of a layer for representative inputs on the CPU, which
+//! are what TVM computes for `qnn.conv2d` before requantization, and picks the
+//! shifts that reproduce them with the least saturation and rounding error.
+
+use crate::tensor3::{Order3, Tensor3};
+use crate::tensor4::{Order4, Tensor4};
+use crate::utils::{calculate_conv2d_out_param_dim, scale_bias};
+use crate::{Padding, Stride, DEFAULT_PADDING, DEFAULT_STRIDE, MAX_MAC_CLIP};
+use alloc::vec::Vec;
+
+/// Larger `pp_clip` values shift every 16-bit sum to 0 or -1
+const MAX_USEFUL_PP_CLIP: u32 = 15;
+
+/// Clip shifts chosen by calibration and the error they are expected to cause
+#[derive(Clone, Debug, PartialEq)]
+pub struct Calibration {
+    pub mac_clip: u32,
+    pub pp_clip: u32,
+    /// Bias scaled down by `mac_clip` for use with the layer, or `None` if no bias was given
+    pub bias: Option<Vec<i16>>,
+    /// Mean absolute error against the reference, in accumulator units
+    pub mean_error: f32,
+    /// Largest absolute error against the reference, in accumulator units
+    pub max_error: u64,
+    /// Number of outputs that saturated in the 16 or 8-bit stage
+    pub saturated: usize,
+    /// Number of outputs the error was measured over
+    pub samples: usize,
+}
+
+impl Calibration {
+    /// Total shift from the accumulator to the 8-bit output. Shifting DLA's
+    /// output left by this returns it to accumulator units, e.g., for TVM's
+    /// requantization.
+    pub fn output_shift(&self) -> u32 {
+        self.mac_clip + self.pp_clip
+    }
+}
+
+/// Models DLA's post-processing of one accumulator. Returns the 8-bit output
+/// shifted back to accumulator units and whether any stage saturated.
+fn simulate(acc: i32, bias: i16, mac_clip: u32, pp_clip: u32) -> (i64, bool) {
+    let shifted = acc >> mac_clip;
+    let mac_out = shifted.clamp(i16::MIN as i32, i16::MAX as i32);
+    let biased = mac_out + bias as i32;
+    let sum = biased.clamp(i16::MIN as i32, i16::MAX as i32);
+    let pp_out = (sum >> pp_clip).clamp(i8::MIN as i32, i8::MAX as i32);
+
+    let saturated = mac_out != shifted || sum != biased || pp_out != sum >> pp_clip;
+    ((pp_out as i64) << (mac_clip + pp_clip), saturated)
+}
+
+/// Computes the exact accumulators of Conv2D on the CPU in CHW order. DLA
+/// can't provide these, as it keeps only 16 bits of them even in i32 mode.
+fn reference_conv2d(
+    input: &Tensor3<i8>,
+    kernels: &Tensor4<i8>,
+    padding: &Padding,
+    stride: &Stride,
+) -> Vec<i32> {
+    let (channels, height, width) = input.dimensions();
+    let (kernel_count, _, kernel_height, kernel_width) = kernels.dimensions();
+    let (out_width, out_height) = calculate_conv2d_out_param_dim(
+        (width as u32, height as u32),
+        (kernel_width as u32, kernel_height as u32),
+        Some(padding.clone()),
+        Some(stride.clone()),
+    );
+    let input = input.to_buffer_with_order(Order3::CHW);
+    let kernels = kernels.to_buffer_with_order(Order4::KCHW);
+
+    let pixel = |channel: usize, row: isize, col: isize| {
+        if row < 0 || col < 0 || row >= height as isize || col >= width as isize {
+            padding.padding_value
+        } else {
+            input[(channel * height + row as usize) * width + col as usize] as i32
+        }
+    };
+
+    let mut output = Vec::with_capacity(kernel_count * out_height * out_width);
+    for kernel in 0..kernel_count {
+        for y in 0..out_height {
+            for x in 0..out_width {
+                let mut acc = 0i32;
+                for channel in 0..channels {
+                    for ky in 0..kernel_height {
+                        for kx in 0..kernel_width {
+                            let row = (y * stride.y as usize + ky) as isize - padding.top as isize;
+                            let col = (x * stride.x as usize + kx) as isize - padding.left as isize;
+                            let weight = kernels[((kernel * channels + channel) * kernel_height
+                                + ky)
+                                * kernel_width
+                                + kx];
+                            acc += weight as i32 * pixel(channel, row, col);
+                        }
+                    }
+                }
+                output.push(acc);
+            }
+        }
+    }
+    output
+}
+
+/// Picks `mac_clip` and `pp_clip` for a Conv2D layer from representative inputs
+///
+/// The exact accumulators of each input are computed on the CPU, then every
+/// clip combination is evaluated against `accumulator + bias`. Ties resolve to
+/// the smallest shifts.
+///
+/// # Arguments
+/// - `inputs`: Representative input feature maps.
+/// - `kernels`: Kernels of the layer.
+/// - `bias`: Optional 32-bit bias per kernel in accumulator units, as given by TVM.
+/// - `padding`, `stride`: Parameters of the layer.
+///
+/// # Returns
+/// - The best clips with their expected error, or an error if there are no inputs, or the kernels or the bias don't match.
+pub fn calibrate_conv2d(
+    inputs: &[Tensor3<i8>],
+    kernels: &Tensor4<i8>,
+    bias: Option<&[i32]>,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+) -> Result<Calibration, &'static str> {
+    if inputs.is_empty() {
+        return Err("No calibration inputs");
+    }
+    if bias.is_some_and(|b| b.len() != kernels.kernels()) {
+        return Err("Bias length doesn't match the number of kernels");
+    }
+    if inputs.iter().any(|i| i.channels() != kernels.channels()) {
+        return Err("Kernel channels don't match the input channels");
+    }
+    let padding = padding.unwrap_or(DEFAULT_PADDING);
+    let stride = stride.unwrap_or(DEFAULT_STRIDE);
+    if stride.x == 0 || stride.y == 0 {
+        return Err("Stride must be non-zero");
+    }
+
+    // Raw accumulators and the kernel each one belongs to
+    let mut accumulators: Vec<(i32, usize)> = Vec::new();
+    for input in inputs {
+        let output = reference_conv2d(input, kernels, &padding, &stride);
+        let plane = output.len() / kernels.kernels();
+        accumulators.extend(
+            output
+                .into_iter()
+                .enumerate()
+                .map(|(i, acc)| (acc, i / plane)),
+        );
+    }
+
+    let mut best: Option<(u64, Calibration)> = None;
+    for mac_clip in 0..=MAX_MAC_CLIP {
+        let layer_bias = bias.map(|b| scale_bias(b, mac_clip));
+        for pp_clip in 0..=MAX_USEFUL_PP_CLIP {
+            let (mut total, mut max_error, mut saturated) = (0u64, 0u64, 0);
+            for &(acc, kernel) in &accumulators {
+                let (exact, scaled) = match (bias, &layer_bias) {
+                    (Some(b), Some(l)) => (b[kernel], l[kernel]),
+                    _ => (0, 0),
+                };
+                let (out, sat) = simulate(acc, scaled, mac_clip, pp_clip);
+                let error = out.abs_diff(acc as i64 + exact as i64);
+                total += error;
+                max_error = max_error.max(error);
+                saturated += sat as usize;
+            }
+            if best
+                .as_ref()
+                .is_some_and(|(best_total, _)| *best_total <= total)
+            {
+                continue;
+            }
+            let calibration = Calibration {
+                mac_clip,
+                pp_clip,
+                bias: layer_bias.clone(),
+                mean_error: total as f32 / accumulators.len() as f32,
+                max_error,
+                saturated,
+                samples: accumulators.len(),
+            };
+            best = Some((total, calibration));
+        }
+    }
+    // At least one combination is always evaluated
+    Ok(best.unwrap().1)
+}
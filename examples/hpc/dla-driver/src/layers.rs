@@ -1,9 +1,14 @@
-use crate::tensor3::{Order3, Tensor3};
-use crate::tensor4::{Order4, Tensor4};
-use crate::{Dla, InputSize, KernelSize, LayerConfig, Padding, SimdBitMode, Stride};
+use crate::tensor3::{Order3, Tensor3, Tensor3View};
+use crate::tensor4::{Order4, PackedKernels, Tensor4, Tensor4View};
+use crate::{Dla, InputSize, KernelSize, LayerConfig, Padding, SimdBitMode, Stride, MAX_MAC_CLIP};
 use alloc::vec::Vec;
 
-use crate::utils::{calculate_conv2d_out_param_dim, get_banks_for_layer};
+use crate::mmap::MEMORY_BANK_COUNT;
+use crate::ops::{round, Axis3, QuantParams};
+use crate::utils::{
+    calculate_conv2d_out_param_dim, calculate_number_of_banks_needed, fit_bias_i32,
+    get_banks_for_layer, ScaledBias,
+};
 
 // Define a trait for output handling
 pub trait DlaOutput: Sized {
@@ -75,8 +80,9 @@ pub fn conv2d<T: DlaOutput + Clone>(
     simd_mode: Option<SimdBitMode>,
 ) -> Tensor3<T> {
     run_layers(
-        input, kernels, None, false, false, padding, stride, mac_clip, pp_clip, simd_mode,
+        input, kernels, None, None, false, padding, stride, mac_clip, pp_clip, simd_mode,
     )
+    .unwrap()
 }
 
 pub fn relu(input: Tensor3<i8>, pp_clip: Option<u32>) -> Tensor3<i8> {
@@ -95,7 +101,7 @@ pub fn relu(input: Tensor3<i8>, pp_clip: Option<u32>) -> Tensor3<i8> {
         input,
         kernels,
         None,
-        false,
+        None,
         true,
         None,
         None,
@@ -103,9 +109,15 @@ pub fn relu(input: Tensor3<i8>, pp_clip: Option<u32>) -> Tensor3<i8> {
         pp_clip,
         Some(SimdBitMode::EightBits),
     )
+    .unwrap()
 }
 
-pub fn bias(input: Tensor3<i8>, bias: Vec<i16>, pp_clip: Option<u32>) -> Tensor3<i8> {
+pub fn bias(
+    input: Tensor3<i8>,
+    bias: Vec<i16>,
+    bias_addr: Option<u32>,
+    pp_clip: Option<u32>,
+) -> Result<Tensor3<i8>, &'static str> {
     let kernel_buf = vec![1; input.get_size() * input.channels()]; // 1 filled kernels for constant conv2d
     let kernels: Tensor4<i8> = Tensor4::from_data_buffer(
         input.channels(),
@@ -121,7 +133,7 @@ pub fn bias(input: Tensor3<i8>, bias: Vec<i16>, pp_clip: Option<u32>) -> Tensor3
         input,
         kernels,
         Some(bias),
-        true,
+        bias_addr,
         false,
         None,
         None,
@@ -154,8 +166,9 @@ pub fn conv2d_relu<T: DlaOutput + Clone>(
     simd_mode: Option<SimdBitMode>,
 ) -> Tensor3<T> {
     run_layers(
-        input, kernels, None, false, true, padding, stride, mac_clip, pp_clip, simd_mode,
+        input, kernels, None, None, true, padding, stride, mac_clip, pp_clip, simd_mode,
     )
+    .unwrap()
 }
 
 /// Performs a 2D convolution + Bias operation with DLA.
@@ -164,6 +177,7 @@ pub fn conv2d_relu<T: DlaOutput + Clone>(
 /// - `input`: A 3-dimensional tensor of 8-bit signed integers (`Tensor3<i8>`) representing the input feature map.
 /// - `kernels`: A 4-dimensional tensor of 8-bit signed integers (`Tensor4<i8>`) representing the convolution kernels.
 /// - `bias`: A vector of 16-bit signed integers containing biases for each channel.
+/// - `bias_addr`: Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. Placed after the output banks if `None`.
 /// - `padding`: An optional `Padding` parameter defining the padding strategy applied to the input.
 /// - `stride`: An optional `Stride` parameter defining the stride of the convolution in X and Y directions.
 /// - `mac_clip`: An optional 32-bit unsigned integer (`u32`) specifying the amount of clipping after Conv2D operations.
@@ -171,23 +185,24 @@ pub fn conv2d_relu<T: DlaOutput + Clone>(
 /// - `simd_mode`: An optional `SimdBitMode` to control which SIMD instruction is used.
 ///
 /// # Returns
-/// - A 3-dimensional tensor of type `T` representing the output of the convolution operation.
+/// - A 3-dimensional tensor of type `T` representing the output of the convolution operation, or an error if `bias_addr` is `None` and there's no memory bank left for bias.
 /// ```
 pub fn conv2d_bias<T: DlaOutput + Clone>(
     input: Tensor3<i8>,
     kernels: Tensor4<i8>,
     bias: Vec<i16>,
+    bias_addr: Option<u32>,
     padding: Option<Padding>,
     stride: Option<Stride>,
     mac_clip: Option<u32>,
     pp_clip: Option<u32>,
     simd_mode: Option<SimdBitMode>,
-) -> Tensor3<T> {
+) -> Result<Tensor3<T>, &'static str> {
     run_layers(
         input,
         kernels,
         Some(bias),
-        true,
+        bias_addr,
         false,
         padding,
         stride,
@@ -202,6 +217,7 @@ pub fn conv2d_bias<T: DlaOutput + Clone>(
 /// - `input`: A 3-dimensional tensor of 8-bit signed integers (`Tensor3<i8>`) representing the input feature map.
 /// - `kernels`: A 4-dimensional tensor of 8-bit signed integers (`Tensor4<i8>`) representing the convolution kernels.
 /// - `bias`: A vector of 16-bit signed integers containing biases for each channel.
+/// - `bias_addr`: Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. Placed after the output banks if `None`.
 /// - `padding`: An optional `Padding` parameter defining the padding strategy applied to the input.
 /// - `stride`: An optional `Stride` parameter defining the stride of the convolution in X and Y directions.
 /// - `mac_clip`: An optional 32-bit unsigned integer (`u32`) specifying the amount of clipping after Conv2D operations.
@@ -209,23 +225,24 @@ pub fn conv2d_bias<T: DlaOutput + Clone>(
 /// - `simd_mode`: An optional `SimdBitMode` to control which SIMD instruction is used.
 ///
 /// # Returns
-/// - A 3-dimensional tensor of type `T` representing the output of the convolution operation.
+/// - A 3-dimensional tensor of type `T` representing the output of the convolution operation, or an error if `bias_addr` is `None` and there's no memory bank left for bias.
 /// ```
 pub fn conv2d_bias_relu<T: DlaOutput + Clone>(
     input: Tensor3<i8>,
     kernels: Tensor4<i8>,
     bias: Vec<i16>,
+    bias_addr: Option<u32>,
     padding: Option<Padding>,
     stride: Option<Stride>,
     mac_clip: Option<u32>,
     pp_clip: Option<u32>,
     simd_mode: Option<SimdBitMode>,
-) -> Tensor3<T> {
+) -> Result<Tensor3<T>, &'static str> {
     run_layers(
         input,
         kernels,
         Some(bias),
-        true,
+        bias_addr,
         true,
         padding,
         stride,
@@ -235,12 +252,71 @@ pub fn conv2d_bias_relu<T: DlaOutput + Clone>(
     )
 }
 
+/// Performs a 2D convolution + Bias operation with DLA using a 32-bit bias,
+/// e.g., from TVM.
+///
+/// DLA's bias is 16 bits wide, so the bias is scaled down with
+/// [fit_bias_i32] instead of being truncated, and the accumulator is shifted
+/// by the same amount through `mac_clip`. The output is in units of
+/// `2^(shift + pp_clip)` of the accumulator.
+///
+/// # Arguments
+/// - `input`, `kernels`: As in [conv2d_bias].
+/// - `bias`: 32-bit biases for each channel in the scale of the accumulator.
+/// - `bias_addr`: As in [conv2d_bias].
+/// - `relu_enabled`: Enables ReLU in the post-processing pipeline.
+/// - `padding`, `stride`, `pp_clip`, `simd_mode`: As in [conv2d_bias].
+/// - `mac_clip`: Smallest accumulator shift to use. Raised if the bias doesn't fit in 16 bits otherwise.
+///
+/// # Returns
+/// - The output and the scaled bias, whose `shift` is the `mac_clip` used and `max_error` the precision lost, or an error as in [conv2d_view].
+pub fn conv2d_bias_i32<T: DlaOutput + Clone>(
+    input: Tensor3<i8>,
+    kernels: Tensor4<i8>,
+    bias: &[i32],
+    bias_addr: Option<u32>,
+    relu_enabled: bool,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Result<(Tensor3<T>, ScaledBias), &'static str> {
+    assert_eq!(
+        bias.len(),
+        kernels.kernels(),
+        "Bias length doesn't match the number of kernels"
+    );
+    let mac_clip = mac_clip.unwrap_or(0);
+    assert!(mac_clip <= MAX_MAC_CLIP, "mac_clip too large");
+    let scaled = fit_bias_i32(bias, mac_clip);
+
+    let output = with_views(&input, &kernels, |input, kernels| {
+        run_batch(
+            core::iter::once(input),
+            kernels,
+            Some(&scaled.bias),
+            bias_addr,
+            relu_enabled,
+            padding,
+            stride,
+            Some(scaled.shift),
+            pp_clip,
+            simd_mode,
+        )
+    })?
+    .pop()
+    .unwrap();
+    Ok((output, scaled))
+}
+
 /// Performs a 2D grouped convolution + Bias operation with DLA.
 ///
 /// # Arguments
 /// - `input`: A 3-dimensional tensor of 8-bit signed integers (`Tensor3<i8>`) representing the input feature map.
 /// - `kernels`: A 4-dimensional tensor of 8-bit signed integers (`Tensor4<i8>`) representing the convolution kernels.
 /// - `bias`: A vector of 16-bit signed integers containing biases for each channel.
+/// - `bias_addr`: Address DLA reads bias from, e.g., in SDRAM to leave all memory banks for data. Placed after the output banks if `None`.
 /// - `padding`: An optional `Padding` parameter defining the padding strategy applied to the input.
 /// - `stride`: An optional `Stride` parameter defining the stride of the convolution in X and Y directions.
 /// - `mac_clip`: An optional 32-bit unsigned integer (`u32`) specifying the amount of clipping after Conv2D operations.
@@ -249,11 +325,12 @@ pub fn conv2d_bias_relu<T: DlaOutput + Clone>(
 /// - `groups`: Number of groups used.
 ///
 /// # Returns
-/// - A 3-dimensional tensor of type `T` representing the output of the convolution operation.
+/// - A 3-dimensional tensor of type `T` representing the output of the convolution operation, or an error if `bias_addr` is `None` and there's no memory bank left for bias.
 ///
 /// # Notes
 /// - The total number of input channels must be divisible by `groups`.
 /// - The total number of kernels must also be divisible by `groups`.
+/// - Each kernel has `input channels / groups` channels, e.g., 1 for depthwise convolution.
 /// - Padding and stride configurations are applied consistently across all groups.
 ///
 ///# Example
@@ -266,13 +343,14 @@ pub fn grouped_conv2d<T: DlaOutput + Clone>(
     input: Tensor3<i8>,
     kernels: Tensor4<i8>,
     bias: Vec<i16>,
+    bias_addr: Option<u32>,
     padding: Option<Padding>,
     stride: Option<Stride>,
     mac_clip: Option<u32>,
     pp_clip: Option<u32>,
     simd_mode: Option<SimdBitMode>,
     groups: usize,
-) -> Tensor3<T> {
+) -> Result<Tensor3<T>, &'static str> {
     let total_in_channels = input.channels();
     let group_in_channels = total_in_channels / groups;
     let group_out_channels = kernels.kernels() / groups;
@@ -282,42 +360,558 @@ pub fn grouped_conv2d<T: DlaOutput + Clone>(
 
     for g in 0..groups {
         let input_group = input.slice_channels(g * group_in_channels..(g + 1) * group_in_channels);
+        // NOTE: Tensor4::slice_channels slices the kernel axis
         let kernels_group =
-            kernels.slice_channels(g * group_in_channels..(g + 1) * group_in_channels);
+            kernels.slice_channels(g * group_out_channels..(g + 1) * group_out_channels);
         let bias_group = bias[g * group_out_channels..(g + 1) * group_out_channels].to_vec();
 
         let output_group = run_layers(
             input_group,
             kernels_group,
             Some(bias_group),
-            true,
+            bias_addr,
             false,
             padding.clone(),
             stride.clone(),
             mac_clip,
             pp_clip,
             simd_mode,
-        );
+        )?;
 
         output_tensors.push(output_group);
     }
 
-    // Concatenate the output tensors along the channel dimension
-    Tensor3::concat_interleaved(&output_tensors)
+    // Each group produces the next `group_out_channels` output channels
+    let (height, width) = (output_tensors[0].height(), output_tensors[0].width());
+    let views: Vec<Tensor3View<'_, T>> = output_tensors
+        .iter()
+        .map(|output| output.view().unwrap())
+        .collect();
+    let mut buffer = Vec::with_capacity(kernels.kernels() * height * width);
+    for row in 0..height {
+        for col in 0..width {
+            for view in views.iter() {
+                buffer.extend(
+                    (0..group_out_channels).map(|c| view.get(c, row, col).unwrap().clone()),
+                );
+            }
+        }
+    }
+    Tensor3::from_data_buffer(kernels.kernels(), height, width, buffer, Order3::HWC)
 }
 
-fn run_layers<T: DlaOutput + Clone>(
-    input: Tensor3<i8>,
+/// Largest post-processing shift usable for [add], as the bias is 16 bits wide
+const MAX_ADD_SHIFT: u32 = 15;
+
+/// Largest relative error allowed for the 8-bit weights of [add]
+const MAX_ADD_WEIGHT_ERROR: f32 = 1.0 / 32.0;
+
+/// Finds weights for `a` and `b`, a bias and a post-processing shift so that
+/// `(wa * a + wb * b + bias) >> shift` is the requantized sum. Returns `None`
+/// if no shift gives weights that are precise enough and a biased sum that
+/// fits in DLA's 16 bits for all inputs.
+fn map_add_to_dla(a: QuantParams, b: QuantParams, out: QuantParams) -> Option<(i8, i8, i16, u32)> {
+    let fa = a.scale / out.scale;
+    let fb = b.scale / out.scale;
+
+    // Prefer the largest shift, it gives the most precise weights
+    for shift in (0..=MAX_ADD_SHIFT).rev() {
+        let scale = (1u32 << shift) as f32;
+        let (ta, tb) = (fa * scale, fb * scale);
+        let (wa, wb) = (round(ta), round(tb));
+        if wa.abs() > i8::MAX as i32 || wb.abs() > i8::MAX as i32 {
+            continue;
+        }
+        // Smaller shifts only lose more precision
+        let error = |w: i32, t: f32| (w as f32 - t).abs() > t.abs() * MAX_ADD_WEIGHT_ERROR;
+        if wa == 0 || wb == 0 || error(wa, ta) || error(wb, tb) {
+            return None;
+        }
+
+        // Zero points and rounding are folded into the bias
+        let half = if shift > 0 { 1i64 << (shift - 1) } else { 0 };
+        let bias = ((out.zero_point as i64) << shift)
+            - wa as i64 * a.zero_point as i64
+            - wb as i64 * b.zero_point as i64
+            + half;
+        let Ok(bias) = i16::try_from(bias) else {
+            continue;
+        };
+
+        // DLA saturates the biased sum to 16 bits before the shift, which
+        // would clip outputs that are in range
+        let range = |w: i32| {
+            let (lo, hi) = (w * i8::MIN as i32, w * i8::MAX as i32);
+            (lo.min(hi), lo.max(hi))
+        };
+        let ((a_min, a_max), (b_min, b_max)) = (range(wa), range(wb));
+        let sum_min = a_min + b_min + bias as i32;
+        let sum_max = a_max + b_max + bias as i32;
+        if sum_min >= i16::MIN as i32 && sum_max <= i16::MAX as i32 {
+            return Some((wa as i8, wb as i8, bias, shift));
+        }
+    }
+    None
+}
+
+/// Element-wise `a + b` with requantization of both inputs, e.g., for
+/// residual connections.
+///
+/// The sum is computed on DLA as a 1x1 convolution over the channel-wise
+/// concatenation of `a` and `b`, where output channel `k` weights channel `k`
+/// of both inputs by their scale relative to `out`. Zero points are folded
+/// into the bias, which DLA adds before the post-processing shift.
+///
+/// Falls back to [Tensor3::add_requant] on the CPU when the scales can't be
+/// represented with 8-bit weights or the layer doesn't fit DLA's memory banks.
+///
+/// # Returns
+/// - The sum in the order of `a`, or an error if the dimensions of `a` and `b` differ.
+pub fn add(
+    a: &Tensor3<i8>,
+    b: &Tensor3<i8>,
+    a_quant: QuantParams,
+    b_quant: QuantParams,
+    out_quant: QuantParams,
+) -> Result<Tensor3<i8>, &'static str> {
+    if a.dimensions() != b.dimensions() {
+        return Err("Tensor dimensions don't match");
+    }
+    let (channels, height, width) = a.dimensions();
+
+    // Input, kernels, output and bias each start in their own bank
+    let banks = calculate_number_of_banks_needed(2 * channels * height * width)
+        + calculate_number_of_banks_needed(2 * channels * channels)
+        + calculate_number_of_banks_needed(channels * height * width)
+        + 1;
+
+    let mapping = map_add_to_dla(a_quant, b_quant, out_quant);
+    let (wa, wb, bias, shift) = match mapping {
+        Some(mapping) if banks <= MEMORY_BANK_COUNT => mapping,
+        _ => return a.add_requant(b, a_quant, b_quant, out_quant),
+    };
+
+    let input = Tensor3::concat(&[a.clone(), b.clone()], Axis3::Channels)?;
+
+    // Kernel `k` picks channel `k` of `a` and of `b`
+    let mut kernel_buf = vec![0; channels * 2 * channels];
+    for k in 0..channels {
+        kernel_buf[k * 2 * channels + k] = wa;
+        kernel_buf[k * 2 * channels + channels + k] = wb;
+    }
+    let kernels =
+        Tensor4::from_data_buffer(channels, 2 * channels, 1, 1, kernel_buf, Order4::KCHW)?;
+
+    let result: Tensor3<i8> = run_layers(
+        input,
+        kernels,
+        Some(vec![bias; channels]),
+        None,
+        false,
+        None,
+        None,
+        Some(0),
+        Some(shift),
+        Some(SimdBitMode::EightBits),
+    )?;
+    Tensor3::from_data_buffer(
+        channels,
+        height,
+        width,
+        result.to_buffer_with_order(a.order()),
+        a.order(),
+    )
+}
+
+/// Performs a 2D convolution with optional bias and ReLU on borrowed tensors.
+/// Data is streamed into DLA's memory banks in the order DLA reads it, so no
+/// intermediate buffers are allocated regardless of the layout of the views.
+///
+/// # Arguments
+/// - `input`: Input feature map in any order.
+/// - `kernels`: Convolution kernels in any order.
+/// - `bias`: Optional biases, one per kernel. Enables bias in the post-processing pipeline.
+/// - `bias_addr`: As in [conv2d_bias].
+/// - `relu_enabled`: Enables ReLU in the post-processing pipeline.
+/// - `padding`, `stride`, `mac_clip`, `pp_clip`, `simd_mode`: As in [conv2d].
+///
+/// # Returns
+/// - A 3-dimensional tensor of type `T` in HWC order, or an error if there's bias but no `bias_addr` and no memory bank left for it.
+pub fn conv2d_view<T: DlaOutput + Clone>(
+    input: Tensor3View<'_, i8>,
+    kernels: Tensor4View<'_, i8>,
+    bias: Option<&[i16]>,
+    bias_addr: Option<u32>,
+    relu_enabled: bool,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Result<Tensor3<T>, &'static str> {
+    let mut outputs = run_batch(
+        core::iter::once(input),
+        kernels,
+        bias,
+        bias_addr,
+        relu_enabled,
+        padding,
+        stride,
+        mac_clip,
+        pp_clip,
+        simd_mode,
+    )?;
+    Ok(outputs.pop().unwrap())
+}
+
+/// Performs a 2D convolution with optional bias and ReLU on a batch of
+/// inputs. Kernels and bias are uploaded once and stay resident in DLA's
+/// memory banks, only the inputs are written for each batch item.
+///
+/// # Arguments
+/// - `inputs`: Batch of feature maps, with the batch on the kernel axis, e.g., [Order4::KHWC] for NHWC.
+/// - `kernels`, `bias`, `bias_addr`, `relu_enabled`, `padding`, `stride`, `mac_clip`, `pp_clip`, `simd_mode`: As in [conv2d_view].
+///
+/// # Returns
+/// - A 4-dimensional tensor of type `T` in NHWC, i.e., [Order4::KHWC], order, or an error as in [conv2d_view].
+pub fn conv2d_batch_view<T: DlaOutput + Clone>(
+    inputs: Tensor4View<'_, i8>,
+    kernels: Tensor4View<'_, i8>,
+    bias: Option<&[i16]>,
+    bias_addr: Option<u32>,
+    relu_enabled: bool,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Result<Tensor4<T>, &'static str> {
+    assert!(inputs.kernels() > 0, "Batch must not be empty");
+    let outputs = run_batch(
+        (0..inputs.kernels()).map(|n| inputs.batch_item(n).unwrap()),
+        kernels,
+        bias,
+        bias_addr,
+        relu_enabled,
+        padding,
+        stride,
+        mac_clip,
+        pp_clip,
+        simd_mode,
+    )?;
+
+    let (channels, height, width) = outputs[0].dimensions();
+    let mut buffer = Vec::with_capacity(outputs.len() * channels * height * width);
+    for output in outputs.iter() {
+        buffer.extend(output.to_buffer_with_order(Order3::HWC));
+    }
+    Tensor4::from_data_buffer(outputs.len(), channels, height, width, buffer, Order4::KHWC)
+}
+
+/// Performs a 2D convolution operation with DLA on a batch of inputs. See
+/// [conv2d_batch_view].
+///
+/// # Arguments
+/// - `inputs`: Batch of feature maps, with the batch on the kernel axis, e.g., [Order4::KHWC] for NHWC.
+/// - Others: As in [conv2d].
+///
+/// # Returns
+/// - A 4-dimensional tensor of type `T` in NHWC, i.e., [Order4::KHWC], order.
+pub fn conv2d_batch<T: DlaOutput + Clone>(
+    inputs: Tensor4<i8>,
+    kernels: Tensor4<i8>,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Tensor4<T> {
+    run_layers_batch(
+        inputs, kernels, None, None, false, padding, stride, mac_clip, pp_clip, simd_mode,
+    )
+    .unwrap()
+}
+
+/// Performs a 2D convolution + ReLU operation with DLA on a batch of inputs.
+/// Arguments are the same as for [conv2d_batch].
+pub fn conv2d_relu_batch<T: DlaOutput + Clone>(
+    inputs: Tensor4<i8>,
+    kernels: Tensor4<i8>,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Tensor4<T> {
+    run_layers_batch(
+        inputs, kernels, None, None, true, padding, stride, mac_clip, pp_clip, simd_mode,
+    )
+    .unwrap()
+}
+
+/// Performs a 2D convolution + Bias operation with DLA on a batch of inputs.
+/// `bias` and `bias_addr` are as in [conv2d_bias], others as in [conv2d_batch].
+pub fn conv2d_bias_batch<T: DlaOutput + Clone>(
+    inputs: Tensor4<i8>,
+    kernels: Tensor4<i8>,
+    bias: Vec<i16>,
+    bias_addr: Option<u32>,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Result<Tensor4<T>, &'static str> {
+    run_layers_batch(
+        inputs,
+        kernels,
+        Some(bias),
+        bias_addr,
+        false,
+        padding,
+        stride,
+        mac_clip,
+        pp_clip,
+        simd_mode,
+    )
+}
+
+/// Performs a 2D convolution + Bias + ReLU operation with DLA on a batch of
+/// inputs. Arguments are the same as for [conv2d_bias_batch].
+pub fn conv2d_bias_relu_batch<T: DlaOutput + Clone>(
+    inputs: Tensor4<i8>,
+    kernels: Tensor4<i8>,
+    bias: Vec<i16>,
+    bias_addr: Option<u32>,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Result<Tensor4<T>, &'static str> {
+    run_layers_batch(
+        inputs,
+        kernels,
+        Some(bias),
+        bias_addr,
+        true,
+        padding,
+        stride,
+        mac_clip,
+        pp_clip,
+        simd_mode,
+    )
+}
+
+/// Performs [relu] on a batch of inputs, with the batch on the kernel axis
+pub fn relu_batch(inputs: Tensor4<i8>, pp_clip: Option<u32>) -> Tensor4<i8> {
+    let kernels = constant_kernels(&inputs);
+    run_layers_batch(
+        inputs,
+        kernels,
+        None,
+        None,
+        true,
+        None,
+        None,
+        Some(0),
+        pp_clip,
+        Some(SimdBitMode::EightBits),
+    )
+    .unwrap()
+}
+
+/// Performs [bias] on a batch of inputs, with the batch on the kernel axis.
+/// `bias_addr` is as in [conv2d_bias].
+pub fn bias_batch(
+    inputs: Tensor4<i8>,
+    bias: Vec<i16>,
+    bias_addr: Option<u32>,
+    pp_clip: Option<u32>,
+) -> Result<Tensor4<i8>, &'static str> {
+    let kernels = constant_kernels(&inputs);
+    run_layers_batch(
+        inputs,
+        kernels,
+        Some(bias),
+        bias_addr,
+        false,
+        None,
+        None,
+        Some(0),
+        pp_clip,
+        Some(SimdBitMode::EightBits),
+    )
+}
+
+/// Performs [dense] on a batch of inputs, with the batch on the kernel axis.
+/// Returns the outputs of each batch item, or an error if `weights` doesn't
+/// match `outputs` and the input shape.
+pub fn dense_batch(
+    outputs: usize,
+    inputs: Tensor4<i8>,
+    weights: Vec<i8>,
+) -> Result<Vec<Vec<i32>>, &'static str> {
+    if outputs == 0 {
+        return Err("Dense layer must have outputs");
+    }
+    let kernels = Tensor4::from_data_buffer(
+        outputs,
+        inputs.channels(),
+        inputs.height(),
+        inputs.width(),
+        weights,
+        Order4::KCHW,
+    )?;
+
+    let output: Tensor4<i32> = conv2d_batch(inputs, kernels, None, None, None, None, None);
+    Ok(output
+        .to_buffer_with_order(Order4::KHWC)
+        .chunks_exact(outputs)
+        .map(|item| item.to_vec())
+        .collect())
+}
+
+/// 1 filled kernels for the constant Conv2D of [relu_batch] and [bias_batch]
+fn constant_kernels(inputs: &Tensor4<i8>) -> Tensor4<i8> {
+    let size = inputs.channels() * inputs.height() * inputs.width();
+    Tensor4::from_data_buffer(
+        inputs.channels(),
+        inputs.channels(),
+        inputs.height(),
+        inputs.width(),
+        vec![1; size * inputs.channels()],
+        Order4::HWKC,
+    )
+    .unwrap()
+}
+
+/// Batched [run_layers], which fails the same way
+fn run_layers_batch<T: DlaOutput + Clone>(
+    inputs: Tensor4<i8>,
     kernels: Tensor4<i8>,
     bias: Option<Vec<i16>>,
-    bias_enabled: bool,
+    bias_addr: Option<u32>,
     relu_enabled: bool,
     padding: Option<Padding>,
     stride: Option<Stride>,
     mac_clip: Option<u32>,
     pp_clip: Option<u32>,
     simd_mode: Option<SimdBitMode>,
-) -> Tensor3<T> {
+) -> Result<Tensor4<T>, &'static str> {
+    let inputs_buffer;
+    let inputs_view = match inputs.view() {
+        Some(view) => view,
+        None => {
+            inputs_buffer = inputs.to_buffer_with_order(Order4::KHWC);
+            Tensor4View::new(
+                inputs.kernels(),
+                inputs.channels(),
+                inputs.height(),
+                inputs.width(),
+                &inputs_buffer,
+                Order4::KHWC,
+            )
+            .unwrap()
+        }
+    };
+    let kernels_buffer;
+    let kernels_view = match kernels.view() {
+        Some(view) => view,
+        None => {
+            kernels_buffer = PackedKernels::new(kernels);
+            kernels_buffer.view()
+        }
+    };
+
+    conv2d_batch_view(
+        inputs_view,
+        kernels_view,
+        bias.as_deref(),
+        bias_addr,
+        relu_enabled,
+        padding,
+        stride,
+        mac_clip,
+        pp_clip,
+        simd_mode,
+    )
+}
+
+/// Runs the same layer on each of `inputs`, writing kernels and bias only for
+/// the first one. Bias is placed after the output banks unless `bias_addr` is
+/// given, which fails if the output reaches the last bank.
+fn run_batch<'a, T: DlaOutput + Clone>(
+    inputs: impl Iterator<Item = Tensor3View<'a, i8>>,
+    kernels: Tensor4View<'_, i8>,
+    bias: Option<&[i16]>,
+    bias_addr: Option<u32>,
+    relu_enabled: bool,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Result<Vec<Tensor3<T>>, &'static str> {
+    let dla = Dla::new();
+    let mut outputs = Vec::new();
+
+    for input in inputs {
+        // Banks keep their contents between layers, so kernels and bias stay
+        // resident for the rest of the batch, sa. examples/batch.rs
+        let output_size = run_layer(
+            &dla,
+            &input,
+            &kernels,
+            bias,
+            bias_addr,
+            outputs.is_empty(),
+            relu_enabled,
+            padding.clone(),
+            stride.clone(),
+            mac_clip,
+            pp_clip,
+            simd_mode,
+        )?;
+
+        let output_buffer = T::read_output(&dla, output_size.0 * output_size.1 * kernels.kernels());
+
+        outputs.push(
+            Tensor3::from_data_buffer(
+                kernels.kernels(),
+                output_size.1,
+                output_size.0,
+                output_buffer,
+                Order3::HWC, // NOTE: (20240610 vaino-waltteri.granat@tuni.fi) This might not be true on ASIC
+            )
+            .unwrap(),
+        );
+    }
+    Ok(outputs)
+}
+
+/// Configures DLA for one Conv2D layer, writes its data and waits until the
+/// result is in the output bank. Kernels and bias are only written when
+/// `write_weights` is set, otherwise the ones left by the previous layer are
+/// used.
+///
+/// # Returns
+/// - Output width and height, or an error if there's bias but no `bias_addr` and no memory bank left for it
+fn run_layer(
+    dla: &Dla,
+    input: &Tensor3View<'_, i8>,
+    kernels: &Tensor4View<'_, i8>,
+    bias: Option<&[i16]>,
+    bias_addr: Option<u32>,
+    write_weights: bool,
+    relu_enabled: bool,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Result<(usize, usize), &'static str> {
+    let bias_enabled = bias.is_some();
     let output_size = calculate_conv2d_out_param_dim(
         (input.width() as u32, input.height() as u32),
         (kernels.width() as u32, kernels.height() as u32),
@@ -325,20 +919,23 @@ fn run_layers<T: DlaOutput + Clone>(
         stride.clone(),
     );
 
-    let dla = Dla::new();
-
     let banks = get_banks_for_layer(
         input.get_size(),
         kernels.get_size(),
         output_size.0 * output_size.1,
     );
 
+    let bias_addr = bias_addr.or(banks.3);
+    if bias_enabled && bias_addr.is_none() {
+        return Err("No memory bank left for bias, place it outside the banks");
+    }
+
     // Initalize layer
     let config = LayerConfig {
         input_bank: Some(banks.0),  // b
         kernel_bank: Some(banks.1), // a
         output_bank: Some(banks.2),
-        bias_addr: banks.3,
+        bias_addr,
         pp_enabled: relu_enabled || bias_enabled,
         relu_enabled,
         bias_enabled,
@@ -362,11 +959,14 @@ fn run_layers<T: DlaOutput + Clone>(
 
     dla.init_layer(config);
 
-    dla.write_input(&mut input.to_buffer_with_order(Order3::HWC));
-    dla.write_kernel(&mut kernels.to_buffer_with_order(Order4::HWKC));
+    dla.write_input_view(input);
+
+    if write_weights {
+        dla.write_kernel_view(kernels);
 
-    if let Some(bias) = bias {
-        dla.write_bias(&bias)
+        if let Some(bias) = bias {
+            dla.write_bias(bias)
+        }
     }
 
     // Mark data ready to start calculations
@@ -375,14 +975,148 @@ fn run_layers<T: DlaOutput + Clone>(
 
     while !dla.handle_handshake() {}
 
-    let output_buffer = T::read_output(&dla, output_size.0 * output_size.1 * kernels.kernels());
+    Ok(output_size)
+}
 
-    Tensor3::from_data_buffer(
-        kernels.kernels(),
-        output_size.1,
-        output_size.0,
-        output_buffer,
-        Order3::HWC, // NOTE: (20240610 vaino-waltteri.granat@tuni.fi) This might not be true on ASIC
+/// Performs a 2D convolution with optional bias and ReLU using kernels packed
+/// once with [PackedKernels::new], which makes it suitable for weights reused
+/// across inferences.
+///
+/// Arguments are the same as for [conv2d_view].
+pub fn conv2d_packed<T: DlaOutput + Clone>(
+    input: Tensor3View<'_, i8>,
+    kernels: &PackedKernels,
+    bias: Option<&[i16]>,
+    bias_addr: Option<u32>,
+    relu_enabled: bool,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Result<Tensor3<T>, &'static str> {
+    conv2d_view(
+        input,
+        kernels.view(),
+        bias,
+        bias_addr,
+        relu_enabled,
+        padding,
+        stride,
+        mac_clip,
+        pp_clip,
+        simd_mode,
     )
-    .unwrap()
+}
+
+/// Performs a 2D convolution with optional bias and ReLU using packed kernels
+/// like [conv2d_packed], but reads the 8-bit result straight into `output`
+/// in HWC order without intermediate buffers.
+///
+/// # Arguments
+/// - `output`: Buffer for the result in HWC order. Must be exactly the size of the output.
+/// - Others: As in [conv2d_view].
+///
+/// # Returns
+/// - An error as in [conv2d_view].
+pub fn conv2d_packed_into(
+    input: Tensor3View<'_, i8>,
+    kernels: &PackedKernels,
+    bias: Option<&[i16]>,
+    bias_addr: Option<u32>,
+    relu_enabled: bool,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    output: &mut [i8],
+) -> Result<(), &'static str> {
+    let dla = Dla::new();
+    let kernels = kernels.view();
+    let output_size = run_layer(
+        &dla,
+        &input,
+        &kernels,
+        bias,
+        bias_addr,
+        true,
+        relu_enabled,
+        padding,
+        stride,
+        mac_clip,
+        pp_clip,
+        None,
+    )?;
+    assert_eq!(
+        output.len(),
+        output_size.0 * output_size.1 * kernels.kernels(),
+        "Output buffer doesn't match the layer output"
+    );
+    dla.read_output_i8_into(output);
+    Ok(())
+}
+
+/// Runs a layer on owned tensors. Only fails if there's bias but no
+/// `bias_addr` and no memory bank left for it, so layers without bias unwrap
+/// the result.
+fn run_layers<T: DlaOutput + Clone>(
+    input: Tensor3<i8>,
+    kernels: Tensor4<i8>,
+    bias: Option<Vec<i16>>,
+    bias_addr: Option<u32>,
+    relu_enabled: bool,
+    padding: Option<Padding>,
+    stride: Option<Stride>,
+    mac_clip: Option<u32>,
+    pp_clip: Option<u32>,
+    simd_mode: Option<SimdBitMode>,
+) -> Result<Tensor3<T>, &'static str> {
+    with_views(&input, &kernels, |input, kernels| {
+        conv2d_view(
+            input,
+            kernels,
+            bias.as_deref(),
+            bias_addr,
+            relu_enabled,
+            padding,
+            stride,
+            mac_clip,
+            pp_clip,
+            simd_mode,
+        )
+    })
+}
+
+/// Calls `f` with views of `input` and `kernels`. Tensors that can't be viewed
+/// in place are copied once into HWC/HWKC.
+fn with_views<R>(
+    input: &Tensor3<i8>,
+    kernels: &Tensor4<i8>,
+    f: impl FnOnce(Tensor3View<'_, i8>, Tensor4View<'_, i8>) -> R,
+) -> R {
+    let input_buffer;
+    let input_view = match input.view() {
+        Some(view) => view,
+        None => {
+            input_buffer = input.to_buffer_with_order(Order3::HWC);
+            Tensor3View::new(
+                input.channels(),
+                input.height(),
+                input.width(),
+                &input_buffer,
+                Order3::HWC,
+            )
+            .unwrap()
+        }
+    };
+    let kernels_buffer;
+    let kernels_view = match kernels.view() {
+        Some(view) => view,
+        None => {
+            kernels_buffer = PackedKernels::new(kernels);
+            kernels_buffer.view()
+        }
+    };
+
+    f(input_view, kernels_view)
 }
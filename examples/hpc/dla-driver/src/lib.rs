@@ -6,7 +6,9 @@
 #[macro_use]
 extern crate alloc;
 
+pub mod calibration;
 pub mod layers;
+pub mod ops;
 pub mod tensor3;
 pub mod tensor4;
 pub mod utils;
@@ -47,10 +49,17 @@ const DEFAULT_MAC_CLIP: u32 = 0;
 const DEFAULT_PP_CLIP: u32 = 8;
 const DEFAULT_SIMD_MODE: SimdBitMode = SimdBitMode::EightBits;
 
+/// Largest supported `mac_clip`
+pub const MAX_MAC_CLIP: u32 = 21;
+/// Largest supported `pp_clip`
+pub const MAX_PP_CLIP: u32 = 0x1F;
+
 use alloc::vec::Vec;
 use core::ptr;
 use headsail_bsp::{sprint, sprintln};
 use mmap::*;
+use tensor3::{Order3, Tensor3View};
+use tensor4::{Order4, Tensor4View};
 
 /// Clip error type
 struct InvalidClip(u32);
@@ -259,20 +268,40 @@ impl Dla {
     }
 
     /// Writes buffer DLA's data bank(s) based on offset
-    pub fn write_data_bank(&self, offset: usize, buf: &mut [i8]) {
+    pub fn write_data_bank(&self, offset: usize, buf: &[i8]) {
+        for (cidx, chunk) in buf.chunks(8).enumerate() {
+            self.write_data_bank_chunk(offset + cidx * 8, chunk);
+        }
+    }
+
+    /// Writes elements of `data` to DLA's data bank(s) based on offset as
+    /// they are produced, without collecting them into a buffer first
+    pub fn write_data_bank_iter(&self, offset: usize, data: impl Iterator<Item = i8>) {
+        let mut chunk = [0; 8];
+        let mut len = 0;
+        let mut cidx = 0;
+        for b in data {
+            chunk[len] = b;
+            len += 1;
+            if len == chunk.len() {
+                self.write_data_bank_chunk(offset + cidx * 8, &chunk);
+                cidx += 1;
+                len = 0;
+            }
+        }
+        if len > 0 {
+            self.write_data_bank_chunk(offset + cidx * 8, &chunk[..len]);
+        }
+    }
+
+    /// Writes up to 64 bits to DLA's data bank(s) at offset
+    fn write_data_bank_chunk(&self, offset: usize, chunk: &[i8]) {
         /* NOTE:(20240604 vaino-waltteri.granat@tuni.fi)
          * After RTL test examination, it was found that DLA needs to
          * be written by reversing the order of bytes in each 64-bit chunk
          */
-        for (cidx, chunk) in buf.chunks(8).enumerate() {
-            for (i, b) in chunk.iter().rev().enumerate() {
-                unsafe {
-                    ptr::write_volatile(
-                        (MEMORY_BANK_BASE_ADDR + offset + cidx * 8 + i) as *mut _,
-                        *b,
-                    )
-                };
-            }
+        for (i, b) in chunk.iter().rev().enumerate() {
+            unsafe { ptr::write_volatile((MEMORY_BANK_BASE_ADDR + offset + i) as *mut _, *b) };
         }
     }
 
@@ -345,8 +374,21 @@ impl Dla {
 
     /// Reads len amount of bytes from DLA's output bank(s)
     pub fn read_output_i8(&self, len: usize) -> Vec<i8> {
-        let bytes = self.read_data_bank(self.get_output_bank(), len);
-        bytes.iter().map(|&x| x as i8).collect()
+        let mut result = vec![0; len];
+        self.read_output_i8_into(&mut result);
+        result
+    }
+
+    /// Fills `output` with bytes from DLA's output bank(s)
+    pub fn read_output_i8_into(&self, output: &mut [i8]) {
+        let bank = self.get_output_bank();
+        for (idx, chunk) in output.chunks_mut(16).enumerate() {
+            // Copy everything from one 128-bit address
+            let data = self.read_data_bank_offset(bank, idx * 0x10);
+            for (i, byte) in chunk.iter_mut().enumerate() {
+                *byte = (data >> (i * 8)) as i8;
+            }
+        }
     }
 
     /// Reads len amount of bytes from DLA's output bank(s)
@@ -389,20 +431,37 @@ impl Dla {
     }
 
     /// Writes buffer to DLA's input bank(s)
-    pub fn write_input(&self, input: &mut [i8]) {
+    pub fn write_input(&self, input: &[i8]) {
         // TODO optimize memory bank logic
         let offset = self.get_input_bank().offset();
         self.write_data_bank(offset, input);
     }
 
     /// Writes buffer to DLA's kernel bank(s)
-    pub fn write_kernel(&self, kernel: &mut [i8]) {
+    pub fn write_kernel(&self, kernel: &[i8]) {
         // TODO optimize memory bank logic
         self.write_data_bank(self.get_kernel_bank().offset(), kernel)
     }
 
+    /// Streams a feature map to DLA's input bank(s) in HWC order
+    pub fn write_input_view(&self, input: &Tensor3View<'_, i8>) {
+        self.write_data_bank_iter(
+            self.get_input_bank().offset(),
+            input.iter_with_order(Order3::HWC),
+        )
+    }
+
+    /// Streams kernels to DLA's kernel bank(s) in HWKC order
+    pub fn write_kernel_view(&self, kernels: &Tensor4View<'_, i8>) {
+        self.write_data_bank_iter(
+            self.get_kernel_bank().offset(),
+            kernels.iter_with_order(Order4::HWKC),
+        )
+    }
+
+    /// Writes bias to the address set with [Dla::set_bias_addr], which may be
+    /// in a memory bank or anywhere else DLA can read
     pub fn write_bias(&self, bias: &[i16]) {
-        // TODO Add support for writing to arbitrary memory location instead of dla memory banks
         let mut bytes = Vec::with_capacity(bias.len() * 2);
         for &x in bias {
             bytes.push((x & 0xFF) as u8);
@@ -717,7 +776,7 @@ impl Dla {
     /// Sets clipping after conv2d
     fn set_mac_clip(&self, clip_amount: u32) -> Result<(), InvalidClip> {
         // Cap clipping amount
-        if clip_amount > 21 {
+        if clip_amount > MAX_MAC_CLIP {
             return Err(InvalidClip(clip_amount));
         }
         let mut reg = self.read_u32(DLA_MAC_CTRL);
@@ -729,7 +788,7 @@ impl Dla {
     /// Sets clipping after post-processing
     fn set_pp_clip(&self, clip_amount: u32) -> Result<(), InvalidClip> {
         // Cap clipping amount
-        if clip_amount > 0x1F {
+        if clip_amount > MAX_PP_CLIP {
             return Err(InvalidClip(clip_amount));
         }
         let mut reg = self.read_u32(DLA_PP_CTRL);
@@ -756,12 +815,15 @@ impl Dla {
         !get_bits!(status, DLA_BUF_DONE_BITMASK) != 0
     }
 
-    /// Sets external memory address containing bias data for post-processing
-    fn set_bias_addr(&self, addr: u32) {
+    /// Sets external memory address containing bias data for post-processing.
+    /// The address is as seen by DLA, e.g., in SDRAM to leave all memory banks
+    /// for data. Overridden by [Dla::init_layer].
+    pub fn set_bias_addr(&self, addr: u32) {
         self.write_u32(DLA_PP_AXI_READ, addr);
     }
 
-    fn get_bias_addr(&self) -> u32 {
+    /// Returns the address DLA reads bias from
+    pub fn get_bias_addr(&self) -> u32 {
         let mut reg = self.read_u32(DLA_PP_AXI_READ);
         reg = get_bits!(reg, DLA_PP_AXI_READ_ADDRESS_BITMASK);
         reg
@@ -873,6 +935,21 @@ impl Dla {
         self.write_u32(DLA_HANDSHAKE, reg);
     }
 
+    /// Waits for the DLA to finish the current layer and performs the handshake
+    ///
+    /// Async version of `while !dla.handle_handshake() {}`, which sleeps on the
+    /// DLA interrupt in between the checks. The VP DLA model has no interrupt
+    /// line, so with the "vp" feature the task yields to the executor instead.
+    #[cfg(feature = "async")]
+    pub async fn wait_handshake(&self) {
+        while !self.handle_handshake() {
+            #[cfg(feature = "vp")]
+            yield_now().await;
+            #[cfg(not(feature = "vp"))]
+            headsail_bsp::wait_for_interrupt(headsail_bsp::Interrupt::Dla).await;
+        }
+    }
+
     /// Configures the next layer in dla
     ///
     /// # Examples
@@ -924,3 +1001,19 @@ impl Dla {
         }
     }
 }
+
+/// Returns `Pending` once, letting the executor run other tasks before the
+/// caller is polled again
+#[cfg(all(feature = "async", feature = "vp"))]
+async fn yield_now() {
+    let mut yielded = false;
+    core::future::poll_fn(|cx| {
+        if yielded {
+            return core::task::Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        core::task::Poll::Pending
+    })
+    .await
+}
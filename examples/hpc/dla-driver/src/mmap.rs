@@ -20,6 +20,7 @@ pub const EXTERNAL_BIT: usize = 0x100000000;
 pub const EXTERNAL_BIT: usize = 0x0;
 
 pub const MEMORY_BANK_SIZE: usize = 0x8000;
+pub const MEMORY_BANK_COUNT: usize = 16;
 pub const MEMORY_BANK_0_OFFSET: usize = 0x00000;
 pub const MEMORY_BANK_1_OFFSET: usize = 0x08000;
 pub const MEMORY_BANK_2_OFFSET: usize = 0x10000;
@@ -0,0 +1,307 @@
+//! CPU-side tensor operations for use between DLA layers
+
+use crate::tensor3::{Order3, Tensor3};
+use alloc::vec::Vec;
+use ndarray::{s, Array, Array3, Axis};
+
+/// Logical axis of a [Tensor3]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis3 {
+    Channels,
+    Height,
+    Width,
+}
+
+/// Quantization parameters of a tensor, `real = scale * (q - zero_point)`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantParams {
+    pub scale: f32,
+    pub zero_point: i32,
+}
+
+impl QuantParams {
+    /// Parameters under which quantized operations become plain saturating arithmetic
+    pub const IDENTITY: QuantParams = QuantParams {
+        scale: 1.0,
+        zero_point: 0,
+    };
+}
+
+/// Integer element types supported by the quantized operations
+pub trait QuantInt: Copy {
+    fn to_i32(self) -> i32;
+    fn from_i32_saturating(value: i32) -> Self;
+}
+
+impl QuantInt for i8 {
+    fn to_i32(self) -> i32 {
+        self as i32
+    }
+    fn from_i32_saturating(value: i32) -> Self {
+        value.clamp(i8::MIN as i32, i8::MAX as i32) as i8
+    }
+}
+
+impl QuantInt for i16 {
+    fn to_i32(self) -> i32 {
+        self as i32
+    }
+    fn from_i32_saturating(value: i32) -> Self {
+        value.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+    }
+}
+
+/// Rounds half away from zero, `f32::round` is not available in core
+pub(crate) fn round(value: f32) -> i32 {
+    if value < 0.0 {
+        (value - 0.5) as i32
+    } else {
+        (value + 0.5) as i32
+    }
+}
+
+/// Rounds half up, i.e., `floor(value + 0.5)`, like DLA's post-processing
+/// shift does with a bias of half
+pub(crate) fn round_half_up(value: f32) -> i32 {
+    let shifted = value + 0.5;
+    let truncated = shifted as i32;
+    if (truncated as f32) > shifted {
+        truncated - 1
+    } else {
+        truncated
+    }
+}
+
+/// exp(-x) for x >= 0, with input and output in Q16
+fn exp_neg_q16(x: u32) -> u32 {
+    const ONE: i64 = 1 << 16;
+    const LN2: u32 = 45426; // ln(2) in Q16
+
+    // e^-x = 2^-k * e^-r, with r in [0, ln(2))
+    let k = x / LN2;
+    if k > 16 {
+        return 0;
+    }
+    let r = (x - k * LN2) as i64;
+
+    // Taylor series up to r^4, error is below 2^-9 for r < ln(2)
+    let r2 = (r * r) >> 16;
+    let r3 = (r2 * r) >> 16;
+    let r4 = (r3 * r) >> 16;
+    let e = ONE - r + r2 / 2 - r3 / 6 + r4 / 24;
+    (e >> k) as u32
+}
+
+impl<T: Clone> Tensor3<T> {
+    /// Creates a tensor in the given order from a CHW array
+    fn from_chw_array(data: Array3<T>, order: Order3) -> Self {
+        let (channels, height, width) = data.dim();
+        let tensor = Tensor3::from_array3(data, Order3::CHW);
+        if order == Order3::CHW {
+            return tensor;
+        }
+        let buffer = tensor.to_buffer_with_order(order);
+        Tensor3::from_data_buffer(channels, height, width, buffer, order).unwrap()
+    }
+
+    /// Copies the tensor into a CHW array
+    fn to_chw_array(&self) -> Array3<T> {
+        Array::from_shape_vec(
+            (self.channels(), self.height(), self.width()),
+            self.to_buffer_with_order(Order3::CHW),
+        )
+        .unwrap()
+    }
+
+    /// Concatenates tensors along `axis`. All other dimensions must match. The
+    /// result has the order of the first tensor.
+    pub fn concat(tensors: &[Tensor3<T>], axis: Axis3) -> Result<Tensor3<T>, &'static str> {
+        let first = tensors.first().ok_or("No tensors to concatenate")?;
+        let (channels, height, width) = first.dimensions();
+        let mut total = 0;
+        for t in tensors {
+            let (c, h, w) = t.dimensions();
+            let (matches, len) = match axis {
+                Axis3::Channels => (h == height && w == width, c),
+                Axis3::Height => (c == channels && w == width, h),
+                Axis3::Width => (c == channels && h == height, w),
+            };
+            if !matches {
+                return Err("Tensor dimensions don't match outside the concatenation axis");
+            }
+            total += len;
+        }
+
+        let dims = match axis {
+            Axis3::Channels => (total, height, width),
+            Axis3::Height => (channels, total, width),
+            Axis3::Width => (channels, height, total),
+        };
+        let buffers: Vec<Vec<T>> = tensors
+            .iter()
+            .map(|t| t.to_buffer_with_order(Order3::CHW))
+            .collect();
+        let mut data = Vec::with_capacity(dims.0 * dims.1 * dims.2);
+        match axis {
+            Axis3::Channels => buffers.iter().for_each(|b| data.extend_from_slice(b)),
+            Axis3::Height => {
+                for c in 0..channels {
+                    for (t, b) in tensors.iter().zip(buffers.iter()) {
+                        let plane = t.height() * width;
+                        data.extend_from_slice(&b[c * plane..(c + 1) * plane]);
+                    }
+                }
+            }
+            Axis3::Width => {
+                for row in 0..channels * height {
+                    for (t, b) in tensors.iter().zip(buffers.iter()) {
+                        let w = t.width();
+                        data.extend_from_slice(&b[row * w..(row + 1) * w]);
+                    }
+                }
+            }
+        }
+        let data = Array::from_shape_vec(dims, data).unwrap();
+        Ok(Tensor3::from_chw_array(data, first.order()))
+    }
+
+    /// Pads `axis` with `before` and `after` elements of `value`
+    pub fn pad(&self, axis: Axis3, before: usize, after: usize, value: T) -> Tensor3<T> {
+        let (channels, height, width) = self.dimensions();
+        let dims = match axis {
+            Axis3::Channels => (channels + before + after, height, width),
+            Axis3::Height => (channels, height + before + after, width),
+            Axis3::Width => (channels, height, width + before + after),
+        };
+        let mut data = Array3::from_elem(dims, value);
+        let src = self.to_chw_array();
+        match axis {
+            Axis3::Channels => data.slice_mut(s![before..before + channels, .., ..]),
+            Axis3::Height => data.slice_mut(s![.., before..before + height, ..]),
+            Axis3::Width => data.slice_mut(s![.., .., before..before + width]),
+        }
+        .assign(&src);
+        Tensor3::from_chw_array(data, self.order())
+    }
+
+    /// Flattens the tensor in its current order into a (size, 1, 1) tensor,
+    /// e.g., as the input of `layers::dense`
+    pub fn flatten(&self) -> Tensor3<T> {
+        Tensor3::from_data_buffer(self.get_size(), 1, 1, self.to_buffer(), Order3::CHW).unwrap()
+    }
+}
+
+impl<T: Clone + PartialOrd> Tensor3<T> {
+    /// Returns the (channel, row, column) of the largest element. Ties resolve
+    /// to the first element in CHW order.
+    pub fn argmax(&self) -> Option<(usize, usize, usize)> {
+        self.top_k(1).first().map(|(pos, _)| *pos)
+    }
+
+    /// Returns the `k` largest elements and their (channel, row, column) in
+    /// descending order. Ties resolve to the first element in CHW order.
+    pub fn top_k(&self, k: usize) -> Vec<((usize, usize, usize), T)> {
+        let (_, height, width) = self.dimensions();
+        let mut top: Vec<((usize, usize, usize), T)> = Vec::with_capacity(k + 1);
+        for (i, x) in self
+            .to_buffer_with_order(Order3::CHW)
+            .into_iter()
+            .enumerate()
+        {
+            // Insert after equal elements to keep the first occurrence first
+            let at = top.iter().position(|(_, y)| x > *y).unwrap_or(top.len());
+            if at < k {
+                let pos = (i / (height * width), (i / width) % height, i % width);
+                top.insert(at, (pos, x));
+                top.truncate(k);
+            }
+        }
+        top
+    }
+}
+
+impl<T: QuantInt> Tensor3<T> {
+    /// Applies `f` element-wise with `other`, which is converted to the order of `self`
+    fn zip_map(
+        &self,
+        other: &Tensor3<T>,
+        f: impl Fn(i32, i32) -> i32,
+    ) -> Result<Tensor3<T>, &'static str> {
+        if self.dimensions() != other.dimensions() {
+            return Err("Tensor dimensions don't match");
+        }
+        let (channels, height, width) = self.dimensions();
+        let buffer = self
+            .to_buffer()
+            .into_iter()
+            .zip(other.to_buffer_with_order(self.order()))
+            .map(|(a, b)| T::from_i32_saturating(f(a.to_i32(), b.to_i32())))
+            .collect();
+        Tensor3::from_data_buffer(channels, height, width, buffer, self.order())
+    }
+
+    /// Saturating element-wise add with requantization, e.g., for residual
+    /// connections. The result has the order of `self`. Rounds half up, which
+    /// matches [add](crate::layers::add) on DLA.
+    pub fn add_requant(
+        &self,
+        other: &Tensor3<T>,
+        a: QuantParams,
+        b: QuantParams,
+        out: QuantParams,
+    ) -> Result<Tensor3<T>, &'static str> {
+        let a_scale = a.scale / out.scale;
+        let b_scale = b.scale / out.scale;
+        self.zip_map(other, |x, y| {
+            round_half_up((x - a.zero_point) as f32 * a_scale + (y - b.zero_point) as f32 * b_scale)
+                .saturating_add(out.zero_point)
+        })
+    }
+
+    /// Saturating element-wise multiply with requantization. The result has
+    /// the order of `self`.
+    pub fn mul_requant(
+        &self,
+        other: &Tensor3<T>,
+        a: QuantParams,
+        b: QuantParams,
+        out: QuantParams,
+    ) -> Result<Tensor3<T>, &'static str> {
+        let scale = a.scale * b.scale / out.scale;
+        self.zip_map(other, |x, y| {
+            let product = (x - a.zero_point) as i64 * (y - b.zero_point) as i64;
+            round(product as f32 * scale).saturating_add(out.zero_point)
+        })
+    }
+}
+
+impl Tensor3<i8> {
+    /// Fixed-point softmax along `axis`
+    ///
+    /// The output is quantized with scale 1/256 and zero point -128, as
+    /// expected by TFLite and TVM for int8 softmax.
+    pub fn softmax(&self, axis: Axis3, input: QuantParams) -> Tensor3<i8> {
+        // Input scale in Q16, the only floating point operation
+        let beta = (input.scale * 65536.0) as u32;
+        let src = self.to_chw_array();
+        let mut data = Array3::from_elem(src.dim(), 0i8);
+        let axis = match axis {
+            Axis3::Channels => Axis(0),
+            Axis3::Height => Axis(1),
+            Axis3::Width => Axis(2),
+        };
+        for (lane, mut out) in src.lanes(axis).into_iter().zip(data.lanes_mut(axis)) {
+            let max = lane.iter().copied().max().unwrap_or(0) as i32;
+            let exps: Vec<u32> = lane
+                .iter()
+                .map(|&x| exp_neg_q16(((max - x as i32) as u32).saturating_mul(beta)))
+                .collect();
+            let sum: u64 = exps.iter().map(|&e| e as u64).sum();
+            for (o, e) in out.iter_mut().zip(exps.iter()) {
+                let p = ((*e as u64 * 256 + sum / 2) / sum) as i32;
+                *o = (p - 128).clamp(i8::MIN as i32, i8::MAX as i32) as i8;
+            }
+        }
+        Tensor3::from_chw_array(data, self.order())
+    }
+}
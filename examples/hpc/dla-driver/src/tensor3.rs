@@ -23,6 +23,19 @@ impl Order3 {
             Order3::WCH => [2, 0, 1],
         }
     }
+
+    /// Inverse of `into_position`
+    pub(crate) fn from_position(position: [usize; 3]) -> Option<Self> {
+        match position {
+            [0, 1, 2] => Some(Order3::CHW),
+            [0, 2, 1] => Some(Order3::CWH),
+            [1, 2, 0] => Some(Order3::HWC),
+            [1, 0, 2] => Some(Order3::HCW),
+            [2, 1, 0] => Some(Order3::WHC),
+            [2, 0, 1] => Some(Order3::WCH),
+            _ => None,
+        }
+    }
 }
 
 impl TryFrom<&str> for Order3 {
@@ -279,10 +292,169 @@ impl<T: Clone> Tensor3<T> {
             return self.to_buffer();
         }
 
+        if let Some(view) = self.view() {
+            return view.to_buffer_with_order(order);
+        }
+
         let mut data = self.clone();
         data.permute(order);
         data.to_buffer()
     }
+
+    /// Borrows the tensor as a [Tensor3View] without copying. Returns `None`
+    /// if the data is not contiguous in memory, e.g., after slicing with steps.
+    pub fn view(&self) -> Option<Tensor3View<'_, T>> {
+        let data = self.data.as_slice_memory_order()?;
+        let position = self.order.into_position();
+        let mut dims = [0; 3];
+        let mut strides = [0; 3];
+        for (axis, &logical) in position.iter().enumerate() {
+            dims[logical] = self.data.shape()[axis];
+            strides[logical] = usize::try_from(self.data.strides()[axis]).ok()?;
+        }
+        Some(Tensor3View {
+            data,
+            dims,
+            strides,
+            order: self.order,
+        })
+    }
+}
+
+/// Borrowed 3D tensor over a flat buffer
+///
+/// Elements are located with a stride per logical axis, so views can describe
+/// any [Order3] as well as sub-tensors of a larger buffer without copying.
+#[derive(Clone, Copy, Debug)]
+pub struct Tensor3View<'a, T> {
+    data: &'a [T],
+    /// Dimensions in CHW
+    dims: [usize; 3],
+    /// Strides in elements in CHW
+    strides: [usize; 3],
+    order: Order3,
+}
+
+impl<'a, T: Clone> Tensor3View<'a, T> {
+    /// Creates a view over a contiguous buffer in the given order
+    pub fn new(
+        channels: usize,
+        height: usize,
+        width: usize,
+        data: &'a [T],
+        order: Order3,
+    ) -> Result<Self, &'static str> {
+        if data.len() != channels * height * width {
+            return Err("Data buffer size does not match specified dimensions");
+        }
+        let dims = [channels, height, width];
+        let mut strides = [0; 3];
+        let mut stride = 1;
+        for &logical in order.into_position().iter().rev() {
+            strides[logical] = stride;
+            stride *= dims[logical];
+        }
+        Ok(Tensor3View {
+            data,
+            dims,
+            strides,
+            order,
+        })
+    }
+
+    /// Creates a view with explicit strides for channel, row and column.
+    /// `order` is the order the strides iterate the buffer in.
+    pub fn from_strides(
+        channels: usize,
+        height: usize,
+        width: usize,
+        data: &'a [T],
+        strides: [usize; 3],
+        order: Order3,
+    ) -> Result<Self, &'static str> {
+        let dims = [channels, height, width];
+        if dims.contains(&0) {
+            return Err("Dimensions must be non-zero");
+        }
+        let last = dims
+            .iter()
+            .zip(strides.iter())
+            .map(|(d, s)| (d - 1) * s)
+            .sum::<usize>();
+        if last >= data.len() {
+            return Err("Strides reach past the end of the data buffer");
+        }
+        Ok(Tensor3View {
+            data,
+            dims,
+            strides,
+            order,
+        })
+    }
+
+    pub fn channels(&self) -> usize {
+        self.dims[0]
+    }
+
+    pub fn height(&self) -> usize {
+        self.dims[1]
+    }
+
+    pub fn width(&self) -> usize {
+        self.dims[2]
+    }
+
+    /// Get the number of elements in the view
+    pub fn get_size(&self) -> usize {
+        self.dims.iter().product()
+    }
+
+    /// Gets the order of the underlying buffer
+    pub fn order(&self) -> Order3 {
+        self.order
+    }
+
+    /// Returns a reference to the element at the specified position
+    pub fn get(&self, channel: usize, row: usize, col: usize) -> Option<&'a T> {
+        if channel >= self.dims[0] || row >= self.dims[1] || col >= self.dims[2] {
+            return None;
+        }
+        self.data
+            .get(channel * self.strides[0] + row * self.strides[1] + col * self.strides[2])
+    }
+
+    /// Narrows the view to the given range of channels without copying
+    pub fn slice_channels(&self, c_range: core::ops::Range<usize>) -> Tensor3View<'a, T> {
+        assert!(
+            c_range.start < c_range.end && c_range.end <= self.dims[0],
+            "Channel range out of bounds"
+        );
+        Tensor3View {
+            data: &self.data[c_range.start * self.strides[0]..],
+            dims: [c_range.len(), self.dims[1], self.dims[2]],
+            strides: self.strides,
+            order: self.order,
+        }
+    }
+
+    /// Iterates the elements in the given order without allocating
+    pub fn iter_with_order(&self, order: Order3) -> impl Iterator<Item = T> + 'a {
+        let [a, b, c] = order.into_position();
+        let (data, dims, strides) = (self.data, self.dims, self.strides);
+        (0..dims[a]).flat_map(move |i| {
+            (0..dims[b]).flat_map(move |j| {
+                (0..dims[c])
+                    .map(move |k| data[i * strides[a] + j * strides[b] + k * strides[c]].clone())
+            })
+        })
+    }
+
+    /// Copies the view to a linear buffer in the given order
+    pub fn to_buffer_with_order(&self, order: Order3) -> Vec<T> {
+        let mut buffer = Vec::with_capacity(self.get_size());
+        buffer.extend(self.iter_with_order(order));
+        buffer
+    }
 }
 
 pub fn rescale(
@@ -1,3 +1,4 @@
+use crate::tensor3::{Order3, Tensor3View};
 use alloc::vec::*;
 use core::ffi::c_char;
 use ndarray::{s, Array, Array4};
@@ -371,6 +372,10 @@ impl<T: Clone> Tensor4<T> {
             return self.to_buffer();
         }
 
+        if let Some(view) = self.view() {
+            return view.to_buffer_with_order(order);
+        }
+
         // NOTE:(20240925 vaino-waltteri.granat@tuni.fi) TVM order fix
         if self.order == Order4::HWCK && order == Order4::HWKC {
             return self.tvm_layout_to_headsail();
@@ -381,6 +386,25 @@ impl<T: Clone> Tensor4<T> {
         data.to_buffer()
     }
 
+    /// Borrows the tensor as a [Tensor4View] without copying. Returns `None`
+    /// if the data is not contiguous in memory, e.g., after slicing with steps.
+    pub fn view(&self) -> Option<Tensor4View<'_, T>> {
+        let data = self.data.as_slice_memory_order()?;
+        let position = self.order.into_position();
+        let mut dims = [0; 4];
+        let mut strides = [0; 4];
+        for (axis, &logical) in position.iter().enumerate() {
+            dims[logical] = self.data.shape()[axis];
+            strides[logical] = usize::try_from(self.data.strides()[axis]).ok()?;
+        }
+        Some(Tensor4View {
+            data,
+            dims,
+            strides,
+            order: self.order,
+        })
+    }
+
     /// Convert HWIO (HWCK) order to HWOI (HWKC) for headsail
     pub fn tvm_layout_to_headsail(&self) -> Vec<T> {
         let data = self.to_buffer();
@@ -405,3 +429,278 @@ impl<T: Clone> Tensor4<T> {
         hwoi_flat
     }
 }
+
+/// Borrowed 4D tensor over a flat buffer
+///
+/// Elements are located with a stride per logical axis, so views can describe
+/// any [Order4] as well as sub-tensors of a larger buffer without copying.
+#[derive(Clone, Copy, Debug)]
+pub struct Tensor4View<'a, T> {
+    data: &'a [T],
+    /// Dimensions in KCHW
+    dims: [usize; 4],
+    /// Strides in elements in KCHW
+    strides: [usize; 4],
+    order: Order4,
+}
+
+impl<'a, T: Clone> Tensor4View<'a, T> {
+    /// Creates a view over a contiguous buffer in the given order
+    pub fn new(
+        kernels: usize,
+        channels: usize,
+        height: usize,
+        width: usize,
+        data: &'a [T],
+        order: Order4,
+    ) -> Result<Self, &'static str> {
+        if data.len() != kernels * channels * height * width {
+            return Err("Data buffer size does not match specified dimensions");
+        }
+        let dims = [kernels, channels, height, width];
+        let mut strides = [0; 4];
+        let mut stride = 1;
+        for &logical in order.into_position().iter().rev() {
+            strides[logical] = stride;
+            stride *= dims[logical];
+        }
+        Ok(Tensor4View {
+            data,
+            dims,
+            strides,
+            order,
+        })
+    }
+
+    /// Creates a view with explicit strides for kernel, channel, row and
+    /// column. `order` is the order the strides iterate the buffer in.
+    pub fn from_strides(
+        kernels: usize,
+        channels: usize,
+        height: usize,
+        width: usize,
+        data: &'a [T],
+        strides: [usize; 4],
+        order: Order4,
+    ) -> Result<Self, &'static str> {
+        let dims = [kernels, channels, height, width];
+        if dims.contains(&0) {
+            return Err("Dimensions must be non-zero");
+        }
+        let last = dims
+            .iter()
+            .zip(strides.iter())
+            .map(|(d, s)| (d - 1) * s)
+            .sum::<usize>();
+        if last >= data.len() {
+            return Err("Strides reach past the end of the data buffer");
+        }
+        Ok(Tensor4View {
+            data,
+            dims,
+            strides,
+            order,
+        })
+    }
+
+    pub fn kernels(&self) -> usize {
+        self.dims[0]
+    }
+
+    pub fn channels(&self) -> usize {
+        self.dims[1]
+    }
+
+    pub fn height(&self) -> usize {
+        self.dims[2]
+    }
+
+    pub fn width(&self) -> usize {
+        self.dims[3]
+    }
+
+    /// Get the number of elements in the view
+    pub fn get_size(&self) -> usize {
+        self.dims.iter().product()
+    }
+
+    /// Gets the order of the underlying buffer
+    pub fn order(&self) -> Order4 {
+        self.order
+    }
+
+    /// Returns a reference to the element at the specified position
+    pub fn get(&self, kernel: usize, channel: usize, row: usize, col: usize) -> Option<&'a T> {
+        let index = [kernel, channel, row, col];
+        if index.iter().zip(self.dims.iter()).any(|(i, d)| i >= d) {
+            return None;
+        }
+        self.data.get(
+            index
+                .iter()
+                .zip(self.strides.iter())
+                .map(|(i, s)| i * s)
+                .sum::<usize>(),
+        )
+    }
+
+    /// Borrows one item of a batch of activations without copying
+    ///
+    /// When a Tensor4 holds activations, the kernel axis is the batch axis,
+    /// i.e., NCHW is stored as [Order4::KCHW] and NHWC as [Order4::KHWC].
+    pub fn batch_item(&self, n: usize) -> Option<Tensor3View<'a, T>> {
+        if n >= self.dims[0] {
+            return None;
+        }
+        let mut position = [0; 3];
+        let mut i = 0;
+        for &logical in self.order.into_position().iter() {
+            if logical != 0 {
+                position[i] = logical - 1;
+                i += 1;
+            }
+        }
+        Tensor3View::from_strides(
+            self.dims[1],
+            self.dims[2],
+            self.dims[3],
+            &self.data[n * self.strides[0]..],
+            [self.strides[1], self.strides[2], self.strides[3]],
+            Order3::from_position(position)?,
+        )
+        .ok()
+    }
+
+    /// Narrows the view to the given range of channels without copying
+    pub fn slice_channels(&self, c_range: core::ops::Range<usize>) -> Tensor4View<'a, T> {
+        assert!(
+            c_range.start < c_range.end && c_range.end <= self.dims[1],
+            "Channel range out of bounds"
+        );
+        Tensor4View {
+            data: &self.data[c_range.start * self.strides[1]..],
+            dims: [self.dims[0], c_range.len(), self.dims[2], self.dims[3]],
+            strides: self.strides,
+            order: self.order,
+        }
+    }
+
+    /// Iterates the elements in the given order without allocating
+    pub fn iter_with_order(&self, order: Order4) -> impl Iterator<Item = T> + 'a {
+        let [a, b, c, d] = order.into_position();
+        let (data, dims, strides) = (self.data, self.dims, self.strides);
+        (0..dims[a]).flat_map(move |i| {
+            (0..dims[b]).flat_map(move |j| {
+                (0..dims[c]).flat_map(move |k| {
+                    (0..dims[d]).map(move |l| {
+                        data[i * strides[a] + j * strides[b] + k * strides[c] + l * strides[d]]
+                            .clone()
+                    })
+                })
+            })
+        })
+    }
+
+    /// Copies the view to a linear buffer in the given order
+    pub fn to_buffer_with_order(&self, order: Order4) -> Vec<T> {
+        let mut buffer = Vec::with_capacity(self.get_size());
+        buffer.extend(self.iter_with_order(order));
+        buffer
+    }
+}
+
+/// Kernels packed into the HWKC layout DLA reads them in. Packing once and
+/// reusing the result avoids permuting the weights on every layer call.
+#[derive(Clone, Debug)]
+pub struct PackedKernels {
+    kernels: usize,
+    channels: usize,
+    height: usize,
+    width: usize,
+    data: Vec<i8>,
+}
+
+impl PackedKernels {
+    /// Packs `kernels` into HWKC
+    pub fn new(kernels: &Tensor4<i8>) -> Self {
+        PackedKernels {
+            kernels: kernels.kernels(),
+            channels: kernels.channels(),
+            height: kernels.height(),
+            width: kernels.width(),
+            data: kernels.to_buffer_with_order(Order4::HWKC),
+        }
+    }
+
+    /// Packs borrowed kernels into HWKC
+    pub fn from_view(kernels: &Tensor4View<'_, i8>) -> Self {
+        PackedKernels {
+            kernels: kernels.kernels(),
+            channels: kernels.channels(),
+            height: kernels.height(),
+            width: kernels.width(),
+            data: kernels.to_buffer_with_order(Order4::HWKC),
+        }
+    }
+
+    /// Wraps a buffer that is already in HWKC order
+    pub fn from_hwkc_buffer(
+        kernels: usize,
+        channels: usize,
+        height: usize,
+        width: usize,
+        data: Vec<i8>,
+    ) -> Result<Self, &'static str> {
+        if data.len() != kernels * channels * height * width {
+            return Err("Data buffer size does not match specified dimensions");
+        }
+        Ok(PackedKernels {
+            kernels,
+            channels,
+            height,
+            width,
+            data,
+        })
+    }
+
+    pub fn kernels(&self) -> usize {
+        self.kernels
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the number of elements in the packed buffer
+    pub fn get_size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Packed HWKC buffer
+    pub fn as_slice(&self) -> &[i8] {
+        &self.data
+    }
+
+    /// Borrows the packed kernels as a [Tensor4View]
+    pub fn view(&self) -> Tensor4View<'_, i8> {
+        Tensor4View {
+            data: &self.data,
+            dims: [self.kernels, self.channels, self.height, self.width],
+            strides: [
+                self.channels,
+                1,
+                self.width * self.kernels * self.channels,
+                self.kernels * self.channels,
+            ],
+            order: Order4::HWKC,
+        }
+    }
+}
@@ -84,8 +84,10 @@ pub fn get_banks_for_layer(
     let kernel_bank = input_bank + num_input_banks;
     let output_bank = kernel_bank + num_kernel_banks;
 
-    let bias_bank =
-        Some((MEMORY_BANK_BASE_ADDR + (output_bank + num_output_banks).offset()) as u32);
+    // No bias address if the output reaches the last bank
+    let bias_bank = MemoryBank::try_from((usize::from(output_bank) + num_output_banks) as u32)
+        .ok()
+        .map(|bank| (MEMORY_BANK_BASE_ADDR + bank.offset()) as u32);
     (input_bank, kernel_bank, output_bank, bias_bank)
 }
 
@@ -127,3 +129,49 @@ pub fn optimal_pp_bias_heuristic(bias: &[i16]) -> u32 {
     }
     pp
 }
+
+/// 32-bit bias scaled down to the 16 bits supported by DLA
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScaledBias {
+    pub bias: Vec<i16>,
+    /// Right shift applied to the bias. The accumulator must be shifted by the
+    /// same amount with `mac_clip` to stay in the scale of the bias.
+    pub shift: u32,
+    /// Largest rounding error of a single bias, in units of the 32-bit bias
+    pub max_error: u32,
+}
+
+/// Scales bias down by `shift`, rounding to nearest and saturating to 16 bits
+pub fn scale_bias(bias: &[i32], shift: u32) -> Vec<i16> {
+    let half = (1i64 << shift) >> 1;
+    bias.iter()
+        .map(|&b| ((b as i64 + half) >> shift).clamp(i16::MIN as i64, i16::MAX as i64) as i16)
+        .collect()
+}
+
+/// Scales a 32-bit bias, e.g., from TVM, into 16 bits without saturating
+///
+/// Picks the smallest shift of at least `min_shift` that brings every bias
+/// into range and reports the precision lost to rounding. `min_shift` is
+/// usually the `mac_clip` of the layer and must be at most [crate::MAX_MAC_CLIP].
+pub fn fit_bias_i32(bias: &[i32], min_shift: u32) -> ScaledBias {
+    let half = |shift: u32| (1i64 << shift) >> 1;
+    let fits = |shift: u32| {
+        bias.iter()
+            .all(|&b| i16::try_from((b as i64 + half(shift)) >> shift).is_ok())
+    };
+    // Any i32 fits in 16 bits after a shift of 17
+    let shift = (min_shift..).find(|&shift| fits(shift)).unwrap();
+    let scaled = scale_bias(bias, shift);
+    let max_error = bias
+        .iter()
+        .zip(scaled.iter())
+        .map(|(&b, &s)| (b as i64).abs_diff((s as i64) << shift) as u32)
+        .max()
+        .unwrap_or(0);
+    ScaledBias {
+        bias: scaled,
+        shift,
+        max_error,
+    }
+}
@@ -9,7 +9,12 @@
 
 use core::arch::asm;
 
-use headsail_bsp::{apb_uart::ApbUart, rt::entry, sysctrl::soc_ctrl, unmask_u32};
+use headsail_bsp::{
+    apb_uart::ApbUart,
+    rt::entry,
+    sysctrl::soc_ctrl::{self, Subsystem},
+    unmask_u32,
+};
 use hello_sysctrl::{print_example_name, NOPS_PER_SEC};
 
 #[entry]
@@ -18,17 +23,8 @@ fn main() -> ! {
     soc_ctrl::periph_clk_div_set(0);
 
     // Enable interconnect and TLP
-    let icn_bit = 1 << 5;
-    let tlp_bit = 1 << 8;
-    soc_ctrl::ss_enable(icn_bit | tlp_bit);
-
-    // Configure ICN clocks
-    let conf_val = 0b1001 << 8;
-    soc_ctrl::clk2_mask(conf_val);
-
-    // Configure TLP clocks
-    let conf_val = 0b1001;
-    soc_ctrl::clk3_mask(conf_val);
+    soc_ctrl::enable(Subsystem::Icn);
+    soc_ctrl::enable(Subsystem::Tlp);
 
     // Disable GPIO behavior for UART pins
     const PAD_CONF_UART0_TX: usize = 0xfff0_7064;
@@ -0,0 +1,72 @@
+//! Boots HPC from the image in SPI NOR flash
+//!
+//! On the VP, create a flash image with `scripts/mk_flash_image.py` and run
+//! with `scripts/resc/2_run_sysctrl_flash.resc`.
+//!
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use headsail_bsp::{
+    pac::Sysctrl,
+    rt::entry,
+    sysctrl::{
+        hpc_boot::{BootError, BootStep},
+        soc_ctrl,
+        spi_flash::SpiFlash,
+        udma::Udma,
+    },
+    ufmt,
+};
+use hello_sysctrl::{print_example_name, sprint, sprintln};
+
+fn report(step: BootStep, res: Result<(), BootError>) {
+    sprint!("> {}... ", step.name());
+    match res {
+        Ok(()) => sprintln!("done."),
+        Err(BootError::InvalidImage) => sprintln!("failed: invalid image in flash."),
+        Err(BootError::Unreachable(addr)) => {
+            sprintln!("failed: {:#x} is not reachable from SysCtrl.", addr)
+        }
+        Err(_) => sprintln!("failed."),
+    }
+}
+
+#[entry]
+fn main() -> ! {
+    // These lines are necessary to initialize uDMA UART prints for sprint-macro
+    soc_ctrl::periph_clk_div_set(0);
+    hello_sysctrl::UdmaUart::init();
+    print_example_name!();
+
+    let sysctrl = unsafe { Sysctrl::steal() };
+    let udma = Udma(sysctrl.udma());
+    let pads = soc_ctrl::Pads::take().unwrap();
+    let mut flash = SpiFlash::new(udma.split().spim.enable(
+        pads.p2.into_spim_sck(),
+        pads.p3.into_spim_cs0(),
+        pads.p4.into_spim_sdio0(),
+        pads.p5.into_spim_sdio1(),
+    ));
+
+    match flash.boot_header() {
+        Some(header) => sprintln!(
+            "Image: {} bytes to {:#x}, entry {:#x}",
+            header.image_len,
+            header.load_addr,
+            header.entry
+        ),
+        None => sprintln!("No boot header in flash"),
+    }
+
+    if flash.boot_hpc(report).is_ok() {
+        sprintln!("HPC booted from flash. Looping in place.");
+    } else {
+        sprintln!("Booting HPC from flash failed. Looping in place.");
+    }
+    loop {
+        continue;
+    }
+}
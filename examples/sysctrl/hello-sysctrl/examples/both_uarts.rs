@@ -49,25 +49,30 @@ fn main() -> ! {
 
     // Set the bit length, enable TX, set clk_div
     let clk_div: u16 = (soc_freq / baud) as u16;
-    let mut uart = udma.split().uart.enable(|w| {
-        unsafe {
-            w
-                // Use this if using parity bit
-                .parity_ena()
-                .bit(false)
-                .bit_length()
-                .bits(0b11)
-                // Stop bit?
-                .stop_bits()
-                .bit(false)
-                .tx_ena()
-                .bit(true)
-                .rx_ena()
-                .bit(true)
-                .clkdiv()
-                .bits(clk_div)
-        }
-    });
+    let pads = soc_ctrl::Pads::take().unwrap();
+    let mut uart = udma.split().uart.enable(
+        pads.p0.into_udma_uart_tx(),
+        pads.p1.into_udma_uart_rx(),
+        |w| {
+            unsafe {
+                w
+                    // Use this if using parity bit
+                    .parity_ena()
+                    .bit(false)
+                    .bit_length()
+                    .bits(0b11)
+                    // Stop bit?
+                    .stop_bits()
+                    .bit(false)
+                    .tx_ena()
+                    .bit(true)
+                    .rx_ena()
+                    .bit(true)
+                    .clkdiv()
+                    .bits(clk_div)
+            }
+        },
+    );
 
     uart_init(soc_freq, baud);
 
@@ -0,0 +1,65 @@
+//! Mirrors a button on pad 8 to the LED on pad 9 and counts presses using the
+//! GPIO interrupt
+//!
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use headsail_bsp::{
+    riscv,
+    rt::entry,
+    sysctrl::{
+        gpio::{self, Edge, GPIO_IRQ},
+        soc_ctrl::{self, Pull},
+    },
+    ufmt,
+};
+use hello_sysctrl::{print_example_name, sprint, sprintln};
+
+/// Written by the interrupt only
+static mut PRESSES: u32 = 0;
+
+fn on_press() {
+    unsafe { PRESSES += 1 };
+}
+
+#[entry]
+fn main() -> ! {
+    // These lines are necessary to initialize uDMA UART prints for sprint-macro
+    soc_ctrl::periph_clk_div_set(0);
+    hello_sysctrl::UdmaUart::init();
+    print_example_name!();
+
+    let pads = unsafe { soc_ctrl::Pads::steal() };
+    let mut button = pads.p8.into_gpio().into_input();
+    let mut led = pads.p9.into_gpio().into_output();
+
+    // Button pulls the pad low when pressed, the pad cell pulls it up otherwise
+    button.set_pull(Pull::Enabled);
+    button.listen(Edge::Falling, on_press);
+    unsafe { riscv::interrupt::enable() };
+
+    let mut reported = 0;
+    loop {
+        if button.is_low() {
+            led.set_high();
+        } else {
+            led.set_low();
+        }
+
+        let presses = riscv::interrupt::free(|| unsafe { PRESSES });
+        if presses != reported {
+            reported = presses;
+            sprintln!("presses: {}", presses);
+        }
+    }
+}
+
+#[export_name = "DefaultHandler"]
+fn default_handler() {
+    if riscv::register::mcause::read().code() == GPIO_IRQ {
+        gpio::on_gpio_interrupt();
+    }
+}
@@ -0,0 +1,96 @@
+//! Lists the root directory of the FAT volume on an SD card and prints
+//! `HELLO.TXT`
+//!
+//! On the VP, run with `scripts/resc/2_run_sysctrl_sd.resc`.
+//!
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use headsail_bsp::{
+    pac::Sysctrl,
+    rt::entry,
+    sd::{
+        self,
+        embedded_sdmmc::{Mode, VolumeIdx, VolumeManager},
+        FixedTime,
+    },
+    sysctrl::{soc_ctrl, time, udma::Udma},
+    ufmt,
+};
+use hello_sysctrl::{print_example_name, sprint, sprintln};
+
+#[entry]
+fn main() -> ! {
+    // These lines are necessary to initialize uDMA UART prints for sprint-macro
+    soc_ctrl::periph_clk_div_set(0);
+    hello_sysctrl::UdmaUart::init();
+    print_example_name!();
+
+    time::init();
+
+    let sysctrl = unsafe { Sysctrl::steal() };
+    let udma = Udma(sysctrl.udma());
+    let pads = soc_ctrl::Pads::take().unwrap();
+    let mut spim = udma.split().spim.enable(
+        pads.p2.into_spim_sck(),
+        pads.p3.into_spim_cs0(),
+        pads.p4.into_spim_sdio0(),
+        pads.p5.into_spim_sdio1(),
+    );
+    spim.send_idle_clocks(10);
+
+    let card = sd::sd_card(spim);
+    match card.num_bytes() {
+        Ok(size) => sprintln!("Card size: {} bytes", size),
+        Err(_) => {
+            sprintln!("SD card initialization failed");
+            halt();
+        }
+    }
+
+    let volume_mgr = VolumeManager::new(card, FixedTime);
+    let Ok(volume) = volume_mgr.open_volume(VolumeIdx(0)) else {
+        sprintln!("Failed to open volume 0");
+        halt();
+    };
+    let Ok(root) = volume.open_root_dir() else {
+        sprintln!("Failed to open root directory");
+        halt();
+    };
+
+    sprintln!("Root directory:");
+    let listed = root.iterate_dir(|entry| {
+        let base = core::str::from_utf8(entry.name.base_name()).unwrap_or("?");
+        let ext = core::str::from_utf8(entry.name.extension()).unwrap_or("?");
+        sprintln!("  {}.{} ({} bytes)", base, ext, entry.size);
+    });
+    if listed.is_err() {
+        sprintln!("Failed to list root directory");
+    }
+
+    match root.open_file_in_dir("HELLO.TXT", Mode::ReadOnly) {
+        Ok(file) => {
+            let mut buf = [0u8; 64];
+            while !file.is_eof() {
+                let Ok(n) = file.read(&mut buf) else {
+                    sprintln!("Read failed");
+                    break;
+                };
+                sprint!("{}", core::str::from_utf8(&buf[..n]).unwrap_or("?"));
+            }
+            sprintln!("");
+        }
+        Err(_) => sprintln!("HELLO.TXT not found"),
+    }
+
+    halt();
+}
+
+fn halt() -> ! {
+    loop {
+        continue;
+    }
+}
@@ -0,0 +1,62 @@
+//! Erases, programs and reads back a sector of the SPI NOR flash
+//!
+//! On the VP, run with `scripts/resc/2_run_sysctrl_flash.resc`.
+//!
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use headsail_bsp::{
+    pac::Sysctrl,
+    rt::entry,
+    sysctrl::{soc_ctrl, spi_flash::SpiFlash, udma::Udma},
+    ufmt,
+};
+use hello_sysctrl::{print_example_name, sprint, sprintln};
+
+/// Last sector of a 16 MiB flash, clear of the boot image
+const TEST_ADDR: u32 = 0xFF_F000;
+
+#[entry]
+fn main() -> ! {
+    // These lines are necessary to initialize uDMA UART prints for sprint-macro
+    soc_ctrl::periph_clk_div_set(0);
+    hello_sysctrl::UdmaUart::init();
+    print_example_name!();
+
+    let sysctrl = unsafe { Sysctrl::steal() };
+    let udma = Udma(sysctrl.udma());
+    let pads = soc_ctrl::Pads::take().unwrap();
+    let mut flash = SpiFlash::new(udma.split().spim.enable(
+        pads.p2.into_spim_sck(),
+        pads.p3.into_spim_cs0(),
+        pads.p4.into_spim_sdio0(),
+        pads.p5.into_spim_sdio1(),
+    ));
+
+    let [manufacturer, ty, capacity] = flash.jedec_id();
+    sprintln!("JEDEC ID: {:#x} {:#x} {:#x}", manufacturer, ty, capacity);
+
+    sprint!("Erase sector {:#x}... ", TEST_ADDR);
+    flash.erase_sector(TEST_ADDR);
+    sprintln!("done.");
+
+    let data: [u8; 8] = [0x01, 0x42, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+    sprint!("Program {} bytes... ", data.len());
+    flash.write(TEST_ADDR, &data);
+    sprintln!("done.");
+
+    let mut read_back = [0u8; 8];
+    flash.read(TEST_ADDR, &mut read_back);
+    if read_back == data {
+        sprintln!("SPI flash test passed");
+    } else {
+        sprintln!("SPI flash test failed");
+    }
+
+    loop {
+        continue;
+    }
+}
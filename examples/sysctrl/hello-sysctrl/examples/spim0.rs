@@ -28,7 +28,13 @@ fn main() -> ! {
     // Split uDMA into sub-drivers for each peripheral
     let udma_periphs = udma.split();
 
-    let mut spim = udma_periphs.spim.enable();
+    let pads = soc_ctrl::Pads::take().unwrap();
+    let mut spim = udma_periphs.spim.enable(
+        pads.p2.into_spim_sck(),
+        pads.p3.into_spim_cs0(),
+        pads.p4.into_spim_sdio0(),
+        pads.p5.into_spim_sdio1(),
+    );
     sprintln!("SPI enabled!");
 
     let tx_data: [u8; 8] = [0x01, 0x42, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
@@ -6,7 +6,13 @@
 
 use core::arch::asm;
 
-use headsail_bsp::{self as bsp, pac::Sysctrl, rt::entry, sysctrl::soc_ctrl, ufmt};
+use headsail_bsp::{
+    self as bsp,
+    pac::Sysctrl,
+    rt::entry,
+    sysctrl::soc_ctrl::{self, Subsystem},
+    ufmt,
+};
 use hello_sysctrl::sysctrl_print;
 
 struct UdmaUart;
@@ -47,17 +53,8 @@ macro_rules! sprintln {
 #[entry]
 fn main() -> ! {
     // Enable interconnect and TLP
-    let icn_bit = 1 << 5;
-    let tlp_bit = 1 << 8;
-    soc_ctrl::ss_enable(icn_bit | tlp_bit);
-
-    // Configure ICN clocks
-    let conf_val = 0b1001 << 8;
-    soc_ctrl::clk2_mask(conf_val);
-
-    // Configure TLP clocks
-    let conf_val = 0b1001;
-    soc_ctrl::clk3_mask(conf_val);
+    soc_ctrl::enable(Subsystem::Icn);
+    soc_ctrl::enable(Subsystem::Tlp);
 
     let (soc_freq, baud) = (30_000_000, 9600_u32);
 
@@ -0,0 +1,55 @@
+//! Reads the chip ID of a BMP180 sensor over uDMA I2C
+//!
+//! On the VP, run with `scripts/resc/2_run_sysctrl_i2c.resc`, which attaches a
+//! BMP180 to `udma_i2c0`.
+//!
+//! | Date              | Status     | Changes   |
+//! | :-                | :-:        | :-        |
+//! | 2026-10-19        | *Untested* |           |
+#![no_std]
+#![no_main]
+
+use embedded_hal::i2c::I2c;
+use headsail_bsp::{
+    pac::Sysctrl,
+    rt::entry,
+    sysctrl::{soc_ctrl, udma::Udma},
+    ufmt,
+};
+use hello_sysctrl::{print_example_name, sprintln};
+
+const BMP180_ADDR: u8 = 0x77;
+const BMP180_REG_ID: u8 = 0xD0;
+const BMP180_ID: u8 = 0x55;
+
+#[entry]
+fn main() -> ! {
+    // These lines are necessary to initialize uDMA UART prints for sprint-macro
+    soc_ctrl::periph_clk_div_set(0);
+    hello_sysctrl::UdmaUart::init();
+    print_example_name!();
+
+    let sysctrl = unsafe { Sysctrl::steal() };
+    let udma = Udma(sysctrl.udma());
+
+    let pads = soc_ctrl::Pads::take().unwrap();
+    let (scl, sda) = (pads.p8.into_i2c_scl(), pads.p9.into_i2c_sda());
+    // 30 MHz / 300 = 100 kHz
+    let mut i2c = udma.split().i2c.enable(300, scl, sda);
+    sprintln!("I2C enabled!");
+
+    let mut id = [0u8];
+    i2c.write_read(BMP180_ADDR, &[BMP180_REG_ID], &mut id)
+        .unwrap();
+    sprintln!("BMP180 chip ID: {:#x}", id[0]);
+
+    if id[0] == BMP180_ID {
+        sprintln!("I2C test passed");
+    } else {
+        sprintln!("I2C test failed, expected {:#x}", BMP180_ID);
+    }
+
+    loop {
+        continue;
+    }
+}
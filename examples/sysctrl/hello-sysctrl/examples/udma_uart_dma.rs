@@ -0,0 +1,62 @@
+//! Prints over SysCtrl UART without blocking on the uDMA
+#![no_std]
+#![no_main]
+
+use core::arch::asm;
+
+use headsail_bsp::{
+    pac,
+    rt::entry,
+    sysctrl::{soc_ctrl, udma::Udma},
+    ufmt,
+};
+use hello_sysctrl::NOPS_PER_SEC;
+
+static MSG: &[u8] = b"Hello non-blocking uDMA UART\r\n";
+
+#[entry]
+fn main() -> ! {
+    let sysctrl = unsafe { pac::Sysctrl::steal() };
+    let udma = Udma(sysctrl.udma());
+
+    soc_ctrl::periph_clk_div_set(0);
+
+    // Set the bit length, enable TX, set clk_div
+    let (soc_freq, baud) = (30_000_000, 9600_u32);
+    let clk_div: u16 = (soc_freq / baud) as u16;
+    let pads = soc_ctrl::Pads::take().unwrap();
+    let mut uart = udma.split().uart.enable(
+        pads.p0.into_udma_uart_tx(),
+        pads.p1.into_udma_uart_rx(),
+        |w| unsafe {
+            w.parity_ena()
+                .bit(false)
+                .bit_length()
+                .bits(0b11)
+                .stop_bits()
+                .bit(false)
+                .tx_ena()
+                .bit(true)
+                .rx_ena()
+                .bit(true)
+                .clkdiv()
+                .bits(clk_div)
+        },
+    );
+
+    loop {
+        let transfer = uart.write_dma(MSG);
+
+        // The CPU is free while the uDMA is sending
+        let mut polls = 0u32;
+        while !transfer.is_done() {
+            polls += 1;
+        }
+        (_, uart) = transfer.wait();
+        ufmt::uwrite!(uart, "Polled {} times while sending\r\n", polls).unwrap();
+
+        for _ in 0..NOPS_PER_SEC {
+            unsafe { asm!("nop") };
+        }
+    }
+}
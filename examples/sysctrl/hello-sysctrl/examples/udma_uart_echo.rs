@@ -0,0 +1,52 @@
+//! Echoes bytes received over SysCtrl UART
+#![no_std]
+#![no_main]
+
+use headsail_bsp::{
+    pac,
+    rt::entry,
+    sysctrl::{soc_ctrl, udma::Udma},
+};
+
+/// Ring buffer the uDMA receives into
+static mut RX_RING: [u8; 64] = [0; 64];
+
+#[entry]
+fn main() -> ! {
+    let sysctrl = unsafe { pac::Sysctrl::steal() };
+    let udma = Udma(sysctrl.udma());
+
+    soc_ctrl::periph_clk_div_set(0);
+
+    // Set the bit length, enable TX & RX, set clk_div
+    let (soc_freq, baud) = (30_000_000, 9600_u32);
+    let clk_div: u16 = (soc_freq / baud) as u16;
+    let pads = soc_ctrl::Pads::take().unwrap();
+    let uart = udma.split().uart.enable(
+        pads.p0.into_udma_uart_tx(),
+        pads.p1.into_udma_uart_rx(),
+        |w| unsafe {
+            w.parity_ena()
+                .bit(false)
+                .bit_length()
+                .bits(0b11)
+                .stop_bits()
+                .bit(false)
+                .tx_ena()
+                .bit(true)
+                .rx_ena()
+                .bit(true)
+                .clkdiv()
+                .bits(clk_div)
+        },
+    );
+
+    let (mut uart, mut rx) = uart.start_rx(unsafe { &mut *core::ptr::addr_of_mut!(RX_RING) });
+    uart.write(b"Type something, it will be echoed back\r\n");
+
+    let mut buf = [0u8; 16];
+    loop {
+        let n = rx.read(&mut buf);
+        uart.write(&buf[..n]);
+    }
+}
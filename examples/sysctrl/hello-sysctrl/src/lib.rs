@@ -69,8 +69,10 @@ impl UdmaUart {
         // Set the bit length, enable TX, set clk_div
         let (soc_freq, baud) = (30_000_000, 9600_u32);
         let clk_div: u16 = (soc_freq / baud) as u16;
-        let _uart = udma.split().uart.enable(|w| {
-            unsafe {
+        let uart = udma.split().uart;
+        // SAFETY: the examples leave pads 0 and 1 to the UART
+        let _uart = unsafe {
+            uart.enable_unchecked(|w| {
                 w
                     // Use this if using parity bit
                     .parity_ena()
@@ -86,8 +88,8 @@ impl UdmaUart {
                     .bit(true)
                     .clkdiv()
                     .bits(clk_div)
-            }
-        });
+            })
+        };
     }
 }
 
@@ -0,0 +1,17 @@
+#!/bin/sh
+
+# Decodes defmt frames from the Renode UART pty with the ELF from $BIN or param $1
+#
+# Requires `defmt-print` (`cargo install defmt-print`). Start the VP first, e.g.,
+# with run_on_hpc.sh, which creates /tmp/uart0 for APB UART0.
+
+BIN=${BIN=$1}
+PTY=${PTY=${2:-/tmp/uart0}}
+if [ -z "$BIN" ]; then
+    echo "!! Pass in an ELF using \$BIN or \$1"
+    exit 1
+fi
+
+# Pass the frames through as is
+stty -F "$PTY" raw -echo
+defmt-print -e "$BIN" < "$PTY"
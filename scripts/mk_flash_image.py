@@ -0,0 +1,45 @@
+#!/usr/bin/env python3
+
+"""
+Create a SPI flash image that SysCtrl can boot HPC from, sa. `headsail_bsp::sysctrl::spi_flash`
+Usage:
+$ riscv64-unknown-elf-objcopy -O binary hello-hpc hello-hpc.bin
+$ python mk_flash_image.py hello-hpc.bin flash.bin --load-addr 0x10000
+"""
+import argparse
+import struct
+import zlib
+
+MAGIC = b"HSBT"
+HEADER_LEN = 32
+# Keep the image in a sector of its own so the header can be rewritten independently
+IMAGE_OFFSET = 0x1000
+
+def make_image(payload, load_addr, entry):
+    """Prefix `payload` with a boot header."""
+    header = MAGIC + struct.pack(
+        "<IIIQQ", IMAGE_OFFSET, len(payload), zlib.crc32(payload), load_addr, entry
+    )
+    assert len(header) == HEADER_LEN
+    padding = b"\xff" * (IMAGE_OFFSET - HEADER_LEN)
+    return header + padding + payload
+
+def main():
+    parser = argparse.ArgumentParser(description="Create a bootable SPI flash image for HPC")
+    parser.add_argument("payload", help="Raw binary to boot on HPC")
+    parser.add_argument("output", help="Flash image to write")
+    parser.add_argument("--load-addr", type=lambda x: int(x, 0), default=0x10000,
+                        help="HPC address to load the payload to (default: bootram)")
+    parser.add_argument("--entry", type=lambda x: int(x, 0), default=None,
+                        help="HPC address to start from (default: load address)")
+    args = parser.parse_args()
+
+    with open(args.payload, "rb") as f:
+        payload = f.read()
+    entry = args.load_addr if args.entry is None else args.entry
+
+    with open(args.output, "wb") as f:
+        f.write(make_image(payload, args.load_addr, entry))
+
+if __name__ == "__main__":
+    main()
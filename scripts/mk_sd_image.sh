@@ -0,0 +1,18 @@
+#!/usr/bin/env bash
+#
+# Create a FAT formatted SD card image, sa. `headsail_bsp::sd`
+# Requires mkfs.fat (dosfstools) and mcopy (mtools).
+# Usage:
+# $ ./mk_sd_image.sh sd.img HELLO.TXT weights.bin
+set -euo pipefail
+
+IMAGE=$1
+shift
+# 64 MiB, the smallest size that mkfs.fat formats as FAT32
+SIZE_MIB=64
+
+dd if=/dev/zero of="$IMAGE" bs=1M count=$SIZE_MIB status=none
+mkfs.fat -F 32 "$IMAGE" >/dev/null
+for file in "$@"; do
+    mcopy -i "$IMAGE" "$file" ::
+done